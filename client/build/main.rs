@@ -0,0 +1,36 @@
+//! Generates the client from the spec the server documents, so it can not
+//! drift from the api it calls.
+
+mod operations;
+mod types;
+
+use std::path::Path;
+use std::{env, fs};
+
+use serde_json::Value;
+use utoipa::OpenApi;
+
+use learning_management_system::api::Doc;
+
+fn main() {
+    let spec = serde_json::to_value(Doc::openapi()).unwrap();
+    let out = env::var("OUT_DIR").unwrap();
+    let mut types = types::Types::new(&spec["components"]["schemas"]);
+    let operations = operations::generate(&spec["paths"], &mut types);
+
+    fs::write(Path::new(&out).join("types.rs"), types.finish()).unwrap();
+    fs::write(Path::new(&out).join("operations.rs"), operations).unwrap();
+
+    println!("cargo:rerun-if-changed=build");
+}
+
+/// Render `text` as doc comment lines indented by `indent`.
+pub fn doc(text: Option<&Value>, indent: &str) -> String {
+    text.and_then(Value::as_str)
+        .map(|text| {
+            text.lines()
+                .map(|line| format!("{indent}/// {line}").trim_end().to_string() + "\n")
+                .collect()
+        })
+        .unwrap_or_default()
+}
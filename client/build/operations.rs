@@ -0,0 +1,144 @@
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use serde_json::{json, Map, Value};
+
+use crate::doc;
+use crate::types::Types;
+
+/// Methods of `Client` calling every operation in `paths`.
+pub fn generate(paths: &Value, types: &mut Types) -> String {
+    let mut code = String::from("impl Client {");
+
+    for (path, item) in paths.as_object().into_iter().flatten() {
+        for (method, operation) in item.as_object().into_iter().flatten() {
+            code.push('\n');
+            code.push_str(&generate_operation(path, method, operation, types));
+        }
+    }
+
+    code + "}\n"
+}
+
+fn generate_operation(path: &str, method: &str, operation: &Value, types: &mut Types) -> String {
+    let name = operation["operationId"].as_str().unwrap().to_snake_case();
+    let context = name.to_upper_camel_case();
+    let parameters = operation["parameters"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    let mut arguments = vec!["&self".to_string()];
+    let mut request = match path.contains('{') {
+        true => format!(
+            "self.request(Method::{}, &format!(\"{path}\"))",
+            method.to_uppercase()
+        ),
+        false => format!(
+            "self.request(Method::{}, \"{path}\")",
+            method.to_uppercase()
+        ),
+    };
+
+    for parameter in parameters
+        .iter()
+        .filter(|parameter| parameter["in"] == "path")
+    {
+        let parameter_name = parameter["name"].as_str().unwrap();
+        let rust = types.rust(
+            &parameter["schema"],
+            &format!("{context}{}", parameter_name.to_upper_camel_case()),
+            "types::",
+        );
+
+        arguments.push(format!("{parameter_name}: {rust}"));
+    }
+
+    let query: Vec<_> = parameters
+        .iter()
+        .filter(|parameter| parameter["in"] == "query")
+        .collect();
+
+    if !query.is_empty() {
+        let name = format!("{context}Query");
+
+        types.define(&name, &schema_of(&query));
+        arguments.push(format!("query: &types::{name}"));
+        request.push_str(".query(query)");
+    }
+
+    let body = &operation["requestBody"]["content"];
+
+    if let Some(schema) = body
+        .get("application/json")
+        .map(|content| &content["schema"])
+    {
+        let rust = types.rust(schema, &format!("{context}Request"), "types::");
+
+        arguments.push(format!("body: &{rust}"));
+        request.push_str(".json(body)");
+    } else if body.get("multipart/form-data").is_some() {
+        arguments.push("form: reqwest::multipart::Form".into());
+        request.push_str(".multipart(form)");
+    }
+
+    let (returns, read) = response(operation, &context, types);
+
+    format!(
+        "{}    pub async fn {name}({}) -> Result<{returns}, Error> {{
+        let request = {request};
+
+        self.{read}(request).await
+    }}
+",
+        doc(
+            operation.get("description").or(operation.get("summary")),
+            "    "
+        ),
+        arguments.join(", "),
+    )
+}
+
+/// What a successful call of `operation` answers with, along with the
+/// method of `Client` that reads it.
+fn response(operation: &Value, context: &str, types: &mut Types) -> (String, &'static str) {
+    let responses = operation["responses"]
+        .as_object()
+        .cloned()
+        .unwrap_or_default();
+    let success = responses
+        .iter()
+        .find(|(status, _)| status.starts_with('2'))
+        .map(|(_, response)| response);
+    let schema = success.and_then(|response| response["content"].get("application/json"));
+
+    match schema.map(|content| &content["schema"]) {
+        Some(schema) => (
+            types.rust(schema, &format!("{context}Response"), "types::"),
+            "json",
+        ),
+        None => ("Vec<u8>".into(), "bytes"),
+    }
+}
+
+/// The object whose fields are the query `parameters` of an operation.
+fn schema_of(parameters: &[&Value]) -> Value {
+    let mut properties = Map::new();
+    let mut required = vec![];
+
+    for parameter in parameters {
+        let name = parameter["name"].as_str().unwrap().to_string();
+        let mut schema = parameter["schema"].clone();
+
+        if let (Some(schema), Some(description)) =
+            (schema.as_object_mut(), parameter.get("description"))
+        {
+            schema.insert("description".into(), description.clone());
+        }
+
+        if parameter["required"] == true {
+            required.push(name.clone());
+        }
+
+        properties.insert(name, schema);
+    }
+
+    json!({ "type": "object", "properties": properties, "required": required })
+}
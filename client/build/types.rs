@@ -0,0 +1,296 @@
+use std::collections::BTreeSet;
+
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use serde_json::{json, Map, Value};
+
+use crate::doc;
+
+/// Words that have to be raw identifiers to name a field.
+const KEYWORDS: [&str; 8] = [
+    "type", "ref", "self", "match", "move", "static", "use", "where",
+];
+
+/// Rust types of the schemas in the spec, defined once each as they are
+/// first met.
+pub struct Types {
+    components: Map<String, Value>,
+    defined: BTreeSet<String>,
+    code: String,
+}
+
+impl Types {
+    pub fn new(components: &Value) -> Self {
+        let components = components.as_object().cloned().unwrap_or_default();
+        let mut types = Self {
+            components: components.clone(),
+            defined: BTreeSet::new(),
+            code: String::new(),
+        };
+
+        for (name, schema) in &components {
+            types.define(name, schema);
+        }
+
+        types
+    }
+
+    /// Name of the component `schema` is a copy of, as responses and query
+    /// parameters embed the schemas they use rather than refer to them.
+    pub fn component(&self, schema: &Value) -> Option<&str> {
+        let wanted = shape(schema);
+
+        self.components
+            .iter()
+            .find(|(_, component)| shape(component) == wanted)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Rust type of `schema` as seen from `module`, naming whatever inline
+    /// object or enum it has after `context`.
+    pub fn rust(&mut self, schema: &Value, context: &str, module: &str) -> String {
+        if let Some(reference) = schema["$ref"].as_str() {
+            return format!("{module}{}", reference.rsplit('/').next().unwrap());
+        }
+
+        if let Some(items) = schema["allOf"].as_array() {
+            if let [item] = items.as_slice() {
+                return self.rust(item, context, module);
+            }
+        }
+
+        let format = schema["format"].as_str().unwrap_or_default();
+        let unsigned = schema["minimum"]
+            .as_f64()
+            .is_some_and(|minimum| minimum >= 0.0);
+
+        match schema["type"].as_str() {
+            _ if schema["enum"].is_array()
+                || schema["allOf"].is_array()
+                || has_properties(schema) =>
+            {
+                let name = match self.component(schema) {
+                    Some(component) => component.to_string(),
+                    None => {
+                        self.define(context, schema);
+                        context.to_string()
+                    }
+                };
+
+                format!("{module}{name}")
+            }
+            Some("string") => match format {
+                "uuid" => "uuid::Uuid",
+                "date-time" => "chrono::DateTime<chrono::FixedOffset>",
+                "date" => "chrono::NaiveDate",
+                "binary" => "Vec<u8>",
+                _ => "String",
+            }
+            .into(),
+            Some("integer") => match (format, unsigned) {
+                ("int32", false) => "i32",
+                ("int32", true) => "u32",
+                (_, false) => "i64",
+                (_, true) => "u64",
+            }
+            .into(),
+            Some("number") if format == "float" => "f32".into(),
+            Some("number") => "f64".into(),
+            Some("boolean") => "bool".into(),
+            Some("array") => {
+                let item = self.rust(&schema["items"], &format!("{context}Item"), module);
+
+                format!("Vec<{item}>")
+            }
+            _ if schema["additionalProperties"].is_object() => {
+                let value = self.rust(
+                    &schema["additionalProperties"],
+                    &format!("{context}Value"),
+                    module,
+                );
+
+                format!("std::collections::HashMap<String, {value}>")
+            }
+            _ => "serde_json::Value".into(),
+        }
+    }
+
+    /// Define `name` as the struct, enum or alias `schema` describes.
+    pub fn define(&mut self, name: &str, schema: &Value) {
+        if !self.defined.insert(name.to_string()) {
+            return;
+        }
+
+        let code = if let Some(values) = schema["enum"].as_array() {
+            enumeration(name, schema, values)
+        } else if let Some(items) = schema["allOf"].as_array() {
+            let merged = self.merge(items);
+
+            self.structure(name, &merged)
+        } else if has_properties(schema) {
+            self.structure(name, schema)
+        } else {
+            let alias = self.rust(schema, name, "");
+
+            format!(
+                "{}pub type {name} = {alias};\n",
+                doc(schema.get("description"), "")
+            )
+        };
+
+        self.code.push('\n');
+        self.code.push_str(&code);
+    }
+
+    pub fn finish(self) -> String {
+        format!("use serde::{{Deserialize, Serialize}};\n{}", self.code)
+    }
+
+    /// The one object with every property of the objects in `items`.
+    fn merge(&self, items: &[Value]) -> Value {
+        let mut properties = Map::new();
+        let mut required = vec![];
+
+        for item in items {
+            let item = match item["$ref"].as_str() {
+                Some(reference) => &self.components[reference.rsplit('/').next().unwrap()],
+                None => item,
+            };
+            let item = match item["allOf"].as_array() {
+                Some(items) => self.merge(items),
+                None => item.clone(),
+            };
+
+            properties.extend(item["properties"].as_object().cloned().unwrap_or_default());
+            required.extend(item["required"].as_array().cloned().unwrap_or_default());
+        }
+
+        json!({ "type": "object", "properties": properties, "required": required })
+    }
+
+    fn structure(&mut self, name: &str, schema: &Value) -> String {
+        let required: Vec<_> = schema["required"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        let mut fields = String::new();
+        let mut optional = true;
+
+        for (property, field) in schema["properties"].as_object().unwrap() {
+            let context = format!("{name}{}", property.to_upper_camel_case());
+            let rust = self.rust(field, &context, "");
+            let snake = property.to_snake_case();
+
+            fields.push_str(&doc(field.get("description"), "    "));
+
+            if snake != *property {
+                fields.push_str(&format!("    #[serde(rename = \"{property}\")]\n"));
+            }
+
+            let rust = if required.contains(&property.as_str()) && !nullable(field) {
+                optional = false;
+                rust
+            } else {
+                fields
+                    .push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+
+                format!("Option<{rust}>")
+            };
+            let snake = match KEYWORDS.contains(&snake.as_str()) {
+                true => format!("r#{snake}"),
+                false => snake,
+            };
+
+            fields.push_str(&format!("    pub {snake}: {rust},\n"));
+        }
+
+        // every field being optional, an empty one is of use as well
+        let derives = match optional {
+            true => "Clone, Debug, Default, PartialEq, Serialize, Deserialize",
+            false => "Clone, Debug, PartialEq, Serialize, Deserialize",
+        };
+
+        format!(
+            "{}#[derive({derives})]\npub struct {name} {{\n{fields}}}\n",
+            doc(schema.get("description"), ""),
+        )
+    }
+}
+
+fn enumeration(name: &str, schema: &Value, values: &[Value]) -> String {
+    let values: Vec<_> = values.iter().filter_map(Value::as_str).collect();
+    let mut variants = String::new();
+    let mut names = String::new();
+
+    for (value, variant) in values.iter().zip(variants_of(&values)) {
+        if variant != *value {
+            variants.push_str(&format!("    #[serde(rename = \"{value}\")]\n"));
+        }
+
+        variants.push_str(&format!("    {variant},\n"));
+        names.push_str(&format!("            Self::{variant} => \"{value}\",\n"));
+    }
+
+    format!(
+        "{}#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum {name} {{
+{variants}}}
+
+impl std::fmt::Display for {name} {{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{
+        f.write_str(match self {{
+{names}        }})
+    }}
+}}
+",
+        doc(schema.get("description"), ""),
+    )
+}
+
+/// Variants named after `values`, spelling out what separates the words of
+/// each when leaving it out makes any two of them the same.
+fn variants_of(values: &[&str]) -> Vec<String> {
+    let variants: Vec<_> = values
+        .iter()
+        .map(|value| value.to_upper_camel_case())
+        .collect();
+
+    if BTreeSet::from_iter(&variants).len() == variants.len() {
+        return variants;
+    }
+
+    values
+        .iter()
+        .map(|value| {
+            value
+                .replace('-', " dash ")
+                .replace('/', " slash ")
+                .replace('.', " dot ")
+                .to_upper_camel_case()
+        })
+        .collect()
+}
+
+/// What of `schema` makes up its type, leaving out how it is annotated.
+fn shape(schema: &Value) -> Value {
+    let mut shape = schema.clone();
+
+    if let Some(shape) = shape.as_object_mut() {
+        for annotation in ["description", "example", "default", "nullable"] {
+            shape.remove(annotation);
+        }
+    }
+
+    shape
+}
+
+fn has_properties(schema: &Value) -> bool {
+    schema["properties"]
+        .as_object()
+        .is_some_and(|properties| !properties.is_empty())
+}
+
+fn nullable(schema: &Value) -> bool {
+    schema["nullable"].as_bool().unwrap_or_default()
+}
@@ -0,0 +1,143 @@
+use std::sync::{Arc, RwLock};
+
+use reqwest::header::ACCEPT_LANGUAGE;
+use reqwest::{Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+
+use crate::error::Error;
+use crate::types;
+
+/// Header the api resolves the tenant from, rather than the host.
+const TENANT: &str = "X-Tenant";
+
+/// Calls the api at `base_url` with one method per operation, authenticated
+/// with the token it holds if any. Clones share the token, so signing in
+/// through one of them signs in all of them.
+#[derive(Clone, Debug)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    token: Arc<RwLock<Option<String>>>,
+    tenant: Option<String>,
+    locale: Option<String>,
+}
+
+impl Client {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            token: Default::default(),
+            tenant: None,
+            locale: None,
+        }
+    }
+
+    /// Send requests through `http`, as configured with timeouts or proxies.
+    pub fn with_http(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Call the tenant of `code` rather than the one the host resolves to.
+    pub fn with_tenant(mut self, code: impl Into<String>) -> Self {
+        self.tenant = Some(code.into());
+        self
+    }
+
+    /// Be answered in `locale` rather than in the one of the user.
+    pub fn with_locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    pub fn token(&self) -> Option<String> {
+        self.token.read().unwrap().clone()
+    }
+
+    /// Authenticate following calls with `token`, or stop authenticating
+    /// them when empty.
+    pub fn set_token(&self, token: Option<String>) {
+        *self.token.write().unwrap() = token;
+    }
+
+    /// Log in, authenticating following calls as the user.
+    pub async fn sign_in(&self, login: &types::Login) -> Result<types::AuthLoginResponse, Error> {
+        let response = self.auth_login(login).await?;
+
+        self.set_token(Some(response.token.clone()));
+
+        Ok(response)
+    }
+
+    /// Log out, no longer authenticating following calls.
+    pub async fn sign_out(&self) -> Result<types::Ok, Error> {
+        let response = self.auth_logout().await?;
+
+        self.set_token(None);
+
+        Ok(response)
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let mut request = self
+            .http
+            .request(method, format!("{}{path}", self.base_url));
+
+        if let Some(token) = self.token() {
+            request = request.bearer_auth(token);
+        }
+
+        if let Some(tenant) = &self.tenant {
+            request = request.header(TENANT, tenant);
+        }
+
+        if let Some(locale) = &self.locale {
+            request = request.header(ACCEPT_LANGUAGE, locale);
+        }
+
+        request
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response, Error> {
+        let response = request.send().await?;
+        let status = response.status();
+
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        Err(Error::of(status, response.text().await?))
+    }
+
+    async fn json<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, Error> {
+        Ok(self.send(request).await?.json().await?)
+    }
+
+    async fn bytes(&self, request: RequestBuilder) -> Result<Vec<u8>, Error> {
+        Ok(self.send(request).await?.bytes().await?.to_vec())
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/operations.rs"));
+
+#[cfg(test)]
+pub mod test {
+    use reqwest::Method;
+
+    use super::Client;
+
+    #[test]
+    pub fn client_must_share_its_token_with_its_clones() {
+        let client = Client::new("http://localhost:8000/").with_tenant("acme");
+        let clone = client.clone();
+
+        client.set_token(Some("secret".into()));
+
+        let request = clone.request(Method::GET, "/api/v1/me").build().unwrap();
+
+        assert_eq!(request.url().as_str(), "http://localhost:8000/api/v1/me");
+        assert_eq!(request.headers()["authorization"], "Bearer secret");
+        assert_eq!(request.headers()["x-tenant"], "acme");
+    }
+}
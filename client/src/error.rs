@@ -0,0 +1,120 @@
+use std::fmt::{Display, Formatter, Result};
+
+use reqwest::StatusCode;
+
+use crate::types::Problem;
+
+/// Why a call failed, named after the responses the api answers with.
+#[derive(Debug)]
+pub enum Error {
+    BadRequest(Problem),
+    Unauthorized(Problem),
+    Forbidden(Problem),
+    NotFound(Problem),
+    Conflict(Problem),
+    /// What is wrong with each field is in `Problem::errors`.
+    UnprocessableEntity(Problem),
+    InternalServerError(Problem),
+    /// Any other status, or a body that is not a problem, as it was answered.
+    Unexpected(StatusCode, String),
+    /// The request could not be sent or its response not be read.
+    Transport(reqwest::Error),
+}
+
+impl Error {
+    /// The error a response of `status` answering `body` stands for.
+    pub fn of(status: StatusCode, body: String) -> Self {
+        let Ok(problem) = serde_json::from_str::<Problem>(&body) else {
+            return Self::Unexpected(status, body);
+        };
+
+        match status {
+            StatusCode::BAD_REQUEST => Self::BadRequest(problem),
+            StatusCode::UNAUTHORIZED => Self::Unauthorized(problem),
+            StatusCode::FORBIDDEN => Self::Forbidden(problem),
+            StatusCode::NOT_FOUND => Self::NotFound(problem),
+            StatusCode::CONFLICT => Self::Conflict(problem),
+            StatusCode::UNPROCESSABLE_ENTITY => Self::UnprocessableEntity(problem),
+            StatusCode::INTERNAL_SERVER_ERROR => Self::InternalServerError(problem),
+            _ => Self::Unexpected(status, body),
+        }
+    }
+
+    pub fn problem(&self) -> Option<&Problem> {
+        match self {
+            Self::BadRequest(problem)
+            | Self::Unauthorized(problem)
+            | Self::Forbidden(problem)
+            | Self::NotFound(problem)
+            | Self::Conflict(problem)
+            | Self::UnprocessableEntity(problem)
+            | Self::InternalServerError(problem) => Some(problem),
+            Self::Unexpected(..) | Self::Transport(_) => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::Unexpected(status, body) => write!(f, "{status}: {body}"),
+            Self::Transport(e) => write!(f, "{e}"),
+            _ => {
+                let problem = self.problem().unwrap();
+
+                write!(
+                    f,
+                    "{} {}: {}",
+                    problem.status, problem.title, problem.detail
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<reqwest::Error> for Error {
+    fn from(value: reqwest::Error) -> Self {
+        Self::Transport(value)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use reqwest::StatusCode;
+
+    use super::Error;
+
+    #[test]
+    pub fn error_must_be_named_after_the_status_of_its_problem() {
+        let body = r#"{
+            "type": "/errors/validation_failed",
+            "title": "Unprocessable Entity",
+            "status": 422,
+            "detail": "The given data was invalid",
+            "code": "validation_failed",
+            "errors": { "email": ["The email has already been taken"] }
+        }"#;
+        let error = Error::of(StatusCode::UNPROCESSABLE_ENTITY, body.into());
+
+        assert!(matches!(error, Error::UnprocessableEntity(_)));
+        assert_eq!(
+            error
+                .problem()
+                .and_then(|problem| problem.errors.as_ref())
+                .unwrap()["email"],
+            ["The email has already been taken"]
+        );
+    }
+
+    #[test]
+    pub fn error_must_keep_a_body_that_is_not_a_problem() {
+        let error = Error::of(StatusCode::BAD_GATEWAY, "upstream went away".into());
+
+        assert!(
+            matches!(error, Error::Unexpected(StatusCode::BAD_GATEWAY, ref body) if body == "upstream went away")
+        );
+        assert!(error.problem().is_none());
+    }
+}
@@ -0,0 +1,27 @@
+//! Typed client of the Learning Management System api, generated at build
+//! time from the spec the server documents.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), client::Error> {
+//! use client::{types, Client};
+//!
+//! let client = Client::new("http://localhost:8000").with_tenant("default");
+//!
+//! client
+//!     .sign_in(&types::Login {
+//!         email_or_username: "root".into(),
+//!         password: "LetMe!nM4te".into(),
+//!     })
+//!     .await?;
+//!
+//! let users = client.user_paginate(&Default::default()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod client;
+mod error;
+pub mod types;
+
+pub use client::Client;
+pub use error::Error;
@@ -0,0 +1,3 @@
+//! Request and response bodies of the api.
+
+include!(concat!(env!("OUT_DIR"), "/types.rs"));
@@ -9,6 +9,23 @@ mod m20230902_025247_create_permission_role;
 mod m20230902_025255_create_role_user;
 mod m20230902_025309_create_tokens;
 mod m20231216_092530_user_initial_seeder;
+mod m20240105_081512_create_jobs;
+mod m20240112_093044_create_scheduled_tasks;
+mod m20240119_101233_add_locale_to_users;
+mod m20240126_140518_create_files;
+mod m20240202_091204_create_invitations;
+mod m20240209_134410_add_registered_at_to_users;
+mod m20240216_103027_create_impersonations;
+mod m20240223_090415_create_audit_logs;
+mod m20240301_084512_create_preferences;
+mod m20240308_101522_add_status_to_users;
+mod m20240315_093120_create_role_parent;
+mod m20240322_101845_create_role_scopes;
+mod m20240329_094210_create_tenants;
+mod m20240405_091530_add_catalogue_to_permissions;
+mod m20240412_083045_add_system_to_roles;
+mod m20240419_102315_add_period_to_grants;
+mod m20240426_091740_add_tenant_to_files_and_role_scopes;
 
 pub struct Migrator;
 
@@ -25,6 +42,23 @@ impl MigratorTrait for Migrator {
             Box::new(m20230902_025255_create_role_user::Migration),
             Box::new(m20230902_025309_create_tokens::Migration),
             Box::new(m20231216_092530_user_initial_seeder::Migration),
+            Box::new(m20240105_081512_create_jobs::Migration),
+            Box::new(m20240112_093044_create_scheduled_tasks::Migration),
+            Box::new(m20240119_101233_add_locale_to_users::Migration),
+            Box::new(m20240126_140518_create_files::Migration),
+            Box::new(m20240202_091204_create_invitations::Migration),
+            Box::new(m20240209_134410_add_registered_at_to_users::Migration),
+            Box::new(m20240216_103027_create_impersonations::Migration),
+            Box::new(m20240223_090415_create_audit_logs::Migration),
+            Box::new(m20240301_084512_create_preferences::Migration),
+            Box::new(m20240308_101522_add_status_to_users::Migration),
+            Box::new(m20240315_093120_create_role_parent::Migration),
+            Box::new(m20240322_101845_create_role_scopes::Migration),
+            Box::new(m20240329_094210_create_tenants::Migration),
+            Box::new(m20240405_091530_add_catalogue_to_permissions::Migration),
+            Box::new(m20240412_083045_add_system_to_roles::Migration),
+            Box::new(m20240419_102315_add_period_to_grants::Migration),
+            Box::new(m20240426_091740_add_tenant_to_files_and_role_scopes::Migration),
         ]
     }
 }
@@ -3,6 +3,7 @@ use learning_management_system::{
     models::{permission_user, permissions, role_user, roles, users},
 };
 use sea_orm::prelude::*;
+use sea_orm::NotSet;
 use sea_orm_migration::prelude::*;
 
 #[derive(DeriveMigrationName)]
@@ -23,6 +24,9 @@ impl MigrationTrait for Migration {
                     id: Uuid::new_v4().into(),
                     code: format!("{}_{}", ability, permission).to_uppercase(),
                     name: format!("{} {}", ability, permission).to_lowercase(),
+                    group: None,
+                    description: None,
+                    orphaned_at: None,
                 };
 
                 permissions.push(permission);
@@ -32,8 +36,10 @@ impl MigrationTrait for Migration {
         for role in ROLES {
             let role = roles::Model {
                 id: Uuid::new_v4().into(),
+                tenant_id: Uuid::nil().into(),
                 code: role.to_uppercase(),
                 name: role.to_lowercase(),
+                system: true,
             };
 
             roles.push(role);
@@ -42,7 +48,15 @@ impl MigrationTrait for Migration {
         let query = permissions::Entity::insert_many(
             permissions
                 .iter()
-                .map(|permission| permissions::ActiveModel::from(permission.clone()))
+                .map(|permission| {
+                    let mut permission = permissions::ActiveModel::from(permission.clone());
+
+                    // filled in by the catalogue sync once the columns exist
+                    permission.group = NotSet;
+                    permission.description = NotSet;
+                    permission.orphaned_at = NotSet;
+                    permission
+                })
                 .collect::<Vec<_>>(),
         );
 
@@ -51,7 +65,15 @@ impl MigrationTrait for Migration {
         let query = roles::Entity::insert_many(
             roles
                 .iter()
-                .map(|role| roles::ActiveModel::from(role.clone()))
+                .map(|role| {
+                    let mut role = roles::ActiveModel::from(role.clone());
+
+                    // assigned to the default tenant once it exists
+                    role.tenant_id = NotSet;
+                    // flagged once the column exists
+                    role.system = NotSet;
+                    role
+                })
                 .collect::<Vec<_>>(),
         );
 
@@ -60,19 +82,35 @@ impl MigrationTrait for Migration {
         let id = Uuid::new_v4();
         let user = users::Model {
             id: id.clone().into(),
+            tenant_id: Uuid::nil().into(),
             name: "root".to_owned(),
             email: "root@local".to_owned(),
             username: "root".to_owned(),
             password: hash::make(id.to_string(), "LetMe!nM4te").to_string(),
             profile_photo_id: None,
+            locale: None,
+            registered_at: None,
+            status: "active".to_owned(),
+            status_reason: None,
+            suspended_until: None,
             email_verified_at: None,
             created_at: time::now(),
             updated_at: time::now(),
             deleted_at: None,
         };
 
-        users::ActiveModel::from(user)
-            .insert(manager.get_connection())
+        let mut user = users::ActiveModel::from(user);
+
+        // columns added by later migrations do not exist yet
+        user.locale = NotSet;
+        user.registered_at = NotSet;
+        user.status = NotSet;
+        user.status_reason = NotSet;
+        user.suspended_until = NotSet;
+        user.tenant_id = NotSet;
+
+        users::Entity::insert(user)
+            .exec(manager.get_connection())
             .await?;
 
         let (mut permission_users, mut role_users) = (vec![], vec![]);
@@ -82,6 +120,8 @@ impl MigrationTrait for Migration {
                 id: Uuid::new_v4().into(),
                 permission_id: permission.id.clone(),
                 user_id: id.clone().into(),
+                starts_at: None,
+                expires_at: None,
             };
 
             permission_users.push(permission_user);
@@ -92,6 +132,8 @@ impl MigrationTrait for Migration {
                 id: Uuid::new_v4().into(),
                 role_id: role.id.clone(),
                 user_id: id.clone().into(),
+                starts_at: None,
+                expires_at: None,
             };
 
             role_users.push(role_user);
@@ -100,7 +142,15 @@ impl MigrationTrait for Migration {
         let query = permission_user::Entity::insert_many(
             permission_users
                 .iter()
-                .map(|permission_user| permission_user::ActiveModel::from(permission_user.clone()))
+                .map(|permission_user| {
+                    let mut permission_user =
+                        permission_user::ActiveModel::from(permission_user.clone());
+
+                    // columns added by later migrations do not exist yet
+                    permission_user.starts_at = NotSet;
+                    permission_user.expires_at = NotSet;
+                    permission_user
+                })
                 .collect::<Vec<_>>(),
         );
 
@@ -109,7 +159,14 @@ impl MigrationTrait for Migration {
         let query = role_user::Entity::insert_many(
             role_users
                 .iter()
-                .map(|role_user| role_user::ActiveModel::from(role_user.clone()))
+                .map(|role_user| {
+                    let mut role_user = role_user::ActiveModel::from(role_user.clone());
+
+                    // columns added by later migrations do not exist yet
+                    role_user.starts_at = NotSet;
+                    role_user.expires_at = NotSet;
+                    role_user
+                })
                 .collect::<Vec<_>>(),
         );
 
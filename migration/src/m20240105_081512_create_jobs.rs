@@ -0,0 +1,168 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        #[cfg(feature = "sqlite")]
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE TABLE IF NOT EXISTS jobs (
+                    id VARCHAR(36) NOT NULL PRIMARY KEY,
+                    queue VARCHAR(255) NOT NULL DEFAULT 'default',
+                    name VARCHAR(255) NOT NULL,
+                    payload TEXT NOT NULL,
+                    status VARCHAR(32) NOT NULL DEFAULT 'pending',
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    max_attempts INTEGER NOT NULL DEFAULT 5,
+                    last_error TEXT NULL DEFAULT NULL,
+                    available_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    locked_until TIMESTAMP NULL DEFAULT NULL,
+                    completed_at TIMESTAMP NULL DEFAULT NULL,
+                    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )",
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .create_table(
+                Table::create()
+                    .table(Job::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Job::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(Job::Queue)
+                            .string()
+                            .not_null()
+                            .default("default"),
+                    )
+                    .col(ColumnDef::new(Job::Name).string().not_null())
+                    .col(ColumnDef::new(Job::Payload).json_binary().not_null())
+                    .col(
+                        ColumnDef::new(Job::Status)
+                            .string_len(32)
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(
+                        ColumnDef::new(Job::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(Job::MaxAttempts)
+                            .integer()
+                            .not_null()
+                            .default(5),
+                    )
+                    .col(
+                        ColumnDef::new(Job::LastError)
+                            .text()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(Job::AvailableAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .col(
+                        ColumnDef::new(Job::LockedUntil)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(Job::CompletedAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(Job::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .col(
+                        ColumnDef::new(Job::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(Job::Table)
+                    .col(Job::Queue)
+                    .col(Job::Status)
+                    .col(Job::AvailableAt)
+                    .name("idx_jobs_queue_status_available_at")
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(Job::Table)
+                    .col(Job::LockedUntil)
+                    .name("idx_jobs_locked_until")
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(Job::Table)
+                    .col(Job::Name)
+                    .name("idx_jobs_name")
+                    .take(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Job::Table).take())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+#[allow(dead_code)]
+pub enum Job {
+    #[sea_orm(iden = "jobs")]
+    Table,
+    Id,
+    Queue,
+    Name,
+    Payload,
+    Status,
+    Attempts,
+    MaxAttempts,
+    LastError,
+    AvailableAt,
+    LockedUntil,
+    CompletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
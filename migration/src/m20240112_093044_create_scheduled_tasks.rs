@@ -0,0 +1,131 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        #[cfg(feature = "sqlite")]
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE TABLE IF NOT EXISTS scheduled_tasks (
+                    id VARCHAR(36) NOT NULL PRIMARY KEY,
+                    name VARCHAR(255) NOT NULL UNIQUE,
+                    expression VARCHAR(255) NOT NULL,
+                    next_run_at TIMESTAMP NULL DEFAULT NULL,
+                    last_run_at TIMESTAMP NULL DEFAULT NULL,
+                    last_status VARCHAR(32) NULL DEFAULT NULL,
+                    last_error TEXT NULL DEFAULT NULL,
+                    last_duration BIGINT NULL DEFAULT NULL,
+                    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )",
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .create_table(
+                Table::create()
+                    .table(ScheduledTask::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ScheduledTask::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(ScheduledTask::Name)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ScheduledTask::Expression)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ScheduledTask::NextRunAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(ScheduledTask::LastRunAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(ScheduledTask::LastStatus)
+                            .string_len(32)
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(ScheduledTask::LastError)
+                            .text()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(ScheduledTask::LastDuration)
+                            .big_integer()
+                            .null()
+                            .default(None as Option<i64>),
+                    )
+                    .col(
+                        ColumnDef::new(ScheduledTask::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .col(
+                        ColumnDef::new(ScheduledTask::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(ScheduledTask::Table)
+                    .col(ScheduledTask::NextRunAt)
+                    .name("idx_scheduled_tasks_next_run_at")
+                    .take(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ScheduledTask::Table).take())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+#[allow(dead_code)]
+pub enum ScheduledTask {
+    #[sea_orm(iden = "scheduled_tasks")]
+    Table,
+    Id,
+    Name,
+    Expression,
+    NextRunAt,
+    LastRunAt,
+    LastStatus,
+    LastError,
+    LastDuration,
+    CreatedAt,
+    UpdatedAt,
+}
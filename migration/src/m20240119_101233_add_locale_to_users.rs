@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        #[cfg(feature = "sqlite")]
+        manager
+            .get_connection()
+            .execute_unprepared("ALTER TABLE users ADD COLUMN locale VARCHAR(8) NULL DEFAULT NULL")
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::Locale)
+                            .string_len(8)
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .take(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::Locale)
+                    .take(),
+            )
+            .await
+    }
+}
+
+#[allow(dead_code)]
+#[derive(DeriveIden)]
+pub enum User {
+    #[sea_orm(iden = "users")]
+    Table,
+    Locale,
+}
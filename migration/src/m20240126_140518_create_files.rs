@@ -0,0 +1,136 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        #[cfg(feature = "sqlite")]
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE TABLE IF NOT EXISTS files (
+                    id VARCHAR(36) NOT NULL PRIMARY KEY,
+                    disk VARCHAR(32) NOT NULL,
+                    path VARCHAR(255) NOT NULL,
+                    thumbnail_path VARCHAR(255) NULL DEFAULT NULL,
+                    name VARCHAR(255) NOT NULL,
+                    mime VARCHAR(127) NOT NULL,
+                    size BIGINT NOT NULL,
+                    checksum VARCHAR(64) NOT NULL,
+                    width INTEGER NULL DEFAULT NULL,
+                    height INTEGER NULL DEFAULT NULL,
+                    uploaded_by VARCHAR(36) NULL DEFAULT NULL,
+                    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )",
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .create_table(
+                Table::create()
+                    .table(File::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(File::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(ColumnDef::new(File::Disk).string_len(32).not_null())
+                    .col(ColumnDef::new(File::Path).string().not_null())
+                    .col(
+                        ColumnDef::new(File::ThumbnailPath)
+                            .string()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(ColumnDef::new(File::Name).string().not_null())
+                    .col(ColumnDef::new(File::Mime).string_len(127).not_null())
+                    .col(ColumnDef::new(File::Size).big_integer().not_null())
+                    .col(ColumnDef::new(File::Checksum).string_len(64).not_null())
+                    .col(
+                        ColumnDef::new(File::Width)
+                            .integer()
+                            .null()
+                            .default(None as Option<i32>),
+                    )
+                    .col(
+                        ColumnDef::new(File::Height)
+                            .integer()
+                            .null()
+                            .default(None as Option<i32>),
+                    )
+                    .col(
+                        ColumnDef::new(File::UploadedBy)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(File::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .col(
+                        ColumnDef::new(File::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(File::Table)
+                    .col(File::Checksum)
+                    .name("idx_files_checksum")
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(File::Table)
+                    .col(File::UploadedBy)
+                    .name("idx_files_uploaded_by")
+                    .take(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(File::Table).take())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+#[allow(dead_code)]
+pub enum File {
+    #[sea_orm(iden = "files")]
+    Table,
+    Id,
+    Disk,
+    Path,
+    ThumbnailPath,
+    Name,
+    Mime,
+    Size,
+    Checksum,
+    Width,
+    Height,
+    UploadedBy,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,168 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        #[cfg(feature = "sqlite")]
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE TABLE IF NOT EXISTS invitations (
+                    id VARCHAR(36) NOT NULL PRIMARY KEY,
+                    email VARCHAR(255) NOT NULL,
+                    name VARCHAR(255) NOT NULL,
+                    roles TEXT NOT NULL DEFAULT '[]',
+                    permissions TEXT NOT NULL DEFAULT '[]',
+                    status VARCHAR(32) NOT NULL DEFAULT 'pending',
+                    invited_by VARCHAR(36) NULL DEFAULT NULL,
+                    user_id VARCHAR(36) NULL DEFAULT NULL,
+                    sent_count INTEGER NOT NULL DEFAULT 1,
+                    sent_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    expired_at TIMESTAMP NOT NULL,
+                    accepted_at TIMESTAMP NULL DEFAULT NULL,
+                    revoked_at TIMESTAMP NULL DEFAULT NULL,
+                    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )",
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .create_table(
+                Table::create()
+                    .table(Invitation::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Invitation::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(ColumnDef::new(Invitation::Email).string().not_null())
+                    .col(ColumnDef::new(Invitation::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(Invitation::Roles)
+                            .json_binary()
+                            .not_null()
+                            .extra("DEFAULT '[]'"),
+                    )
+                    .col(
+                        ColumnDef::new(Invitation::Permissions)
+                            .json_binary()
+                            .not_null()
+                            .extra("DEFAULT '[]'"),
+                    )
+                    .col(
+                        ColumnDef::new(Invitation::Status)
+                            .string_len(32)
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(
+                        ColumnDef::new(Invitation::InvitedBy)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(Invitation::UserId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(Invitation::SentCount)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .col(
+                        ColumnDef::new(Invitation::SentAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .col(ColumnDef::new(Invitation::ExpiredAt).timestamp().not_null())
+                    .col(
+                        ColumnDef::new(Invitation::AcceptedAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(Invitation::RevokedAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(Invitation::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .col(
+                        ColumnDef::new(Invitation::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(Invitation::Table)
+                    .col(Invitation::Email)
+                    .name("idx_invitations_email")
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(Invitation::Table)
+                    .col(Invitation::Status)
+                    .col(Invitation::ExpiredAt)
+                    .name("idx_invitations_status_expired_at")
+                    .take(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Invitation::Table).take())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+#[allow(dead_code)]
+pub enum Invitation {
+    #[sea_orm(iden = "invitations")]
+    Table,
+    Id,
+    Email,
+    Name,
+    Roles,
+    Permissions,
+    Status,
+    InvitedBy,
+    UserId,
+    SentCount,
+    SentAt,
+    ExpiredAt,
+    AcceptedAt,
+    RevokedAt,
+    CreatedAt,
+    UpdatedAt,
+}
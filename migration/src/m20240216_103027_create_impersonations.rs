@@ -0,0 +1,207 @@
+use learning_management_system::models::{permission_role, permissions, roles};
+use sea_orm::prelude::*;
+use sea_orm::{NotSet, QuerySelect};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const PERMISSION: &str = "IMPERSONATE_USER";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        #[cfg(feature = "sqlite")]
+        {
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    "ALTER TABLE tokens ADD COLUMN impersonator_id VARCHAR(36) NULL DEFAULT NULL",
+                )
+                .await?;
+
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS impersonations (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        impersonator_id VARCHAR(36) NOT NULL,
+                        user_id VARCHAR(36) NOT NULL,
+                        token_id VARCHAR(36) NOT NULL,
+                        ip VARCHAR(45) NULL DEFAULT NULL,
+                        user_agent TEXT NULL DEFAULT NULL,
+                        started_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        ended_at TIMESTAMP NULL DEFAULT NULL
+                    )",
+                )
+                .await?;
+        }
+
+        #[cfg(feature = "postgres")]
+        {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Token::Table)
+                        .add_column(
+                            ColumnDef::new(Token::ImpersonatorId)
+                                .uuid()
+                                .null()
+                                .default(None as Option<String>),
+                        )
+                        .take(),
+                )
+                .await?;
+
+            manager
+                .create_table(
+                    Table::create()
+                        .table(Impersonation::Table)
+                        .if_not_exists()
+                        .col(
+                            ColumnDef::new(Impersonation::Id)
+                                .uuid()
+                                .not_null()
+                                .primary_key()
+                                .extra("DEFAULT uuid_generate_v4()"),
+                        )
+                        .col(
+                            ColumnDef::new(Impersonation::ImpersonatorId)
+                                .uuid()
+                                .not_null(),
+                        )
+                        .col(ColumnDef::new(Impersonation::UserId).uuid().not_null())
+                        .col(ColumnDef::new(Impersonation::TokenId).uuid().not_null())
+                        .col(
+                            ColumnDef::new(Impersonation::Ip)
+                                .string_len(45)
+                                .null()
+                                .default(None as Option<String>),
+                        )
+                        .col(
+                            ColumnDef::new(Impersonation::UserAgent)
+                                .text()
+                                .null()
+                                .default(None as Option<String>),
+                        )
+                        .col(
+                            ColumnDef::new(Impersonation::StartedAt)
+                                .timestamp()
+                                .not_null()
+                                .extra("DEFAULT NOW()"),
+                        )
+                        .col(
+                            ColumnDef::new(Impersonation::EndedAt)
+                                .timestamp()
+                                .null()
+                                .default(None as Option<String>),
+                        )
+                        .take(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(Impersonation::Table)
+                    .col(Impersonation::ImpersonatorId)
+                    .name("idx_impersonations_impersonator_id")
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(Impersonation::Table)
+                    .col(Impersonation::TokenId)
+                    .name("idx_impersonations_token_id")
+                    .take(),
+            )
+            .await?;
+
+        let permission = permissions::Model {
+            id: Uuid::new_v4().into(),
+            code: PERMISSION.to_string(),
+            name: "impersonate user".to_string(),
+            group: None,
+            description: None,
+            orphaned_at: None,
+        };
+        let mut active = permissions::ActiveModel::from(permission.clone());
+
+        // filled in by the catalogue sync once the columns exist
+        active.group = NotSet;
+        active.description = NotSet;
+        active.orphaned_at = NotSet;
+
+        permissions::Entity::insert(active)
+            .exec(manager.get_connection())
+            .await?;
+
+        // only the id, columns added by later migrations do not exist yet
+        let superuser = roles::Entity::find()
+            .select_only()
+            .column(roles::Column::Id)
+            .filter(roles::Column::Code.eq("SUPERUSER"))
+            .into_tuple::<Uuid>()
+            .one(manager.get_connection())
+            .await?;
+
+        if let Some(superuser) = superuser {
+            permission_role::ActiveModel::from(permission_role::Model {
+                id: Uuid::new_v4().into(),
+                permission_id: permission.id,
+                role_id: superuser.into(),
+            })
+            .insert(manager.get_connection())
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        permissions::Entity::delete_many()
+            .filter(permissions::Column::Code.eq(PERMISSION))
+            .exec(manager.get_connection())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Impersonation::Table).take())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Token::Table)
+                    .drop_column(Token::ImpersonatorId)
+                    .take(),
+            )
+            .await
+    }
+}
+
+#[allow(dead_code)]
+#[derive(DeriveIden)]
+pub enum Token {
+    #[sea_orm(iden = "tokens")]
+    Table,
+    ImpersonatorId,
+}
+
+#[derive(DeriveIden)]
+#[allow(dead_code)]
+pub enum Impersonation {
+    #[sea_orm(iden = "impersonations")]
+    Table,
+    Id,
+    ImpersonatorId,
+    UserId,
+    TokenId,
+    Ip,
+    UserAgent,
+    StartedAt,
+    EndedAt,
+}
@@ -0,0 +1,150 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        #[cfg(feature = "sqlite")]
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE TABLE IF NOT EXISTS audit_logs (
+                    id VARCHAR(36) NOT NULL PRIMARY KEY,
+                    actor_id VARCHAR(36) NULL DEFAULT NULL,
+                    impersonator_id VARCHAR(36) NULL DEFAULT NULL,
+                    action VARCHAR(32) NOT NULL,
+                    entity_type VARCHAR(64) NOT NULL,
+                    entity_id VARCHAR(36) NOT NULL,
+                    before TEXT NULL DEFAULT NULL,
+                    after TEXT NULL DEFAULT NULL,
+                    ip VARCHAR(45) NULL DEFAULT NULL,
+                    user_agent TEXT NULL DEFAULT NULL,
+                    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )",
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLog::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuditLog::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::ActorId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::ImpersonatorId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(ColumnDef::new(AuditLog::Action).string_len(32).not_null())
+                    .col(
+                        ColumnDef::new(AuditLog::EntityType)
+                            .string_len(64)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AuditLog::EntityId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(AuditLog::Before)
+                            .json_binary()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::After)
+                            .json_binary()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::Ip)
+                            .string_len(45)
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::UserAgent)
+                            .text()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(AuditLog::Table)
+                    .col(AuditLog::EntityType)
+                    .col(AuditLog::EntityId)
+                    .name("idx_audit_logs_entity")
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(AuditLog::Table)
+                    .col(AuditLog::ActorId)
+                    .name("idx_audit_logs_actor_id")
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(AuditLog::Table)
+                    .col(AuditLog::CreatedAt)
+                    .name("idx_audit_logs_created_at")
+                    .take(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuditLog::Table).take())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+#[allow(dead_code)]
+pub enum AuditLog {
+    #[sea_orm(iden = "audit_logs")]
+    Table,
+    Id,
+    ActorId,
+    ImpersonatorId,
+    Action,
+    EntityType,
+    EntityId,
+    Before,
+    After,
+    Ip,
+    UserAgent,
+    CreatedAt,
+}
@@ -0,0 +1,106 @@
+use sea_orm_migration::prelude::*;
+
+#[allow(unused_imports)]
+use crate::m20230902_024725_create_users::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        #[cfg(feature = "sqlite")]
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE TABLE IF NOT EXISTS preferences (
+                    user_id VARCHAR(36) NOT NULL PRIMARY KEY,
+                    timezone VARCHAR(64) NOT NULL DEFAULT 'UTC',
+                    date_format VARCHAR(16) NOT NULL DEFAULT 'YYYY-MM-DD',
+                    notifications TEXT NOT NULL DEFAULT '{}',
+                    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+                )",
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .create_table(
+                Table::create()
+                    .table(Preference::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Preference::UserId)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Preference::Timezone)
+                            .string_len(64)
+                            .not_null()
+                            .default("UTC"),
+                    )
+                    .col(
+                        ColumnDef::new(Preference::DateFormat)
+                            .string_len(16)
+                            .not_null()
+                            .default("YYYY-MM-DD"),
+                    )
+                    .col(
+                        ColumnDef::new(Preference::Notifications)
+                            .json_binary()
+                            .not_null()
+                            .extra("DEFAULT '{}'"),
+                    )
+                    .col(
+                        ColumnDef::new(Preference::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .col(
+                        ColumnDef::new(Preference::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .take(),
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .from(Preference::Table, Preference::UserId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .take(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Preference::Table).take())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+#[allow(dead_code)]
+pub enum Preference {
+    #[sea_orm(iden = "preferences")]
+    Table,
+    UserId,
+    Timezone,
+    DateFormat,
+    Notifications,
+    CreatedAt,
+    UpdatedAt,
+}
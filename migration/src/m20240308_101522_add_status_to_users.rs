@@ -0,0 +1,102 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        #[cfg(feature = "sqlite")]
+        {
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    "ALTER TABLE users ADD COLUMN status VARCHAR(16) NOT NULL DEFAULT 'active'",
+                )
+                .await?;
+
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    "ALTER TABLE users ADD COLUMN status_reason TEXT NULL DEFAULT NULL",
+                )
+                .await?;
+
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    "ALTER TABLE users ADD COLUMN suspended_until TIMESTAMP NULL DEFAULT NULL",
+                )
+                .await?;
+        }
+
+        #[cfg(feature = "postgres")]
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::Status)
+                            .string_len(16)
+                            .not_null()
+                            .default("active"),
+                    )
+                    .add_column(
+                        ColumnDef::new(User::StatusReason)
+                            .text()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .add_column(
+                        ColumnDef::new(User::SuspendedUntil)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(User::Table)
+                    .col(User::Status)
+                    .name("idx_users_status")
+                    .take(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .table(User::Table)
+                    .name("idx_users_status")
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::Status)
+                    .drop_column(User::StatusReason)
+                    .drop_column(User::SuspendedUntil)
+                    .take(),
+            )
+            .await
+    }
+}
+
+#[allow(dead_code)]
+#[derive(DeriveIden)]
+pub enum User {
+    #[sea_orm(iden = "users")]
+    Table,
+    Status,
+    StatusReason,
+    SuspendedUntil,
+}
@@ -0,0 +1,127 @@
+use learning_management_system::models::{role_parent, roles};
+use sea_orm::prelude::*;
+use sea_orm::QuerySelect;
+use sea_orm_migration::prelude::*;
+
+#[allow(unused_imports)]
+use crate::m20230902_025106_create_roles::Role;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        #[cfg(feature = "sqlite")]
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE TABLE IF NOT EXISTS role_parent (
+                    id VARCHAR(36) NOT NULL PRIMARY KEY,
+                    role_id VARCHAR(36) NOT NULL,
+                    parent_id VARCHAR(36) NOT NULL,
+                    FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE,
+                    FOREIGN KEY (parent_id) REFERENCES roles (id) ON DELETE CASCADE
+                )",
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .create_table(
+                Table::create()
+                    .table(RoleParent::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RoleParent::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(ColumnDef::new(RoleParent::RoleId).uuid().not_null())
+                    .col(ColumnDef::new(RoleParent::ParentId).uuid().not_null())
+                    .take(),
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .from(RoleParent::Table, RoleParent::RoleId)
+                    .to(Role::Table, Role::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .take(),
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .from(RoleParent::Table, RoleParent::ParentId)
+                    .to(Role::Table, Role::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(RoleParent::Table)
+                    .col(RoleParent::RoleId)
+                    .col(RoleParent::ParentId)
+                    .name("idx_role_parent_role_id_parent_id")
+                    .unique()
+                    .take(),
+            )
+            .await?;
+
+        // the seeded superuser has always outranked the admin, selecting only
+        // the ids as columns added by later migrations do not exist yet
+        let superuser = roles::Entity::find()
+            .select_only()
+            .column(roles::Column::Id)
+            .filter(roles::Column::Code.eq("SUPERUSER"))
+            .into_tuple::<Uuid>()
+            .one(manager.get_connection())
+            .await?;
+        let admin = roles::Entity::find()
+            .select_only()
+            .column(roles::Column::Id)
+            .filter(roles::Column::Code.eq("ADMIN"))
+            .into_tuple::<Uuid>()
+            .one(manager.get_connection())
+            .await?;
+
+        if let (Some(superuser), Some(admin)) = (superuser, admin) {
+            role_parent::ActiveModel::from(role_parent::Model {
+                id: Uuid::new_v4().into(),
+                role_id: admin.into(),
+                parent_id: superuser.into(),
+            })
+            .insert(manager.get_connection())
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RoleParent::Table).take())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+#[allow(dead_code)]
+enum RoleParent {
+    #[sea_orm(iden = "role_parent")]
+    Table,
+    Id,
+    RoleId,
+    ParentId,
+}
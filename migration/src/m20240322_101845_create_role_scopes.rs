@@ -0,0 +1,126 @@
+use sea_orm_migration::prelude::*;
+
+#[allow(unused_imports)]
+use crate::{m20230902_024725_create_users::User, m20230902_025106_create_roles::Role};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        #[cfg(feature = "sqlite")]
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE TABLE IF NOT EXISTS role_scopes (
+                    id VARCHAR(36) NOT NULL PRIMARY KEY,
+                    user_id VARCHAR(36) NOT NULL,
+                    role_id VARCHAR(36) NOT NULL,
+                    scope_type VARCHAR(32) NOT NULL,
+                    scope_id VARCHAR(36) NOT NULL,
+                    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+                    FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE
+                )",
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .create_table(
+                Table::create()
+                    .table(RoleScope::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RoleScope::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(ColumnDef::new(RoleScope::UserId).uuid().not_null())
+                    .col(ColumnDef::new(RoleScope::RoleId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(RoleScope::ScopeType)
+                            .string_len(32)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(RoleScope::ScopeId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(RoleScope::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .take(),
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .from(RoleScope::Table, RoleScope::UserId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .take(),
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .from(RoleScope::Table, RoleScope::RoleId)
+                    .to(Role::Table, Role::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(RoleScope::Table)
+                    .col(RoleScope::UserId)
+                    .col(RoleScope::RoleId)
+                    .col(RoleScope::ScopeType)
+                    .col(RoleScope::ScopeId)
+                    .name("idx_role_scopes_user_id_role_id_scope")
+                    .unique()
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(RoleScope::Table)
+                    .col(RoleScope::ScopeType)
+                    .col(RoleScope::ScopeId)
+                    .name("idx_role_scopes_scope")
+                    .take(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RoleScope::Table).take())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+#[allow(dead_code)]
+enum RoleScope {
+    #[sea_orm(iden = "role_scopes")]
+    Table,
+    Id,
+    UserId,
+    RoleId,
+    ScopeType,
+    ScopeId,
+    CreatedAt,
+}
@@ -0,0 +1,289 @@
+use sea_orm_migration::prelude::*;
+
+#[allow(unused_imports)]
+use crate::{m20230902_024725_create_users::User, m20230902_025106_create_roles::Role};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Tables whose rows belong to a single tenant.
+const OWNED: [&str; 3] = ["users", "roles", "invitations"];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        #[cfg(feature = "sqlite")]
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE TABLE IF NOT EXISTS tenants (
+                    id VARCHAR(36) NOT NULL PRIMARY KEY,
+                    code VARCHAR(64) NOT NULL UNIQUE,
+                    name VARCHAR(255) NOT NULL,
+                    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                )",
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tenant::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Tenant::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(Tenant::Code)
+                            .string_len(64)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Tenant::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(Tenant::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .col(
+                        ColumnDef::new(Tenant::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .take(),
+            )
+            .await?;
+
+        // everything that exists so far belongs to the institution the
+        // deployment was set up for
+        #[cfg(feature = "sqlite")]
+        {
+            manager
+                .get_connection()
+                .execute_unprepared(&format!(
+                    "INSERT INTO tenants (id, code, name) VALUES ('{}', 'default', 'default')",
+                    sea_orm::prelude::Uuid::new_v4()
+                ))
+                .await?;
+
+            for table in OWNED {
+                manager
+                    .get_connection()
+                    .execute_unprepared(&format!(
+                        "ALTER TABLE {table} ADD COLUMN tenant_id VARCHAR(36) NULL DEFAULT NULL
+                        REFERENCES tenants (id) ON DELETE CASCADE"
+                    ))
+                    .await?;
+            }
+
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    "ALTER TABLE audit_logs ADD COLUMN tenant_id VARCHAR(36) NULL DEFAULT NULL",
+                )
+                .await?;
+        }
+
+        #[cfg(feature = "postgres")]
+        {
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    "INSERT INTO tenants (code, name) VALUES ('default', 'default')",
+                )
+                .await?;
+
+            for table in OWNED {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(Alias::new(table))
+                            .add_column(ColumnDef::new(Tenant::TenantId).uuid().null())
+                            .take(),
+                    )
+                    .await?;
+
+                manager
+                    .create_foreign_key(
+                        ForeignKey::create()
+                            .from(Alias::new(table), Tenant::TenantId)
+                            .to(Tenant::Table, Tenant::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .take(),
+                    )
+                    .await?;
+            }
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Alias::new("audit_logs"))
+                        .add_column(
+                            ColumnDef::new(Tenant::TenantId)
+                                .uuid()
+                                .null()
+                                .default(None as Option<String>),
+                        )
+                        .take(),
+                )
+                .await?;
+        }
+
+        for table in OWNED.iter().chain(&["audit_logs"]) {
+            manager
+                .get_connection()
+                .execute_unprepared(&format!(
+                    "UPDATE {table} SET tenant_id = (SELECT id FROM tenants WHERE code = 'default')"
+                ))
+                .await?;
+
+            manager
+                .create_index(
+                    Index::create()
+                        .table(Alias::new(*table))
+                        .col(Tenant::TenantId)
+                        .name(format!("idx_{table}_tenant_id"))
+                        .take(),
+                )
+                .await?;
+        }
+
+        // emails, usernames and role codes only need to be unique within a
+        // tenant; sqlite cannot drop the inline constraints without
+        // rebuilding the tables, so they stay global there
+        #[cfg(feature = "postgres")]
+        {
+            for table in OWNED {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(Alias::new(table))
+                            .modify_column(ColumnDef::new(Tenant::TenantId).uuid().not_null())
+                            .take(),
+                    )
+                    .await?;
+            }
+
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    "ALTER TABLE users DROP CONSTRAINT IF EXISTS users_email_key;
+                    ALTER TABLE users DROP CONSTRAINT IF EXISTS users_username_key;
+                    ALTER TABLE roles DROP CONSTRAINT IF EXISTS roles_code_key",
+                )
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(User::Table)
+                    .col(Tenant::TenantId)
+                    .col(User::Email)
+                    .name("idx_users_tenant_id_email")
+                    .unique()
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(User::Table)
+                    .col(Tenant::TenantId)
+                    .col(User::Username)
+                    .name("idx_users_tenant_id_username")
+                    .unique()
+                    .take(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(Role::Table)
+                    .col(Tenant::TenantId)
+                    .col(Role::Code)
+                    .name("idx_roles_tenant_id_code")
+                    .unique()
+                    .take(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for name in [
+            "idx_users_tenant_id_email",
+            "idx_users_tenant_id_username",
+            "idx_roles_tenant_id_code",
+        ] {
+            manager
+                .drop_index(Index::drop().name(name).to_owned())
+                .await?;
+        }
+
+        // whatever other tenants held cannot share the global constraints
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "DELETE FROM tenants WHERE code <> 'default';
+                DELETE FROM audit_logs WHERE tenant_id NOT IN (SELECT id FROM tenants)",
+            )
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE users ADD CONSTRAINT users_email_key UNIQUE (email);
+                ALTER TABLE users ADD CONSTRAINT users_username_key UNIQUE (username);
+                ALTER TABLE roles ADD CONSTRAINT roles_code_key UNIQUE (code)",
+            )
+            .await?;
+
+        for table in OWNED.iter().chain(&["audit_logs"]) {
+            manager
+                .drop_index(
+                    Index::drop()
+                        .table(Alias::new(*table))
+                        .name(format!("idx_{table}_tenant_id"))
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Alias::new(*table))
+                        .drop_column(Tenant::TenantId)
+                        .take(),
+                )
+                .await?;
+        }
+
+        manager
+            .drop_table(Table::drop().table(Tenant::Table).take())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+#[allow(dead_code)]
+pub enum Tenant {
+    #[sea_orm(iden = "tenants")]
+    Table,
+    Id,
+    Code,
+    Name,
+    TenantId,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,85 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        #[cfg(feature = "sqlite")]
+        {
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    "ALTER TABLE permissions ADD COLUMN \"group\" VARCHAR(64) NULL DEFAULT NULL",
+                )
+                .await?;
+
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    "ALTER TABLE permissions ADD COLUMN description TEXT NULL DEFAULT NULL",
+                )
+                .await?;
+
+            manager
+                .get_connection()
+                .execute_unprepared(
+                    "ALTER TABLE permissions ADD COLUMN orphaned_at TIMESTAMP NULL DEFAULT NULL",
+                )
+                .await?;
+        }
+
+        #[cfg(feature = "postgres")]
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Permission::Table)
+                    .add_column(
+                        ColumnDef::new(Permission::Group)
+                            .string_len(64)
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .add_column(
+                        ColumnDef::new(Permission::Description)
+                            .text()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .add_column(
+                        ColumnDef::new(Permission::OrphanedAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>),
+                    )
+                    .take(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Permission::Table)
+                    .drop_column(Permission::Group)
+                    .drop_column(Permission::Description)
+                    .drop_column(Permission::OrphanedAt)
+                    .take(),
+            )
+            .await
+    }
+}
+
+#[allow(dead_code)]
+#[derive(DeriveIden)]
+pub enum Permission {
+    #[sea_orm(iden = "permissions")]
+    Table,
+    Group,
+    Description,
+    OrphanedAt,
+}
@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Tables granting roles and permissions to users directly.
+const GRANTS: [&str; 2] = ["role_user", "permission_user"];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in GRANTS {
+            #[cfg(feature = "sqlite")]
+            {
+                manager
+                    .get_connection()
+                    .execute_unprepared(&format!(
+                        "ALTER TABLE {table} ADD COLUMN starts_at TIMESTAMP NULL DEFAULT NULL"
+                    ))
+                    .await?;
+
+                manager
+                    .get_connection()
+                    .execute_unprepared(&format!(
+                        "ALTER TABLE {table} ADD COLUMN expires_at TIMESTAMP NULL DEFAULT NULL"
+                    ))
+                    .await?;
+            }
+
+            #[cfg(feature = "postgres")]
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Alias::new(table))
+                        .add_column(
+                            ColumnDef::new(Grant::StartsAt)
+                                .timestamp()
+                                .null()
+                                .default(None as Option<String>),
+                        )
+                        .add_column(
+                            ColumnDef::new(Grant::ExpiresAt)
+                                .timestamp()
+                                .null()
+                                .default(None as Option<String>),
+                        )
+                        .take(),
+                )
+                .await?;
+
+            // looked up by the cleanup of lapsed grants
+            manager
+                .create_index(
+                    Index::create()
+                        .table(Alias::new(table))
+                        .col(Grant::ExpiresAt)
+                        .name(format!("idx_{table}_expires_at"))
+                        .take(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in GRANTS {
+            manager
+                .drop_index(
+                    Index::drop()
+                        .table(Alias::new(table))
+                        .name(format!("idx_{table}_expires_at"))
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Alias::new(table))
+                        .drop_column(Grant::StartsAt)
+                        .drop_column(Grant::ExpiresAt)
+                        .take(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[allow(dead_code)]
+#[derive(DeriveIden)]
+pub enum Grant {
+    StartsAt,
+    ExpiresAt,
+}
@@ -0,0 +1,106 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240329_094210_create_tenants::Tenant;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Tables left out when rows came to belong to a tenant, along with the
+/// column of the user whose tenant they fall into.
+const OWNED: [(&str, &str); 2] = [("files", "uploaded_by"), ("role_scopes", "user_id")];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (table, user) in OWNED {
+            #[cfg(feature = "sqlite")]
+            manager
+                .get_connection()
+                .execute_unprepared(&format!(
+                    "ALTER TABLE {table} ADD COLUMN tenant_id VARCHAR(36) NULL DEFAULT NULL
+                    REFERENCES tenants (id) ON DELETE CASCADE"
+                ))
+                .await?;
+
+            #[cfg(feature = "postgres")]
+            {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(Alias::new(table))
+                            .add_column(ColumnDef::new(Tenant::TenantId).uuid().null())
+                            .take(),
+                    )
+                    .await?;
+
+                manager
+                    .create_foreign_key(
+                        ForeignKey::create()
+                            .from(Alias::new(table), Tenant::TenantId)
+                            .to(Tenant::Table, Tenant::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .take(),
+                    )
+                    .await?;
+            }
+
+            // files nobody is known to have uploaded stay with the
+            // institution the deployment was set up for
+            manager
+                .get_connection()
+                .execute_unprepared(&format!(
+                    "UPDATE {table} SET tenant_id = COALESCE(
+                        (SELECT tenant_id FROM users WHERE users.id = {table}.{user}),
+                        (SELECT id FROM tenants WHERE code = 'default')
+                    )"
+                ))
+                .await?;
+
+            #[cfg(feature = "postgres")]
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Alias::new(table))
+                        .modify_column(ColumnDef::new(Tenant::TenantId).uuid().not_null())
+                        .take(),
+                )
+                .await?;
+
+            manager
+                .create_index(
+                    Index::create()
+                        .table(Alias::new(table))
+                        .col(Tenant::TenantId)
+                        .name(format!("idx_{table}_tenant_id"))
+                        .take(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (table, _) in OWNED {
+            manager
+                .drop_index(
+                    Index::drop()
+                        .table(Alias::new(table))
+                        .name(format!("idx_{table}_tenant_id"))
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Alias::new(table))
+                        .drop_column(Tenant::TenantId)
+                        .take(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
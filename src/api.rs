@@ -1,15 +1,16 @@
-use chrono::Local;
 use serde_json::json;
 use utoipa::openapi::schema::{Schema, SchemaType};
 use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
 use utoipa::openapi::{KnownFormat, ObjectBuilder, RefOr, SchemaFormat};
 use utoipa::{Modify, OpenApi, ToSchema};
-use uuid::Uuid;
 
 use crate::controllers;
 use crate::models;
+use crate::queue;
 use crate::requests;
 use crate::responses;
+use crate::sheet;
+use crate::storage;
 
 #[derive(OpenApi)]
 #[openapi(
@@ -25,69 +26,230 @@ use crate::responses;
     ),
     tags(
         (name = "Authentication"),
+        (name = "Me"),
         (name = "Master User"),
+        (name = "Impersonation"),
+        (name = "Invitation"),
         (name = "Permission"),
         (name = "Role"),
+        (name = "Job"),
+        (name = "Schedule"),
+        (name = "File"),
+        (name = "Audit"),
+        (name = "Tenant"),
     ),
     paths(
         controllers::auth::login,
         controllers::auth::authenticate,
         controllers::auth::logout,
+        controllers::auth::register,
+        controllers::auth::verify_email,
+        controllers::auth::set_password,
+        controllers::auth::resend_verification,
+
+        controllers::me::show,
+        controllers::me::update,
+        controllers::me::preferences,
+        controllers::me::update_preferences,
+        controllers::me::update_password,
+        controllers::me::delete,
 
         controllers::user::paginate,
         controllers::user::store,
+        controllers::user::import,
+        controllers::user::export,
         controllers::user::show,
+        controllers::user::effective_permissions,
+        controllers::user::check_permission,
         controllers::user::update_general_information,
-        controllers::user::update_password,
+        controllers::user::set_password,
         controllers::user::delete,
+        controllers::user::suspend,
+        controllers::user::deactivate,
+        controllers::user::reactivate,
+        controllers::role_scope::all,
+        controllers::role_scope::store,
+        controllers::role_scope::delete,
+
+        controllers::impersonation::start,
+        controllers::impersonation::stop,
+
+        controllers::invitation::paginate,
+        controllers::invitation::store,
+        controllers::invitation::show,
+        controllers::invitation::resend,
+        controllers::invitation::revoke,
+        controllers::invitation::preview,
+        controllers::invitation::accept,
 
         controllers::permission::paginate,
         controllers::permission::store,
+        controllers::permission::export,
         controllers::permission::show,
         controllers::permission::update,
         controllers::permission::delete,
+        controllers::permission::users,
+        controllers::permission::assign,
+        controllers::permission::revoke,
 
         controllers::role::paginate,
         controllers::role::store,
+        controllers::role::export,
         controllers::role::show,
         controllers::role::update,
         controllers::role::delete,
+        controllers::role::parents,
+        controllers::role::set_parents,
+        controllers::role::users,
+        controllers::role::assign,
+        controllers::role::revoke,
+
+        controllers::job::paginate,
+        controllers::job::show,
+        controllers::job::retry,
+        controllers::job::delete,
+
+        controllers::schedule::all,
+
+        controllers::file::upload,
+        controllers::file::show,
+        controllers::file::download,
+
+        controllers::audit::paginate,
+        controllers::audit::history,
+
+        controllers::tenant::all,
+        controllers::tenant::store,
+        controllers::tenant::show,
+        controllers::tenant::update,
     ),
     components(
-        schemas(T),
         schemas(Id),
         schemas(Timestamp),
         schemas(requests::Sort),
-        schemas(requests::PaginationRequest<models::users::Column>),
+        schemas(sheet::ExportFormat),
+        schemas(responses::Problem),
+        schemas(responses::Ok),
+        schemas(responses::CreatedWithId),
 
         schemas(requests::auth::Login),
+        schemas(requests::auth::Register),
+        schemas(requests::auth::ResendVerification),
 
         schemas(models::users::Column),
+        schemas(requests::PaginationRequest<models::users::Column>),
         schemas(requests::user::UserStoreRequest),
         schemas(requests::user::UserUpdateGeneralInformationRequest),
+        schemas(requests::user::UserUpdateProfileRequest),
         schemas(requests::user::UserUpdatePasswordRequest),
+        schemas(requests::user::UserSetPasswordRequest),
+        schemas(requests::user::UserDeleteAccountRequest),
+        schemas(requests::user::UserImportRequest),
+        schemas(requests::user::ImportMode),
+        schemas(requests::user::AccountStatus),
+        schemas(requests::user::UserFilterRequest),
+        schemas(requests::user::UserSuspendRequest),
+        schemas(requests::user::UserPermissionCheckRequest),
+        schemas(requests::user::UserDeactivateRequest),
+
+        schemas(requests::preference::DateFormat),
+        schemas(requests::preference::Notifications),
+        schemas(requests::preference::PreferenceUpdateRequest),
+        schemas(responses::preference::PreferenceOAS),
+
+        schemas(models::invitations::Column),
+        schemas(requests::PaginationRequest<models::invitations::Column>),
+        schemas(requests::invitation::InvitationStatus),
+        schemas(requests::invitation::InvitationFilterRequest),
+        schemas(requests::invitation::InvitationStoreRequest),
+        schemas(requests::invitation::InvitationAcceptRequest),
 
         schemas(models::permissions::Column),
+        schemas(requests::PaginationRequest<models::permissions::Column>),
+        schemas(requests::permission::PermissionFilterRequest),
         schemas(requests::permission::PermissionStoreRequest),
         schemas(requests::permission::PermissionUpdateRequest),
         schemas(requests::permission::PermissionBulkRequest),
 
+        schemas(models::roles::Column),
+        schemas(requests::PaginationRequest<models::roles::Column>),
         schemas(requests::role::RoleStoreRequest),
         schemas(requests::role::RoleUpdateRequest),
         schemas(requests::role::RoleBulkRequest),
+        schemas(requests::role::RoleParentRequest),
+        schemas(requests::role_scope::RoleScopeStoreRequest),
 
         schemas(responses::user::UserOAS),
         schemas(responses::user::UserPaginationResponse),
+        schemas(responses::user::ImportStatus),
+        schemas(responses::user::UserImportRowOAS),
+        schemas(responses::user::UserImportResponse),
+        schemas(responses::user::UserExportRecord),
+
+        schemas(responses::invitation::InvitationOAS),
+        schemas(responses::invitation::InvitationPaginationResponse),
+        schemas(responses::invitation::InvitationLinkOAS),
 
         schemas(responses::permission::PermissionOAS),
+        schemas(responses::permission::PermissionSummaryOAS),
         schemas(responses::permission::PermissionPaginationResponse),
+        schemas(responses::permission::PermissionSourceType),
+        schemas(responses::permission::PermissionSourceOAS),
+        schemas(responses::permission::EffectivePermissionOAS),
+        schemas(responses::permission::EffectivePermissionListResponse),
+        schemas(responses::permission::PermissionCheckOAS),
 
         schemas(responses::role::RoleOAS),
+        schemas(responses::role::RoleSummaryOAS),
         schemas(responses::role::RolePaginationResponse),
+        schemas(responses::role::RoleListResponse),
+        schemas(responses::role_scope::RoleScopeOAS),
+        schemas(responses::role_scope::RoleScopeListResponse),
+        schemas(responses::role::RoleExportRecord),
+
+        schemas(queue::Status),
+        schemas(models::jobs::Column),
+        schemas(requests::PaginationRequest<models::jobs::Column>),
+        schemas(responses::job::JobOAS),
+        schemas(responses::job::JobPaginationResponse),
+
+        schemas(responses::schedule::ScheduledTaskOAS),
+        schemas(responses::schedule::ScheduledTaskListResponse),
+
+        schemas(storage::Variant),
+        schemas(requests::file::FileUploadRequest),
+        schemas(responses::file::FileOAS),
+
+        schemas(models::audit_logs::Column),
+        schemas(requests::PaginationRequest<models::audit_logs::Column>),
+        schemas(requests::audit::AuditAction),
+        schemas(requests::audit::AuditEntity),
+        schemas(requests::audit::AuditFilterRequest),
+        schemas(responses::audit::AuditLogOAS),
+        schemas(responses::audit::AuditLogPaginationResponse),
+
+        schemas(requests::tenant::TenantStoreRequest),
+        schemas(requests::tenant::TenantUpdateRequest),
+        schemas(responses::tenant::TenantOAS),
+        schemas(responses::tenant::TenantListResponse),
     ),
 )]
 pub struct Doc;
 
+/// The spec as `json` or `yaml`, for generating clients of other languages.
+pub fn dump(format: &str) -> std::io::Result<String> {
+    let spec = Doc::openapi();
+
+    match format {
+        "json" => spec.to_pretty_json().map_err(std::io::Error::other),
+        "yaml" => spec.to_yaml().map_err(std::io::Error::other),
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("unknown spec format {format}, expected json or yaml"),
+        )),
+    }
+}
+
 struct Authentication;
 
 impl Modify for Authentication {
@@ -113,7 +275,7 @@ impl<'__s> ToSchema<'__s> for Id {
         let schema = ObjectBuilder::new()
             .schema_type(SchemaType::String)
             .format(Some(SchemaFormat::KnownFormat(KnownFormat::Uuid)))
-            .example(Some(json!(Uuid::new_v4())))
+            .example(Some(json!("0b6f6e2c-5d1e-4d3a-9f8e-2a4c1b7d9e10")))
             .build();
 
         ("Id", schema.into())
@@ -127,22 +289,35 @@ impl ToSchema<'_> for Timestamp {
         let schema = ObjectBuilder::new()
             .schema_type(SchemaType::String)
             .format(Some(SchemaFormat::KnownFormat(KnownFormat::DateTime)))
-            .example(Some(json!(Local::now())))
+            .example(Some(json!("2024-03-01T08:00:00+07:00")))
             .build();
 
         ("Timestamp", schema.into())
     }
 }
 
-pub struct T;
+#[cfg(test)]
+pub mod test {
+    use std::{env, fs};
 
-impl ToSchema<'_> for T {
-    fn schema() -> (&'static str, RefOr<Schema>) {
-        let schema = ObjectBuilder::new()
-            .schema_type(SchemaType::String)
-            .enum_values(Some(["name", "createdAt"]))
-            .build();
+    use super::dump;
+
+    const SNAPSHOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/docs/openapi.json");
+
+    /// Any change to the documented api has to be reviewed along with the
+    /// snapshot, which `UPDATE_SNAPSHOTS=1 cargo test` rewrites.
+    #[test]
+    pub async fn spec_must_match_snapshot() {
+        let spec = dump("json").unwrap() + "\n";
+
+        if env::var("UPDATE_SNAPSHOTS").is_ok() {
+            fs::write(SNAPSHOT, &spec).unwrap();
+        }
 
-        ("T", schema.into())
+        assert!(
+            fs::read_to_string(SNAPSHOT).is_ok_and(|snapshot| snapshot == spec),
+            "the api spec no longer matches {}, run UPDATE_SNAPSHOTS=1 cargo test to review",
+            SNAPSHOT
+        );
     }
 }
@@ -10,20 +10,36 @@ use utoipa_swagger_ui::{SwaggerUi, Url};
 
 use crate::api::Doc;
 use crate::middlewares::auth::Authenticated;
+use crate::middlewares::impersonation::Impersonation;
+use crate::middlewares::locale::Localize;
+use crate::middlewares::tenant::Tenancy;
 use crate::responses::BadRequest;
-use crate::route;
+use crate::{queue, route, scheduler, tenant};
 
+/// Run the job worker and the scheduler next to the server, over every
+/// tenant.
+pub fn spawn(db: &DatabaseConnection) {
+    tokio::spawn(tenant::every(
+        queue::Worker::new(db.clone(), queue::registry()).run(),
+    ));
+    tokio::spawn(tenant::every(scheduler::scheduler(db.clone()).run()));
+}
+
+/// Routes along with the middlewares every request goes through, for the
+/// server to be the same however it is started.
 pub fn configure(
     db: DatabaseConnection,
 ) -> impl Fn(&mut ServiceConfig) + Clone + Send + Sync + 'static {
+    // shared by every worker, so revoking a session is seen by all of them
+    let authenticated = Data::new(Authenticated::new());
+
     move |cfg: &mut ServiceConfig| {
         let cors = Cors::default()
             .allow_any_origin()
             .allow_any_method()
             .max_age(3600);
 
-        route::route(cfg)
-            .app_data(cors)
+        cfg.app_data(cors)
             .app_data(PayloadConfig::new(MAX))
             .app_data(PathConfig::default().error_handler(|e, _| {
                 BadRequest {
@@ -44,12 +60,21 @@ pub fn configure(
                 .into()
             }))
             .app_data(Data::new(db.clone()))
-            .app_data(Data::new(Authenticated::new()))
-            .service(web::redirect("/", "/doc"))
-            .service(web::redirect("/doc", "/doc/"))
-            .service(SwaggerUi::new("/doc/{_:.*}").urls(vec![(
-                Url::new("learning-management-system", "/doc/api.json"),
-                Doc::openapi(),
-            )]));
+            .app_data(authenticated.clone())
+            .service(
+                web::scope("")
+                    .wrap(Tenancy)
+                    .wrap(Impersonation)
+                    .wrap(Localize)
+                    .configure(|cfg| {
+                        route::route(cfg)
+                            .service(web::redirect("/", "/doc"))
+                            .service(web::redirect("/doc", "/doc/"))
+                            .service(SwaggerUi::new("/doc/{_:.*}").urls(vec![(
+                                Url::new("learning-management-system", "/doc/api.json"),
+                                Doc::openapi(),
+                            )]));
+                    }),
+            );
     }
 }
@@ -0,0 +1,112 @@
+use std::future::Future;
+
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::common::env;
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Checks the response token a CAPTCHA widget handed to the client.
+pub trait Verifier: Send + Sync {
+    /// Whether a token is asked for at all.
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    fn verify(
+        &self,
+        token: &str,
+        ip: Option<&str>,
+    ) -> impl Future<Output = Result<bool, Error>> + Send;
+}
+
+/// Any provider speaking the `siteverify` protocol, which reCAPTCHA,
+/// hCaptcha and Turnstile share.
+pub struct SiteVerify {
+    client: Client,
+    url: String,
+    secret: String,
+}
+
+#[derive(Deserialize)]
+struct SiteVerifyResponse {
+    success: bool,
+}
+
+impl SiteVerify {
+    pub fn new<T: ToString>(url: T, secret: T) -> Self {
+        Self {
+            client: Client::new(),
+            url: url.to_string(),
+            secret: secret.to_string(),
+        }
+    }
+}
+
+impl Verifier for SiteVerify {
+    async fn verify(&self, token: &str, ip: Option<&str>) -> Result<bool, Error> {
+        let mut form = vec![("secret", self.secret.as_str()), ("response", token)];
+
+        if let Some(ip) = ip {
+            form.push(("remoteip", ip));
+        }
+
+        let response = self
+            .client
+            .post(&self.url)
+            .form(&form)
+            .send()
+            .await?
+            .error_for_status()?;
+        let response: SiteVerifyResponse = serde_json::from_slice(&response.bytes().await?)?;
+
+        Ok(response.success)
+    }
+}
+
+/// The verifier selected by `CAPTCHA`, disabled by default.
+pub enum Captcha {
+    Disabled,
+    SiteVerify(SiteVerify),
+}
+
+impl Captcha {
+    pub fn configured() -> Result<Self, Error> {
+        let url = match env::var("CAPTCHA", String::new()).as_str() {
+            "" | "none" => return Ok(Self::Disabled),
+            "recaptcha" => "https://www.google.com/recaptcha/api/siteverify",
+            "hcaptcha" => "https://api.hcaptcha.com/siteverify",
+            "turnstile" => "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+            name => return Err(format!("unknown captcha provider {}", name).into()),
+        };
+        let secret = std::env::var("CAPTCHA_SECRET")
+            .map_err(|_| "CAPTCHA_SECRET must be set to verify captchas")?;
+
+        Ok(Self::SiteVerify(SiteVerify::new(url, secret.as_str())))
+    }
+}
+
+impl Verifier for Captcha {
+    fn enabled(&self) -> bool {
+        !matches!(self, Self::Disabled)
+    }
+
+    async fn verify(&self, token: &str, ip: Option<&str>) -> Result<bool, Error> {
+        match self {
+            Self::Disabled => Ok(true),
+            Self::SiteVerify(verifier) => verifier.verify(token, ip).await,
+        }
+    }
+}
+
+/// A verifier answering the same for every token, to test what relies on one.
+#[cfg(test)]
+pub struct Fake(pub bool);
+
+#[cfg(test)]
+impl Verifier for Fake {
+    async fn verify(&self, _: &str, _: Option<&str>) -> Result<bool, Error> {
+        Ok(self.0)
+    }
+}
@@ -0,0 +1,232 @@
+use crate::models::permissions;
+
+pub const IMPERSONATE_USER: &str = "IMPERSONATE_USER";
+pub const READ_FILE: &str = "READ_FILE";
+
+/// A permission the application checks somewhere, kept in the
+/// `permissions` table by [`crate::dao::permission::sync`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Permission {
+    pub code: &'static str,
+    pub name: &'static str,
+    /// What the permission is about, for permissions to be listed together.
+    pub group: &'static str,
+    pub description: &'static str,
+}
+
+pub const PERMISSIONS: &[Permission] = &[
+    Permission {
+        code: "CREATE_USER",
+        name: "create user",
+        group: "user",
+        description: "Create users and import them from spreadsheets.",
+    },
+    Permission {
+        code: "READ_USER",
+        name: "read user",
+        group: "user",
+        description: "List, search and export users.",
+    },
+    Permission {
+        code: "UPDATE_USER",
+        name: "update user",
+        group: "user",
+        description: "Change the details, status, roles and permissions of users.",
+    },
+    Permission {
+        code: "DELETE_USER",
+        name: "delete user",
+        group: "user",
+        description: "Delete users.",
+    },
+    Permission {
+        code: IMPERSONATE_USER,
+        name: "impersonate user",
+        group: "user",
+        description: "Act as another user, as recorded against both in the audit log.",
+    },
+    Permission {
+        code: "CREATE_ROLE",
+        name: "create role",
+        group: "role",
+        description: "Create roles.",
+    },
+    Permission {
+        code: "READ_ROLE",
+        name: "read role",
+        group: "role",
+        description: "List, search and export roles.",
+    },
+    Permission {
+        code: "UPDATE_ROLE",
+        name: "update role",
+        group: "role",
+        description: "Change roles, the roles above them and their permissions.",
+    },
+    Permission {
+        code: "DELETE_ROLE",
+        name: "delete role",
+        group: "role",
+        description: "Delete roles.",
+    },
+    Permission {
+        code: "CREATE_PERMISSION",
+        name: "create permission",
+        group: "permission",
+        description: "Create permissions.",
+    },
+    Permission {
+        code: "READ_PERMISSION",
+        name: "read permission",
+        group: "permission",
+        description: "List, search and export permissions.",
+    },
+    Permission {
+        code: "UPDATE_PERMISSION",
+        name: "update permission",
+        group: "permission",
+        description: "Rename permissions.",
+    },
+    Permission {
+        code: "DELETE_PERMISSION",
+        name: "delete permission",
+        group: "permission",
+        description: "Delete permissions the application no longer defines.",
+    },
+    Permission {
+        code: READ_FILE,
+        name: "read file",
+        group: "file",
+        description: "See files uploaded by other users.",
+    },
+];
+
+/// The permission of the catalogue `code` stands for, if any.
+pub fn find(code: &str) -> Option<&'static Permission> {
+    PERMISSIONS
+        .iter()
+        .find(|permission| permission.code == code)
+}
+
+/// What syncing the catalogue into the `permissions` table changes.
+#[derive(Debug, Default)]
+pub struct Plan<'a> {
+    /// Permissions of the catalogue missing from the table.
+    pub create: Vec<&'static Permission>,
+    /// Rows that differ from their permission, or were orphaned before it
+    /// came back.
+    pub update: Vec<(&'a permissions::Model, &'static Permission)>,
+    /// Rows the catalogue knows nothing of, not yet marked as orphaned.
+    pub orphan: Vec<&'a permissions::Model>,
+}
+
+impl Plan<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.create.is_empty() && self.update.is_empty() && self.orphan.is_empty()
+    }
+}
+
+/// Compare the rows of the `permissions` table against the catalogue.
+pub fn plan(rows: &[permissions::Model]) -> Plan<'_> {
+    let mut plan = Plan::default();
+
+    for permission in PERMISSIONS {
+        match rows.iter().find(|row| row.code == permission.code) {
+            None => plan.create.push(permission),
+            Some(row) if !matches(row, permission) => plan.update.push((row, permission)),
+            Some(_) => {}
+        }
+    }
+
+    plan.orphan = rows
+        .iter()
+        .filter(|row| row.orphaned_at.is_none() && find(&row.code).is_none())
+        .collect();
+
+    plan
+}
+
+fn matches(row: &permissions::Model, permission: &Permission) -> bool {
+    row.name == permission.name
+        && row.group.as_deref() == Some(permission.group)
+        && row.description.as_deref() == Some(permission.description)
+        && row.orphaned_at.is_none()
+}
+
+#[cfg(test)]
+pub mod test {
+    use std::collections::HashSet;
+
+    use uuid::Uuid;
+
+    use crate::common::time;
+    use crate::models::permissions;
+
+    use super::{find, plan, PERMISSIONS};
+
+    fn row(code: &str) -> permissions::Model {
+        let permission = find(code);
+
+        permissions::Model {
+            id: Uuid::new_v4(),
+            code: code.to_string(),
+            name: code.to_lowercase().replace('_', " "),
+            group: permission.map(|permission| permission.group.to_string()),
+            description: permission.map(|permission| permission.description.to_string()),
+            orphaned_at: None,
+        }
+    }
+
+    #[test]
+    pub async fn catalogue_must_not_repeat_codes() {
+        let codes = PERMISSIONS
+            .iter()
+            .map(|permission| permission.code)
+            .collect::<HashSet<_>>();
+
+        assert_eq!(codes.len(), PERMISSIONS.len());
+    }
+
+    #[test]
+    pub async fn plan_must_leave_a_synced_table_as_is() {
+        let rows = PERMISSIONS
+            .iter()
+            .map(|permission| row(permission.code))
+            .collect::<Vec<_>>();
+
+        assert!(plan(&rows).is_empty());
+    }
+
+    #[test]
+    pub async fn plan_must_create_update_and_orphan() {
+        let mut rows = PERMISSIONS
+            .iter()
+            .skip(1)
+            .map(|permission| row(permission.code))
+            .collect::<Vec<_>>();
+
+        rows[0].description = None;
+        rows[1].orphaned_at = Some(time::now());
+        rows.push(row("PUBLISH_COURSE"));
+        rows.push(row("ARCHIVE_COURSE"));
+        rows.last_mut().unwrap().orphaned_at = Some(time::now());
+
+        let plan = plan(&rows);
+
+        assert_eq!(plan.create, vec![&PERMISSIONS[0]]);
+        assert_eq!(
+            plan.update
+                .iter()
+                .map(|(row, _)| row.code.as_str())
+                .collect::<Vec<_>>(),
+            vec![PERMISSIONS[1].code, PERMISSIONS[2].code]
+        );
+        assert_eq!(
+            plan.orphan
+                .iter()
+                .map(|row| row.code.as_str())
+                .collect::<Vec<_>>(),
+            vec!["PUBLISH_COURSE"]
+        );
+    }
+}
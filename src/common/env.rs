@@ -0,0 +1,15 @@
+use std::str::FromStr;
+
+/// Read `key` from the environment, falling back to `default` when it is
+/// missing or cannot be parsed.
+pub fn var<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Whether the service runs with `APP_ENV=production`.
+pub fn production() -> bool {
+    var("APP_ENV", "development".to_string()) == "production"
+}
@@ -56,6 +56,10 @@ impl From<&str> for Hash {
 }
 
 impl Hash {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
     pub fn verify(self, other: Hash) -> bool {
         self == other
     }
@@ -1,4 +1,6 @@
 pub mod base58;
+pub mod env;
 pub mod hash;
 pub mod log;
+pub mod sign;
 pub mod time;
@@ -0,0 +1,88 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::common::env;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub fn hmac<K: AsRef<[u8]>, M: AsRef<[u8]>>(key: K, message: M) -> [u8; 32] {
+    let mut mac = HmacSha256::new_from_slice(key.as_ref()).expect("hmac accepts any key length");
+
+    mac.update(message.as_ref());
+    mac.finalize().into_bytes().into()
+}
+
+/// The `APP_KEY` shipped in `.env`, fine for development only.
+const DEFAULT_KEY: &str = "change-me-in-production";
+
+/// Fail unless `APP_KEY` can sign, for the server to check as it starts
+/// rather than when the first url is signed.
+pub fn check() -> Result<(), &'static str> {
+    usable(std::env::var("APP_KEY").ok().as_deref(), env::production())
+}
+
+fn usable(key: Option<&str>, production: bool) -> Result<(), &'static str> {
+    match key.map(str::trim) {
+        None | Some("") => Err("APP_KEY must be set to sign urls"),
+        Some(DEFAULT_KEY) if production => Err("APP_KEY must be changed in production"),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Checked by [`check`] at startup, there is no failing here.
+fn key() -> String {
+    std::env::var("APP_KEY").unwrap_or_default()
+}
+
+/// Hex encoded signature of `message` with the `APP_KEY`.
+pub fn sign<T: AsRef<[u8]>>(message: T) -> String {
+    hex::encode(hmac(key(), message))
+}
+
+pub fn verify<T: AsRef<[u8]>>(message: T, signature: &str) -> bool {
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    let mut mac =
+        HmacSha256::new_from_slice(key().as_bytes()).expect("hmac accepts any key length");
+
+    mac.update(message.as_ref());
+    mac.verify_slice(&signature).is_ok()
+}
+
+#[cfg(test)]
+pub mod test {
+    #[test]
+    pub async fn signature_must_be_verifiable() {
+        use super::{sign, verify};
+
+        std::env::set_var("APP_KEY", "secret");
+
+        let signature = sign("file:1:1700000000");
+
+        assert!(verify("file:1:1700000000", &signature));
+        assert!(!verify("file:1:1700000001", &signature));
+        assert!(!verify("file:1:1700000000", "not hex"));
+    }
+
+    #[test]
+    pub async fn key_must_be_set_and_changed_in_production() {
+        use super::{usable, DEFAULT_KEY};
+
+        assert!(usable(None, false).is_err());
+        assert!(usable(Some(" "), false).is_err());
+        assert!(usable(Some(DEFAULT_KEY), false).is_ok());
+        assert!(usable(Some(DEFAULT_KEY), true).is_err());
+        assert!(usable(Some("base64:5vWq3"), true).is_ok());
+    }
+
+    #[test]
+    pub async fn hmac_must_match_rfc_4231() {
+        let mac = super::hmac([0x0b; 20], "Hi There");
+
+        assert_eq!(
+            hex::encode(mac),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+    }
+}
@@ -1,3 +1,11 @@
+use std::cell::Cell;
+use std::future::Future;
+
+use chrono::TimeZone;
+use chrono_tz::Tz;
+
+use crate::models::Timestamp;
+
 #[cfg(feature = "postgres")]
 pub fn now() -> chrono::NaiveDateTime {
     chrono::Utc::now().naive_local()
@@ -11,3 +19,87 @@ pub fn now() -> chrono::DateTime<chrono::Utc> {
 pub fn unix() -> u64 {
     now().timestamp_millis() as u64
 }
+
+tokio::task_local! {
+    static TIMEZONE: Cell<Tz>;
+}
+
+/// Timezone of the request being handled, UTC outside of a request.
+pub fn timezone() -> Tz {
+    TIMEZONE.try_with(Cell::get).unwrap_or(Tz::UTC)
+}
+
+/// Switch the timezone for the rest of the current request.
+pub fn set_timezone(timezone: Tz) {
+    let _ = TIMEZONE.try_with(|current| current.set(timezone));
+}
+
+pub async fn scope<F: Future>(timezone: Tz, f: F) -> F::Output {
+    TIMEZONE.scope(Cell::new(timezone), f).await
+}
+
+pub fn sync_scope<F: FnOnce() -> R, R>(timezone: Tz, f: F) -> R {
+    TIMEZONE.sync_scope(Cell::new(timezone), f)
+}
+
+/// `at`, stored in UTC, as seen in the timezone of the request.
+#[cfg(feature = "postgres")]
+pub fn localize(at: &Timestamp) -> chrono::DateTime<Tz> {
+    timezone().from_utc_datetime(at)
+}
+
+#[cfg(feature = "sqlite")]
+pub fn localize(at: &Timestamp) -> chrono::DateTime<Tz> {
+    timezone().from_utc_datetime(&at.naive_utc())
+}
+
+/// Serialize timestamps as RFC 3339 with the offset of the request's
+/// timezone, for `#[serde(with = "time::rfc3339")]`.
+pub mod rfc3339 {
+    use serde::Serializer;
+
+    use crate::models::Timestamp;
+
+    pub fn serialize<S: Serializer>(at: &Timestamp, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::localize(at).to_rfc3339())
+    }
+
+    pub mod option {
+        use serde::Serializer;
+
+        use crate::models::Timestamp;
+
+        pub fn serialize<S: Serializer>(
+            at: &Option<Timestamp>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match at {
+                Some(at) => super::serialize(at, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::NaiveDate;
+    use chrono_tz::Tz;
+
+    use super::{localize, sync_scope, timezone};
+
+    #[test]
+    pub async fn localize_must_follow_the_scoped_timezone() {
+        let at = NaiveDate::from_ymd_opt(2024, 2, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+
+        assert_eq!(localize(&at).to_rfc3339(), "2024-02-01T12:00:00+00:00");
+        assert_eq!(
+            sync_scope(Tz::Asia__Jakarta, || localize(&at).to_rfc3339()),
+            "2024-02-01T19:00:00+07:00"
+        );
+        assert_eq!(timezone(), Tz::UTC);
+    }
+}
@@ -0,0 +1,64 @@
+use actix_web::web::{Data, Path, Query};
+use actix_web::Responder;
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+use crate::middlewares::auth::Admin;
+use crate::models::audit_logs;
+use crate::requests::audit::{AuditEntity, AuditFilterRequest};
+use crate::requests::PaginationRequest;
+use crate::responses::audit::AuditLogPaginationResponse;
+use crate::responses::{Forbidden, InternalServerError, Unauthorized};
+use crate::services;
+
+/// audit log of the changes made to users, roles and permissions, newest first
+#[utoipa::path(
+    tag = "Audit",
+    operation_id = "audit_paginate",
+    security(("token" = [])),
+    params(PaginationRequest<audit_logs::Column>, AuditFilterRequest),
+    responses(
+        AuditLogPaginationResponse,
+        Unauthorized,
+        Forbidden,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/audit")]
+pub async fn paginate(
+    _: Admin,
+    db: Data<DatabaseConnection>,
+    request: Query<PaginationRequest<audit_logs::Column>>,
+    filter: Query<AuditFilterRequest>,
+) -> impl Responder {
+    services::audit::paginate(&db, request.into_inner(), filter.into_inner()).await
+}
+
+/// history of a single user, role or permission, newest first
+#[utoipa::path(
+    tag = "Audit",
+    operation_id = "audit_history",
+    security(("token" = [])),
+    params(
+        ("entity" = AuditEntity, Path, description = "user, role or permission"),
+        ("id" = Uuid, Path, description = "Id of the user, role or permission"),
+        PaginationRequest<audit_logs::Column>,
+    ),
+    responses(
+        AuditLogPaginationResponse,
+        Unauthorized,
+        Forbidden,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/audit/{entity}/{id}")]
+pub async fn history(
+    _: Admin,
+    db: Data<DatabaseConnection>,
+    path: Path<(AuditEntity, Uuid)>,
+    request: Query<PaginationRequest<audit_logs::Column>>,
+) -> impl Responder {
+    let (entity, id) = path.into_inner();
+
+    services::audit::history(&db, entity, id, request.into_inner()).await
+}
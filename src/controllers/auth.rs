@@ -1,15 +1,25 @@
-use actix_web::web::{Data, Json};
-use actix_web::Responder;
+use actix_web::web::{Data, Json, Path, Query};
+use actix_web::{HttpRequest, Responder};
 use sea_orm::DatabaseConnection;
+use uuid::Uuid;
 
-use crate::middlewares::auth::Auth;
+use crate::captcha::Captcha;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::{Auth, Authenticated};
+use crate::requests::auth::{
+    Login, PasswordLinkRequest, Register, ResendVerification, VerifyEmailRequest,
+};
+use crate::requests::user::UserSetPasswordRequest;
 use crate::responses;
-use crate::responses::{InternalServerError, Ok, Unauthorized, UnprocessableEntity};
-use crate::{requests::auth::Login, services};
+use crate::responses::{
+    AppError, Forbidden, InternalServerError, Ok, Unauthorized, UnprocessableEntity,
+};
+use crate::services;
 
 /// Login by email or username
 #[utoipa::path(
     tag = "Authentication",
+    operation_id = "auth_login",
     responses(responses::auth::Login, UnprocessableEntity, InternalServerError,)
 )]
 #[post("/login")]
@@ -20,6 +30,7 @@ pub async fn login(db: Data<DatabaseConnection>, request: Json<Login>) -> impl R
 /// Get authenticated user, permissions and roles
 #[utoipa::path(
     tag = "Authentication",
+    operation_id = "auth_authenticate",
     security(("token" = [])),
     responses(
         responses::auth::Authenticated,
@@ -35,6 +46,7 @@ pub async fn authenticate(auth: Auth) -> impl Responder {
 /// Logout by request authorization token
 #[utoipa::path(
     tag = "Authentication",
+    operation_id = "auth_logout",
     security(("token" = [])),
     responses(
         Ok,
@@ -46,3 +58,88 @@ pub async fn authenticate(auth: Auth) -> impl Responder {
 pub async fn logout(db: Data<DatabaseConnection>, auth: Auth) -> impl Responder {
     services::auth::logout(&db, auth).await
 }
+
+/// Sign up, when the registration policy allows it
+#[utoipa::path(
+    tag = "Authentication",
+    operation_id = "auth_register",
+    responses(
+        responses::auth::Registered,
+        Forbidden,
+        UnprocessableEntity,
+        InternalServerError,
+    )
+)]
+#[post("/register")]
+pub async fn register(
+    req: HttpRequest,
+    db: Data<DatabaseConnection>,
+    request: Json<Register>,
+) -> impl Responder {
+    let captcha = Captcha::configured().map_err(|e| AppError::Internal(e.to_string()))?;
+    let actor = Actor::guest(&req);
+
+    services::auth::register(&db, &captcha, &actor, request.into_inner()).await
+}
+
+/// Verify the email of a registered user through the mailed link
+#[utoipa::path(
+    tag = "Authentication",
+    operation_id = "auth_verify_email",
+    params(("id" = Uuid, Path, description = "Id of the registered user"), VerifyEmailRequest),
+    responses(Ok, Forbidden, InternalServerError,)
+)]
+#[get("/register/verify/{id}")]
+pub async fn verify_email(
+    req: HttpRequest,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+    request: Query<VerifyEmailRequest>,
+) -> impl Responder {
+    let actor = Actor::guest(&req);
+
+    services::auth::verify_email(&db, &actor, id.into_inner(), request.into_inner()).await
+}
+
+/// Choose the password of an account through the mailed link, which can be used once
+#[utoipa::path(
+    tag = "Authentication",
+    operation_id = "auth_set_password",
+    params(("id" = Uuid, Path, description = "Id of the user"), PasswordLinkRequest),
+    responses(Ok, Forbidden, UnprocessableEntity, InternalServerError,)
+)]
+#[post("/password/{id}")]
+pub async fn set_password(
+    req: HttpRequest,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    id: Path<Uuid>,
+    link: Query<PasswordLinkRequest>,
+    request: Json<UserSetPasswordRequest>,
+) -> impl Responder {
+    let actor = Actor::guest(&req);
+
+    services::auth::set_password(
+        &db,
+        &cache,
+        &actor,
+        id.into_inner(),
+        link.into_inner(),
+        request.into_inner(),
+    )
+    .await
+}
+
+/// Mail a new verification link to a registered user who has not verified yet
+#[utoipa::path(
+    tag = "Authentication",
+    operation_id = "auth_resend_verification",
+    responses(Ok, UnprocessableEntity, InternalServerError,)
+)]
+#[post("/register/verification")]
+pub async fn resend_verification(
+    db: Data<DatabaseConnection>,
+    request: Json<ResendVerification>,
+) -> impl Responder {
+    services::auth::resend_verification(&db, request.into_inner()).await
+}
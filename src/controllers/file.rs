@@ -0,0 +1,76 @@
+use actix_multipart::Multipart;
+use actix_web::web::{Data, Path, Query};
+use actix_web::Responder;
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+use crate::middlewares::auth::Auth;
+use crate::requests::file::FileDownloadRequest;
+use crate::responses::file::FileOAS;
+use crate::responses::{
+    BadRequest, Forbidden, InternalServerError, NotFound, Unauthorized, UnprocessableEntity,
+};
+use crate::services;
+
+/// upload an image, e.g. a profile photo
+#[utoipa::path(
+    tag = "File",
+    operation_id = "file_upload",
+    security(("token" = [])),
+    request_body(content = FileUploadRequest, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Created", body = FileOAS),
+        BadRequest,
+        Unauthorized,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/file")]
+pub async fn upload(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    payload: Multipart,
+) -> impl Responder {
+    services::file::upload(&db, auth, payload).await
+}
+
+/// show file by id with its download urls, to whoever uploaded it or holds READ_FILE
+#[utoipa::path(
+    tag = "File",
+    operation_id = "file_show",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the file")),
+    responses(
+        FileOAS,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/file/{id}")]
+pub async fn show(auth: Auth, db: Data<DatabaseConnection>, id: Path<Uuid>) -> impl Responder {
+    services::file::show(&db, auth, id.into_inner()).await
+}
+
+/// download file content through a signed url
+#[utoipa::path(
+    tag = "File",
+    operation_id = "file_download",
+    params(("id" = Uuid, Path, description = "Id of the file"), FileDownloadRequest),
+    responses(
+        (status = 200, description = "File content", content_type = "application/octet-stream"),
+        Forbidden,
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/file/{id}/download")]
+pub async fn download(
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+    request: Query<FileDownloadRequest>,
+) -> impl Responder {
+    services::file::download(&db, id.into_inner(), request.into_inner()).await
+}
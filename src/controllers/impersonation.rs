@@ -0,0 +1,61 @@
+use actix_web::web::{Data, Path};
+use actix_web::{HttpRequest, Responder};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::{Auth, Authenticated};
+use crate::responses;
+use crate::responses::{Conflict, Forbidden, InternalServerError, NotFound, Ok, Unauthorized};
+use crate::services;
+
+/// act as the given user, the token answered carries both identities and expires on its own
+#[utoipa::path(
+    tag = "Impersonation",
+    operation_id = "impersonation_start",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the user")),
+    responses(
+        responses::auth::Login,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        Conflict,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/user/{id}/impersonate")]
+pub async fn start(
+    req: HttpRequest,
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+) -> impl Responder {
+    let actor = Actor::of(&auth, &req);
+
+    services::impersonation::start(&db, auth, actor, id.into_inner()).await
+}
+
+/// stop impersonating, revoking the impersonation token
+#[utoipa::path(
+    tag = "Impersonation",
+    operation_id = "impersonation_stop",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        Conflict,
+        InternalServerError,
+    ),
+)]
+#[delete("/api/v1/impersonation")]
+pub async fn stop(
+    req: HttpRequest,
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+) -> impl Responder {
+    let actor = Actor::of(&auth, &req);
+
+    services::impersonation::stop(&db, &cache, auth, actor).await
+}
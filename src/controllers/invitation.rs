@@ -0,0 +1,171 @@
+use actix_web::web::{Data, Json, Path, Query};
+use actix_web::{HttpRequest, Responder};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::Admin;
+use crate::models::invitations;
+use crate::requests::invitation::{
+    InvitationAcceptRequest, InvitationFilterRequest, InvitationLinkRequest, InvitationStoreRequest,
+};
+use crate::requests::PaginationRequest;
+use crate::responses::invitation::{
+    InvitationLinkOAS, InvitationOAS, InvitationPaginationResponse,
+};
+use crate::responses::{
+    Conflict, Forbidden, InternalServerError, NotFound, Unauthorized, UnprocessableEntity,
+};
+use crate::services;
+
+/// invitation pagination
+#[utoipa::path(
+    tag = "Invitation",
+    operation_id = "invitation_paginate",
+    security(("token" = [])),
+    params(PaginationRequest<invitations::Column>, InvitationFilterRequest),
+    responses(
+        InvitationPaginationResponse,
+        Unauthorized,
+        Forbidden,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/invitation")]
+pub async fn paginate(
+    _: Admin,
+    db: Data<DatabaseConnection>,
+    request: Query<PaginationRequest<invitations::Column>>,
+    filter: Query<InvitationFilterRequest>,
+) -> impl Responder {
+    services::invitation::paginate(&db, request.into_inner(), filter.into_inner()).await
+}
+
+/// invite an email, mailing it a signed link to create its account
+#[utoipa::path(
+    tag = "Invitation",
+    operation_id = "invitation_store",
+    security(("token" = [])),
+    responses(
+        (status = 201, description = "Created", body = InvitationOAS),
+        Unauthorized,
+        Forbidden,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/invitation")]
+pub async fn store(
+    admin: Admin,
+    db: Data<DatabaseConnection>,
+    request: Json<InvitationStoreRequest>,
+) -> impl Responder {
+    services::invitation::store(&db, admin.0, request.into_inner()).await
+}
+
+/// get invitation by id
+#[utoipa::path(
+    tag = "Invitation",
+    operation_id = "invitation_show",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the invitation")),
+    responses(
+        InvitationOAS,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/invitation/{id}")]
+pub async fn show(_: Admin, db: Data<DatabaseConnection>, id: Path<Uuid>) -> impl Responder {
+    services::invitation::show(&db, id.into_inner()).await
+}
+
+/// mail a new link for a pending or expired invitation, voiding the previous ones
+#[utoipa::path(
+    tag = "Invitation",
+    operation_id = "invitation_resend",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the invitation")),
+    responses(
+        InvitationOAS,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        Conflict,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/invitation/{id}/resend")]
+pub async fn resend(_: Admin, db: Data<DatabaseConnection>, id: Path<Uuid>) -> impl Responder {
+    services::invitation::resend(&db, id.into_inner()).await
+}
+
+/// revoke an invitation that has not been accepted yet
+#[utoipa::path(
+    tag = "Invitation",
+    operation_id = "invitation_revoke",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the invitation")),
+    responses(
+        InvitationOAS,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        Conflict,
+        InternalServerError,
+    ),
+)]
+#[delete("/api/v1/invitation/{id}")]
+pub async fn revoke(_: Admin, db: Data<DatabaseConnection>, id: Path<Uuid>) -> impl Responder {
+    services::invitation::revoke(&db, id.into_inner()).await
+}
+
+/// show who an invite link is for
+#[utoipa::path(
+    tag = "Invitation",
+    operation_id = "invitation_preview",
+    params(("id" = Uuid, Path, description = "Id of the invitation"), InvitationLinkRequest),
+    responses(InvitationLinkOAS, Forbidden, NotFound, Conflict, InternalServerError,)
+)]
+#[get("/api/v1/invitation/{id}/accept")]
+pub async fn preview(
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+    link: Query<InvitationLinkRequest>,
+) -> impl Responder {
+    services::invitation::preview(&db, id.into_inner(), link.into_inner()).await
+}
+
+/// accept an invitation through its link, creating the invited user
+#[utoipa::path(
+    tag = "Invitation",
+    operation_id = "invitation_accept",
+    params(("id" = Uuid, Path, description = "Id of the invitation"), InvitationLinkRequest),
+    responses(
+        (status = 201, description = "Created", body = UserOAS),
+        Forbidden,
+        NotFound,
+        Conflict,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/invitation/{id}/accept")]
+pub async fn accept(
+    req: HttpRequest,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+    link: Query<InvitationLinkRequest>,
+    request: Json<InvitationAcceptRequest>,
+) -> impl Responder {
+    services::invitation::accept(
+        &db,
+        &Actor::guest(&req),
+        id.into_inner(),
+        link.into_inner(),
+        request.into_inner(),
+    )
+    .await
+}
@@ -0,0 +1,106 @@
+use actix_web::web::{Data, Path, Query};
+use actix_web::Responder;
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+use crate::middlewares::auth::PlatformAdmin;
+use crate::models::jobs;
+use crate::requests::job::JobFilterRequest;
+use crate::requests::PaginationRequest;
+use crate::responses::job::{JobOAS, JobPaginationResponse};
+use crate::responses::{Conflict, Forbidden, InternalServerError, NotFound, Ok, Unauthorized};
+use crate::services;
+
+/// Job pagination
+#[utoipa::path(
+    tag = "Job",
+    operation_id = "job_paginate",
+    security(("token" = [])),
+    params(PaginationRequest<jobs::Column>, JobFilterRequest),
+    responses(
+        JobPaginationResponse,
+        Unauthorized,
+        Forbidden,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/job")]
+pub async fn paginate(
+    _: PlatformAdmin,
+    db: Data<DatabaseConnection>,
+    request: Query<PaginationRequest<jobs::Column>>,
+    filter: Query<JobFilterRequest>,
+) -> impl Responder {
+    services::job::paginate(&db, request.into_inner(), filter.into_inner()).await
+}
+
+/// Get job by id
+#[utoipa::path(
+    tag = "Job",
+    operation_id = "job_show",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the job")),
+    responses(
+        JobOAS,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/job/{id}")]
+pub async fn show(
+    _: PlatformAdmin,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+) -> impl Responder {
+    services::job::show(&db, id.into_inner()).await
+}
+
+/// Retry job by id, resetting its attempts
+#[utoipa::path(
+    tag = "Job",
+    operation_id = "job_retry",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the job")),
+    responses(
+        JobOAS,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        Conflict,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/job/{id}/retry")]
+pub async fn retry(
+    _: PlatformAdmin,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+) -> impl Responder {
+    services::job::retry(&db, id.into_inner()).await
+}
+
+/// Delete job by id
+#[utoipa::path(
+    tag = "Job",
+    operation_id = "job_delete",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the job")),
+    responses(
+        Ok,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        Conflict,
+        InternalServerError,
+    ),
+)]
+#[delete("/api/v1/job/{id}")]
+pub async fn delete(
+    _: PlatformAdmin,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+) -> impl Responder {
+    services::job::delete(&db, id.into_inner()).await
+}
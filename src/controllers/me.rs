@@ -0,0 +1,147 @@
+use actix_web::web::{Data, Json};
+use actix_web::{HttpRequest, Responder};
+use sea_orm::DatabaseConnection;
+
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::{Auth, Authenticated};
+use crate::requests::preference::PreferenceUpdateRequest;
+use crate::requests::user::{
+    UserDeleteAccountRequest, UserUpdatePasswordRequest, UserUpdateProfileRequest,
+};
+use crate::responses::preference::PreferenceOAS;
+use crate::responses::user::UserOAS;
+use crate::responses::{Conflict, InternalServerError, Ok, Unauthorized, UnprocessableEntity};
+use crate::services;
+
+/// show the authenticated user
+#[utoipa::path(
+    tag = "Me",
+    operation_id = "me_show",
+    security(("token" = [])),
+    responses(
+        UserOAS,
+        Unauthorized,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/me")]
+pub async fn show(auth: Auth) -> impl Responder {
+    services::me::show(auth).await
+}
+
+/// update the profile of the authenticated user
+#[utoipa::path(
+    tag = "Me",
+    operation_id = "me_update",
+    security(("token" = [])),
+    responses(
+        UserOAS,
+        Unauthorized,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[put("/api/v1/me")]
+pub async fn update(
+    req: HttpRequest,
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    request: Json<UserUpdateProfileRequest>,
+) -> impl Responder {
+    let actor = Actor::of(&auth, &req);
+
+    services::me::update(&db, &cache, auth, &actor, request.into_inner()).await
+}
+
+/// show the preferences of the authenticated user, defaults when never saved
+#[utoipa::path(
+    tag = "Me",
+    operation_id = "me_preferences",
+    security(("token" = [])),
+    responses(
+        PreferenceOAS,
+        Unauthorized,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/me/preferences")]
+pub async fn preferences(auth: Auth) -> impl Responder {
+    services::me::preferences(auth).await
+}
+
+/// update the preferences of the authenticated user, timestamps are answered in their timezone
+#[utoipa::path(
+    tag = "Me",
+    operation_id = "me_update_preferences",
+    security(("token" = [])),
+    responses(
+        PreferenceOAS,
+        Unauthorized,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[put("/api/v1/me/preferences")]
+pub async fn update_preferences(
+    req: HttpRequest,
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    request: Json<PreferenceUpdateRequest>,
+) -> impl Responder {
+    let actor = Actor::of(&auth, &req);
+
+    services::me::update_preferences(&db, &cache, auth, &actor, request.into_inner()).await
+}
+
+/// change the password of the authenticated user, signing out their other sessions
+#[utoipa::path(
+    tag = "Me",
+    operation_id = "me_update_password",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[patch("/api/v1/me/password")]
+pub async fn update_password(
+    req: HttpRequest,
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    request: Json<UserUpdatePasswordRequest>,
+) -> impl Responder {
+    let actor = Actor::of(&auth, &req);
+
+    services::me::update_password(&db, &cache, auth, &actor, request.into_inner()).await
+}
+
+/// delete the account of the authenticated user
+#[utoipa::path(
+    tag = "Me",
+    operation_id = "me_delete",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        Conflict,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[delete("/api/v1/me")]
+pub async fn delete(
+    req: HttpRequest,
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    request: Json<UserDeleteAccountRequest>,
+) -> impl Responder {
+    let actor = Actor::of(&auth, &req);
+
+    services::me::delete(&db, &cache, auth, &actor, request.into_inner()).await
+}
@@ -1,21 +1,30 @@
 use actix_web::web::{Data, Json, Path, Query};
-use actix_web::Responder;
+use actix_web::{HttpRequest, Responder};
 use sea_orm::DatabaseConnection;
 use uuid::Uuid;
 
-use crate::middlewares::auth::Auth;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::{Admin, Auth, Authenticated, PlatformAdmin};
 use crate::models::permissions;
-use crate::requests::permission::{PermissionStoreRequest, PermissionUpdateRequest};
-use crate::requests::PaginationRequest;
+use crate::requests::permission::{
+    PermissionBulkRequest, PermissionFilterRequest, PermissionStoreRequest, PermissionUpdateRequest,
+};
+use crate::requests::user::UserFilterRequest;
+use crate::requests::{ExportRequest, PaginationRequest};
 use crate::responses::permission::{PermissionOAS, PermissionPaginationResponse};
-use crate::responses::{CreatedWithId, InternalServerError, NotFound, Ok, Unauthorized};
-use crate::services;
+use crate::responses::user::UserPaginationResponse;
+use crate::responses::{
+    Conflict, CreatedWithId, Forbidden, InternalServerError, NotFound, Ok, Unauthorized,
+    UnprocessableEntity,
+};
+use crate::{models, services};
 
 /// Permission pagination
 #[utoipa::path(
     tag = "Permission",
+    operation_id = "permission_paginate",
     security(("token" = [])),
-    params(PaginationRequest),
+    params(PaginationRequest<permissions::Column>, PermissionFilterRequest),
     responses(
         PermissionPaginationResponse,
         Unauthorized,
@@ -27,33 +36,76 @@ pub async fn paginate(
     _: Auth,
     db: Data<DatabaseConnection>,
     request: Query<PaginationRequest<permissions::Column>>,
+    filter: Query<PermissionFilterRequest>,
 ) -> impl Responder {
-    services::permission::paginate(&db, request.into_inner()).await
+    services::permission::paginate(&db, request.into_inner(), filter.into_inner()).await
+}
+
+/// Export permissions matching the pagination search, streamed in the requested format
+#[utoipa::path(
+    tag = "Permission",
+    operation_id = "permission_export",
+    security(("token" = [])),
+    params(PaginationRequest<permissions::Column>, PermissionFilterRequest, ExportRequest),
+    responses(
+        (
+            status = 200,
+            description = "Every matching permission, as an attachment",
+            content(
+                ("text/csv" = String),
+                ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" = String),
+                ("application/x-ndjson" = PermissionOAS),
+            ),
+        ),
+        Unauthorized,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/permission/export")]
+pub async fn export(
+    _: Auth,
+    db: Data<DatabaseConnection>,
+    request: Query<PaginationRequest<permissions::Column>>,
+    filter: Query<PermissionFilterRequest>,
+    export: Query<ExportRequest>,
+) -> impl Responder {
+    services::permission::export(
+        &db,
+        request.into_inner(),
+        filter.into_inner(),
+        export.into_inner().format.unwrap_or_default(),
+    )
+    .await
 }
 
 /// Store new permission
 #[utoipa::path(
     tag = "Permission",
+    operation_id = "permission_store",
     security(("token" = [])),
     responses(
         CreatedWithId,
         Unauthorized,
+        Forbidden,
         InternalServerError,
     ),
 )]
 #[post("/api/v1/permission")]
 pub async fn store(
-    _: Auth,
+    req: HttpRequest,
+    admin: PlatformAdmin,
     db: Data<DatabaseConnection>,
     request: Json<PermissionStoreRequest>,
 ) -> impl Responder {
-    services::permission::store(&db, request.into_inner()).await
+    services::permission::store(&db, &Actor::of(&admin, &req), request.into_inner()).await
 }
 
 /// Get permission by id
 #[utoipa::path(
     tag = "Permission",
+    operation_id = "permission_show",
     security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the permission")),
     responses(
         PermissionOAS,
         Unauthorized,
@@ -66,39 +118,142 @@ pub async fn show(_: Auth, db: Data<DatabaseConnection>, id: Path<Uuid>) -> impl
     services::permission::show(&db, id.into_inner()).await
 }
 
-/// Update permission by id
+/// Update permission by id, unless the application defines it
 #[utoipa::path(
     tag = "Permission",
+    operation_id = "permission_update",
     security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the permission")),
     responses(
         Ok,
         Unauthorized,
+        Forbidden,
         NotFound,
+        Conflict,
         InternalServerError,
     ),
 )]
 #[put("/api/v1/permission/{id}")]
 pub async fn update(
-    _: Auth,
+    req: HttpRequest,
+    admin: PlatformAdmin,
     db: Data<DatabaseConnection>,
     id: Path<Uuid>,
     request: Json<PermissionUpdateRequest>,
 ) -> impl Responder {
-    services::permission::update(&db, id.into_inner(), request.into_inner()).await
+    let actor = Actor::of(&admin, &req);
+
+    services::permission::update(&db, &actor, id.into_inner(), request.into_inner()).await
 }
 
-/// Delete permission by id
+/// Delete permission by id, unless the application defines it
 #[utoipa::path(
     tag = "Permission",
+    operation_id = "permission_delete",
     security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the permission")),
     responses(
         Ok,
         Unauthorized,
+        Forbidden,
         NotFound,
+        Conflict,
         InternalServerError,
     ),
 )]
 #[delete("/api/v1/permission/{id}")]
-pub async fn delete(_: Auth, db: Data<DatabaseConnection>, id: Path<Uuid>) -> impl Responder {
-    services::permission::delete(&db, id.into_inner()).await
+pub async fn delete(
+    req: HttpRequest,
+    admin: PlatformAdmin,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+) -> impl Responder {
+    services::permission::delete(&db, &Actor::of(&admin, &req), id.into_inner()).await
+}
+
+/// List the users granted a permission directly by id
+#[utoipa::path(
+    tag = "Permission",
+    operation_id = "permission_users",
+    security(("token" = [])),
+    params(
+        ("id" = Uuid, Path, description = "Id of the permission"),
+        PaginationRequest<models::users::Column>,
+        UserFilterRequest,
+    ),
+    responses(
+        UserPaginationResponse,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/permission/{id}/users")]
+pub async fn users(
+    _: Admin,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+    request: Query<PaginationRequest<models::users::Column>>,
+    filter: Query<UserFilterRequest>,
+) -> impl Responder {
+    services::permission::users(
+        &db,
+        id.into_inner(),
+        request.into_inner(),
+        filter.into_inner(),
+    )
+    .await
+}
+
+/// Grant every permission of the request to each of its users
+#[utoipa::path(
+    tag = "Permission",
+    operation_id = "permission_assign",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        Forbidden,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/permission/assign")]
+pub async fn assign(
+    req: HttpRequest,
+    admin: Admin,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    request: Json<PermissionBulkRequest>,
+) -> impl Responder {
+    let actor = Actor::of(&admin, &req);
+
+    services::permission::assign(&db, &cache, &actor, request.into_inner()).await
+}
+
+/// Revoke every permission of the request from each of its users
+#[utoipa::path(
+    tag = "Permission",
+    operation_id = "permission_revoke",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        Forbidden,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/permission/revoke")]
+pub async fn revoke(
+    req: HttpRequest,
+    admin: Admin,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    request: Json<PermissionBulkRequest>,
+) -> impl Responder {
+    let actor = Actor::of(&admin, &req);
+
+    services::permission::revoke(&db, &cache, &actor, request.into_inner()).await
 }
@@ -1,21 +1,30 @@
 use actix_web::web::{Data, Json, Path, Query};
-use actix_web::Responder;
+use actix_web::{HttpRequest, Responder};
 use sea_orm::DatabaseConnection;
 use uuid::Uuid;
 
-use crate::middlewares::auth::Auth;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::{Admin, Auth, Authenticated};
 use crate::models::roles;
-use crate::requests::role::{RoleStoreRequest, RoleUpdateRequest};
-use crate::requests::PaginationRequest;
-use crate::responses::role::{RoleOAS, RolePaginationResponse};
-use crate::responses::{CreatedWithId, InternalServerError, NotFound, Ok, Unauthorized};
-use crate::services;
+use crate::requests::role::{
+    RoleBulkRequest, RoleParentRequest, RoleStoreRequest, RoleUpdateRequest,
+};
+use crate::requests::user::UserFilterRequest;
+use crate::requests::{ExportRequest, PaginationRequest};
+use crate::responses::role::{RoleListResponse, RoleOAS, RolePaginationResponse};
+use crate::responses::user::UserPaginationResponse;
+use crate::responses::{
+    Conflict, CreatedWithId, Forbidden, InternalServerError, NotFound, Ok, Unauthorized,
+    UnprocessableEntity,
+};
+use crate::{models, services};
 
 /// Role pagination
 #[utoipa::path(
     tag = "Role",
+    operation_id = "role_paginate",
     security(("token" = [])),
-    params(PaginationRequest),
+    params(PaginationRequest<roles::Column>),
     responses(
         RolePaginationResponse,
         Unauthorized,
@@ -31,29 +40,69 @@ pub async fn paginate(
     services::role::paginate(&db, request.into_inner()).await
 }
 
+/// Export roles matching the pagination search, streamed in the requested format
+#[utoipa::path(
+    tag = "Role",
+    operation_id = "role_export",
+    security(("token" = [])),
+    params(PaginationRequest<roles::Column>, ExportRequest),
+    responses(
+        (
+            status = 200,
+            description = "Every matching role, as an attachment",
+            content(
+                ("text/csv" = String),
+                ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" = String),
+                ("application/x-ndjson" = RoleExportRecord),
+            ),
+        ),
+        Unauthorized,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/role/export")]
+pub async fn export(
+    _: Auth,
+    db: Data<DatabaseConnection>,
+    request: Query<PaginationRequest<roles::Column>>,
+    export: Query<ExportRequest>,
+) -> impl Responder {
+    services::role::export(
+        &db,
+        request.into_inner(),
+        export.into_inner().format.unwrap_or_default(),
+    )
+    .await
+}
+
 /// Store new role
 #[utoipa::path(
     tag = "Role",
+    operation_id = "role_store",
     security(("token" = [])),
     responses(
         CreatedWithId,
         Unauthorized,
+        Forbidden,
         InternalServerError,
     ),
 )]
 #[post("/api/v1/role")]
 pub async fn store(
-    _: Auth,
+    req: HttpRequest,
+    admin: Admin,
     db: Data<DatabaseConnection>,
     request: Json<RoleStoreRequest>,
 ) -> impl Responder {
-    services::role::store(&db, request.into_inner()).await
+    services::role::store(&db, &Actor::of(&admin, &req), request.into_inner()).await
 }
 
 /// Get role by id
 #[utoipa::path(
     tag = "Role",
+    operation_id = "role_show",
     security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the role")),
     responses(
         RoleOAS,
         Unauthorized,
@@ -66,39 +115,200 @@ pub async fn show(_: Auth, db: Data<DatabaseConnection>, id: Path<Uuid>) -> impl
     services::role::show(&db, id.into_inner()).await
 }
 
-/// Update role by id
+/// Update role by id, unless it is a system role
 #[utoipa::path(
     tag = "Role",
+    operation_id = "role_update",
     security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the role")),
     responses(
         Ok,
         Unauthorized,
+        Forbidden,
         NotFound,
+        Conflict,
         InternalServerError,
     ),
 )]
 #[put("/api/v1/role/{id}")]
 pub async fn update(
-    _: Auth,
+    req: HttpRequest,
+    admin: Admin,
     db: Data<DatabaseConnection>,
     id: Path<Uuid>,
     request: Json<RoleUpdateRequest>,
 ) -> impl Responder {
-    services::role::update(&db, id.into_inner(), request.into_inner()).await
+    let actor = Actor::of(&admin, &req);
+
+    services::role::update(&db, &actor, id.into_inner(), request.into_inner()).await
 }
 
-/// Delete role by id
+/// Delete role by id, unless it is a system role
 #[utoipa::path(
     tag = "Role",
+    operation_id = "role_delete",
     security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the role")),
     responses(
         Ok,
         Unauthorized,
+        Forbidden,
         NotFound,
+        Conflict,
         InternalServerError,
     ),
 )]
 #[delete("/api/v1/role/{id}")]
-pub async fn delete(_: Auth, db: Data<DatabaseConnection>, id: Path<Uuid>) -> impl Responder {
-    services::role::delete(&db, id.into_inner()).await
+pub async fn delete(
+    req: HttpRequest,
+    admin: Admin,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+) -> impl Responder {
+    services::role::delete(&db, &Actor::of(&admin, &req), id.into_inner()).await
+}
+
+/// Get the roles directly above a role by id
+#[utoipa::path(
+    tag = "Role",
+    operation_id = "role_parents",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the role")),
+    responses(
+        RoleListResponse,
+        Unauthorized,
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/role/{id}/parents")]
+pub async fn parents(_: Auth, db: Data<DatabaseConnection>, id: Path<Uuid>) -> impl Responder {
+    services::role::parents(&db, id.into_inner()).await
+}
+
+/// Replace the roles directly above a role by id, they inherit whatever it is granted
+#[utoipa::path(
+    tag = "Role",
+    operation_id = "role_set_parents",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the role")),
+    responses(
+        RoleListResponse,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        Conflict,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[put("/api/v1/role/{id}/parents")]
+pub async fn set_parents(
+    req: HttpRequest,
+    admin: Admin,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    id: Path<Uuid>,
+    request: Json<RoleParentRequest>,
+) -> impl Responder {
+    let actor = Actor::of(&admin, &req);
+
+    services::role::set_parents(
+        &db,
+        &cache,
+        &admin,
+        &actor,
+        id.into_inner(),
+        request.into_inner(),
+    )
+    .await
+}
+
+/// List the users assigned a role directly by id
+#[utoipa::path(
+    tag = "Role",
+    operation_id = "role_users",
+    security(("token" = [])),
+    params(
+        ("id" = Uuid, Path, description = "Id of the role"),
+        PaginationRequest<models::users::Column>,
+        UserFilterRequest,
+    ),
+    responses(
+        UserPaginationResponse,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/role/{id}/users")]
+pub async fn users(
+    _: Admin,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+    request: Query<PaginationRequest<models::users::Column>>,
+    filter: Query<UserFilterRequest>,
+) -> impl Responder {
+    services::role::users(
+        &db,
+        id.into_inner(),
+        request.into_inner(),
+        filter.into_inner(),
+    )
+    .await
+}
+
+/// Assign every role of the request to each of its users, unless that leaves nobody a superuser without an end
+#[utoipa::path(
+    tag = "Role",
+    operation_id = "role_assign",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        Forbidden,
+        Conflict,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/role/assign")]
+pub async fn assign(
+    req: HttpRequest,
+    admin: Admin,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    request: Json<RoleBulkRequest>,
+) -> impl Responder {
+    let actor = Actor::of(&admin, &req);
+
+    services::role::assign(&db, &cache, &admin, &actor, request.into_inner()).await
+}
+
+/// Revoke every role of the request from each of its users, unless that leaves nobody a superuser
+#[utoipa::path(
+    tag = "Role",
+    operation_id = "role_revoke",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        Forbidden,
+        Conflict,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/role/revoke")]
+pub async fn revoke(
+    req: HttpRequest,
+    admin: Admin,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    request: Json<RoleBulkRequest>,
+) -> impl Responder {
+    let actor = Actor::of(&admin, &req);
+
+    services::role::revoke(&db, &cache, &admin, &actor, request.into_inner()).await
 }
@@ -0,0 +1,93 @@
+use actix_web::web::{Data, Json, Path};
+use actix_web::{HttpRequest, Responder};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::{Admin, Authenticated};
+use crate::requests::role_scope::RoleScopeStoreRequest;
+use crate::responses::role_scope::RoleScopeListResponse;
+use crate::responses::{
+    Conflict, CreatedWithId, Forbidden, InternalServerError, NotFound, Ok, Unauthorized,
+    UnprocessableEntity,
+};
+use crate::services;
+
+/// list the roles a user holds within a scope, such as a course
+#[utoipa::path(
+    tag = "Master User",
+    operation_id = "role_scope_all",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the user")),
+    responses(
+        RoleScopeListResponse,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/user/{id}/scoped-role")]
+pub async fn all(_: Admin, db: Data<DatabaseConnection>, id: Path<Uuid>) -> impl Responder {
+    services::role_scope::all(&db, id.into_inner()).await
+}
+
+/// assign a role to a user within a scope only, granting its permissions on that resource
+#[utoipa::path(
+    tag = "Master User",
+    operation_id = "role_scope_store",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the user")),
+    responses(
+        CreatedWithId,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        Conflict,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/user/{id}/scoped-role")]
+pub async fn store(
+    req: HttpRequest,
+    admin: Admin,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    id: Path<Uuid>,
+    request: Json<RoleScopeStoreRequest>,
+) -> impl Responder {
+    let actor = Actor::of(&admin, &req);
+
+    services::role_scope::store(&db, &cache, &actor, id.into_inner(), request.into_inner()).await
+}
+
+/// revoke a role a user holds within a scope
+#[utoipa::path(
+    tag = "Master User",
+    operation_id = "role_scope_delete",
+    security(("token" = [])),
+    params(
+        ("id" = Uuid, Path, description = "Id of the user"),
+        ("scoped_role_id" = Uuid, Path, description = "Id of the scoped role"),
+    ),
+    responses(
+        Ok,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[delete("/api/v1/user/{id}/scoped-role/{scoped_role_id}")]
+pub async fn delete(
+    req: HttpRequest,
+    admin: Admin,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    path: Path<(Uuid, Uuid)>,
+) -> impl Responder {
+    let (id, scoped_role_id) = path.into_inner();
+
+    services::role_scope::delete(&db, &cache, &Actor::of(&admin, &req), id, scoped_role_id).await
+}
@@ -0,0 +1,25 @@
+use actix_web::web::Data;
+use actix_web::Responder;
+use sea_orm::DatabaseConnection;
+
+use crate::middlewares::auth::PlatformAdmin;
+use crate::responses::schedule::ScheduledTaskListResponse;
+use crate::responses::{Forbidden, InternalServerError, Unauthorized};
+use crate::services;
+
+/// Scheduled maintenance tasks and their last run
+#[utoipa::path(
+    tag = "Schedule",
+    operation_id = "schedule_all",
+    security(("token" = [])),
+    responses(
+        ScheduledTaskListResponse,
+        Unauthorized,
+        Forbidden,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/schedule")]
+pub async fn all(_: PlatformAdmin, db: Data<DatabaseConnection>) -> impl Responder {
+    services::schedule::all(&db).await
+}
@@ -0,0 +1,104 @@
+use actix_web::web::{Data, Json, Path};
+use actix_web::{HttpRequest, Responder};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::PlatformAdmin;
+use crate::requests::tenant::{TenantStoreRequest, TenantUpdateRequest};
+use crate::responses::tenant::{TenantListResponse, TenantOAS};
+use crate::responses::{
+    CreatedWithId, Forbidden, InternalServerError, NotFound, Unauthorized, UnprocessableEntity,
+};
+use crate::services;
+
+/// List every tenant of the deployment
+#[utoipa::path(
+    tag = "Tenant",
+    operation_id = "tenant_all",
+    security(("token" = [])),
+    responses(
+        TenantListResponse,
+        Unauthorized,
+        Forbidden,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/tenant")]
+pub async fn all(_: PlatformAdmin, db: Data<DatabaseConnection>) -> impl Responder {
+    services::tenant::all(&db).await
+}
+
+/// Store new tenant, along with copies of the platform's administrator roles
+#[utoipa::path(
+    tag = "Tenant",
+    operation_id = "tenant_store",
+    security(("token" = [])),
+    responses(
+        CreatedWithId,
+        Unauthorized,
+        Forbidden,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/tenant")]
+pub async fn store(
+    req: HttpRequest,
+    admin: PlatformAdmin,
+    db: Data<DatabaseConnection>,
+    request: Json<TenantStoreRequest>,
+) -> impl Responder {
+    services::tenant::store(&db, &Actor::of(&admin, &req), request.into_inner()).await
+}
+
+/// Get tenant by id
+#[utoipa::path(
+    tag = "Tenant",
+    operation_id = "tenant_show",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the tenant")),
+    responses(
+        TenantOAS,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/tenant/{id}")]
+pub async fn show(
+    _: PlatformAdmin,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+) -> impl Responder {
+    services::tenant::show(&db, id.into_inner()).await
+}
+
+/// Update tenant by id
+#[utoipa::path(
+    tag = "Tenant",
+    operation_id = "tenant_update",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the tenant")),
+    responses(
+        TenantOAS,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[put("/api/v1/tenant/{id}")]
+pub async fn update(
+    req: HttpRequest,
+    admin: PlatformAdmin,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+    request: Json<TenantUpdateRequest>,
+) -> impl Responder {
+    let actor = Actor::of(&admin, &req);
+
+    services::tenant::update(&db, &actor, id.into_inner(), request.into_inner()).await
+}
@@ -1,132 +1,391 @@
+use actix_multipart::Multipart;
 use actix_web::web::{Data, Json, Path, Query};
-use actix_web::Responder;
+use actix_web::{HttpRequest, Responder};
 use sea_orm::DatabaseConnection;
 use uuid::Uuid;
 
-use crate::middlewares::auth::Auth;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::{Admin, Authenticated};
 use crate::models::users;
 use crate::requests::user::{
-    UserStoreRequest, UserUpdateGeneralInformationRequest, UserUpdatePasswordRequest,
+    UserDeactivateRequest, UserFilterRequest, UserImportOptionsRequest, UserPermissionCheckRequest,
+    UserSetPasswordRequest, UserStoreRequest, UserSuspendRequest,
+    UserUpdateGeneralInformationRequest,
 };
-use crate::requests::PaginationRequest;
-use crate::responses::user::{UserOAS, UserPaginationResponse};
+use crate::requests::{ExportRequest, PaginationRequest};
+use crate::responses::permission::{EffectivePermissionListResponse, PermissionCheckOAS};
+use crate::responses::user::{UserImportResponse, UserOAS, UserPaginationResponse};
 use crate::responses::{
-    CreatedWithId, InternalServerError, NotFound, Ok, Unauthorized, UnprocessableEntity,
+    BadRequest, Conflict, CreatedWithId, Forbidden, InternalServerError, NotFound, Ok,
+    Unauthorized, UnprocessableEntity,
 };
 use crate::services;
 
 /// user pagination
 #[utoipa::path(
     tag = "Master User",
+    operation_id = "user_paginate",
     security(("token" = [])),
-    params(PaginationRequest),
+    params(PaginationRequest<users::Column>, UserFilterRequest),
     responses(
         UserPaginationResponse,
         Unauthorized,
+        Forbidden,
         InternalServerError,
     ),
 )]
 #[get("/api/v1/user")]
 pub async fn paginate(
-    _: Auth,
+    _: Admin,
     db: Data<DatabaseConnection>,
     request: Query<PaginationRequest<users::Column>>,
+    filter: Query<UserFilterRequest>,
 ) -> impl Responder {
-    services::user::paginate(&db, request.into_inner()).await
+    services::user::paginate(&db, request.into_inner(), filter.into_inner()).await
+}
+
+/// export users matching the pagination search, streamed in the requested format
+#[utoipa::path(
+    tag = "Master User",
+    operation_id = "user_export",
+    security(("token" = [])),
+    params(PaginationRequest<users::Column>, UserFilterRequest, ExportRequest),
+    responses(
+        (
+            status = 200,
+            description = "Every matching user, as an attachment",
+            content(
+                ("text/csv" = String),
+                ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" = String),
+                ("application/x-ndjson" = UserExportRecord),
+            ),
+        ),
+        Unauthorized,
+        Forbidden,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/user/export")]
+pub async fn export(
+    _: Admin,
+    db: Data<DatabaseConnection>,
+    request: Query<PaginationRequest<users::Column>>,
+    filter: Query<UserFilterRequest>,
+    export: Query<ExportRequest>,
+) -> impl Responder {
+    services::user::export(
+        &db,
+        request.into_inner(),
+        filter.into_inner(),
+        export.into_inner().format.unwrap_or_default(),
+    )
+    .await
 }
 
 /// store new user
 #[utoipa::path(
     tag = "Master User",
+    operation_id = "user_store",
     security(("token" = [])),
     responses(
         CreatedWithId,
         Unauthorized,
+        Forbidden,
         UnprocessableEntity,
         InternalServerError,
     ),
 )]
 #[post("/api/v1/user")]
 pub async fn store(
-    _: Auth,
+    req: HttpRequest,
+    admin: Admin,
     db: Data<DatabaseConnection>,
     request: Json<UserStoreRequest>,
 ) -> impl Responder {
-    services::user::store(&db, request.into_inner()).await
+    services::user::store(&db, &Actor::of(&admin, &req), request.into_inner()).await
+}
+
+/// import users from a csv or xlsx file, see the request body for its columns
+#[utoipa::path(
+    tag = "Master User",
+    operation_id = "user_import",
+    security(("token" = [])),
+    params(UserImportOptionsRequest),
+    request_body(
+        content = UserImportRequest,
+        content_type = "multipart/form-data",
+    ),
+    responses(
+        UserImportResponse,
+        BadRequest,
+        Unauthorized,
+        Forbidden,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/user/import")]
+pub async fn import(
+    req: HttpRequest,
+    admin: Admin,
+    db: Data<DatabaseConnection>,
+    options: Query<UserImportOptionsRequest>,
+    payload: Multipart,
+) -> impl Responder {
+    let actor = Actor::of(&admin, &req);
+
+    services::user::import(&db, &actor, payload, options.into_inner()).await
 }
 
 /// show user by id
 #[utoipa::path(
     tag = "Master User",
+    operation_id = "user_show",
     security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the user")),
     responses(
         UserOAS,
         Unauthorized,
+        Forbidden,
         NotFound,
         InternalServerError,
     ),
 )]
 #[get("/api/v1/user/{id}")]
-pub async fn show(_: Auth, db: Data<DatabaseConnection>, id: Path<Uuid>) -> impl Responder {
+pub async fn show(_: Admin, db: Data<DatabaseConnection>, id: Path<Uuid>) -> impl Responder {
     services::user::show(&db, id.into_inner()).await
 }
 
+/// list every permission of a user by id along with where they hold it from: directly, through an assigned role or inherited from a role below one
+#[utoipa::path(
+    tag = "Master User",
+    operation_id = "user_effective_permissions",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the user")),
+    responses(
+        EffectivePermissionListResponse,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/user/{id}/permissions/effective")]
+pub async fn effective_permissions(
+    _: Admin,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+) -> impl Responder {
+    services::user::effective_permissions(&db, id.into_inner()).await
+}
+
+/// check a permission for a user by id, explaining why it is allowed or denied
+#[utoipa::path(
+    tag = "Master User",
+    operation_id = "user_check_permission",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the user"), UserPermissionCheckRequest),
+    responses(
+        PermissionCheckOAS,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[get("/api/v1/user/{id}/permissions/check")]
+pub async fn check_permission(
+    _: Admin,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+    request: Query<UserPermissionCheckRequest>,
+) -> impl Responder {
+    services::user::check_permission(&db, id.into_inner(), request.into_inner()).await
+}
+
 /// update user by id
 #[utoipa::path(
     tag = "Master User",
+    operation_id = "user_update_general_information",
     security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the user")),
     responses(
         UserOAS,
         Unauthorized,
+        Forbidden,
         NotFound,
+        Conflict,
         UnprocessableEntity,
         InternalServerError,
     ),
 )]
 #[put("/api/v1/user/{id}")]
 pub async fn update_general_information(
-    _: Auth,
+    req: HttpRequest,
+    admin: Admin,
     db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
     id: Path<Uuid>,
     request: Json<UserUpdateGeneralInformationRequest>,
 ) -> impl Responder {
-    services::user::update_general_information(&db, id.into_inner(), request.into_inner()).await
+    let actor = Actor::of(&admin, &req);
+
+    services::user::update_general_information(
+        &db,
+        &cache,
+        &admin,
+        &actor,
+        id.into_inner(),
+        request.into_inner(),
+    )
+    .await
 }
 
-/// update user password by id
+/// set user password by id without the current password, signing out all of their sessions
 #[utoipa::path(
     tag = "Master User",
+    operation_id = "user_set_password",
     security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the user")),
     responses(
         Ok,
         Unauthorized,
+        Forbidden,
         NotFound,
         UnprocessableEntity,
         InternalServerError,
     ),
 )]
 #[patch("/api/v1/user/{id}")]
-pub async fn update_password(
-    _: Auth,
+pub async fn set_password(
+    req: HttpRequest,
+    admin: Admin,
     db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
     id: Path<Uuid>,
-    request: Json<UserUpdatePasswordRequest>,
+    request: Json<UserSetPasswordRequest>,
 ) -> impl Responder {
-    services::user::update_password(&db, id.into_inner(), request.into_inner()).await
+    let actor = Actor::of(&admin, &req);
+
+    services::user::set_password(
+        &db,
+        &cache,
+        &admin,
+        &actor,
+        id.into_inner(),
+        request.into_inner(),
+    )
+    .await
 }
 
 /// delete user by id
 #[utoipa::path(
     tag = "Master User",
+    operation_id = "user_delete",
     security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the user")),
     responses(
         Ok,
         Unauthorized,
+        Forbidden,
         NotFound,
+        Conflict,
         InternalServerError,
     ),
 )]
 #[delete("/api/v1/user/{id}")]
-pub async fn delete(_: Auth, db: Data<DatabaseConnection>, id: Path<Uuid>) -> impl Responder {
-    services::user::delete(&db, id.into_inner()).await
+pub async fn delete(
+    req: HttpRequest,
+    admin: Admin,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    id: Path<Uuid>,
+) -> impl Responder {
+    services::user::delete(&db, &cache, &Actor::of(&admin, &req), id.into_inner()).await
+}
+
+/// suspend user by id, until the given time or indefinitely, signing out all of their sessions
+#[utoipa::path(
+    tag = "Master User",
+    operation_id = "user_suspend",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the user")),
+    responses(
+        Ok,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        Conflict,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/user/{id}/suspend")]
+pub async fn suspend(
+    req: HttpRequest,
+    admin: Admin,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    id: Path<Uuid>,
+    request: Json<UserSuspendRequest>,
+) -> impl Responder {
+    let actor = Actor::of(&admin, &req);
+
+    services::user::suspend(&db, &cache, &actor, id.into_inner(), request.into_inner()).await
+}
+
+/// deactivate user by id, signing out all of their sessions
+#[utoipa::path(
+    tag = "Master User",
+    operation_id = "user_deactivate",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the user")),
+    responses(
+        Ok,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        Conflict,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/user/{id}/deactivate")]
+pub async fn deactivate(
+    req: HttpRequest,
+    admin: Admin,
+    db: Data<DatabaseConnection>,
+    cache: Data<Authenticated>,
+    id: Path<Uuid>,
+    request: Json<UserDeactivateRequest>,
+) -> impl Responder {
+    let actor = Actor::of(&admin, &req);
+
+    services::user::deactivate(&db, &cache, &actor, id.into_inner(), request.into_inner()).await
+}
+
+/// reactivate a suspended or deactivated user by id
+#[utoipa::path(
+    tag = "Master User",
+    operation_id = "user_reactivate",
+    security(("token" = [])),
+    params(("id" = Uuid, Path, description = "Id of the user")),
+    responses(
+        Ok,
+        Unauthorized,
+        Forbidden,
+        NotFound,
+        Conflict,
+        InternalServerError,
+    ),
+)]
+#[post("/api/v1/user/{id}/reactivate")]
+pub async fn reactivate(
+    req: HttpRequest,
+    admin: Admin,
+    db: Data<DatabaseConnection>,
+    id: Path<Uuid>,
+) -> impl Responder {
+    services::user::reactivate(&db, &Actor::of(&admin, &req), id.into_inner()).await
 }
@@ -0,0 +1,317 @@
+use sea_orm::prelude::*;
+use serde_json::{json, Map, Value};
+
+use crate::common::time;
+use crate::dao::grant::{Grants, Period};
+use crate::middlewares::audit::Actor;
+use crate::models::{audit_logs, permissions, role_scopes, roles, tenants, users, Id};
+use crate::requests::audit::{AuditAction, AuditEntity};
+use crate::tenant;
+
+/// What the audit log keeps of an entity.
+pub trait Auditable {
+    const ENTITY: AuditEntity;
+
+    fn id(&self) -> Id;
+
+    fn snapshot(&self) -> Value;
+}
+
+impl Auditable for users::Model {
+    const ENTITY: AuditEntity = AuditEntity::User;
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Everything but the password hash and the bookkeeping timestamps.
+    fn snapshot(&self) -> Value {
+        json!({
+            "name": self.name,
+            "email": self.email,
+            "emailVerifiedAt": self.email_verified_at,
+            "username": self.username,
+            "profilePhotoId": self.profile_photo_id,
+            "locale": self.locale,
+            "registeredAt": self.registered_at,
+            "status": self.status,
+            "statusReason": self.status_reason,
+            "suspendedUntil": self.suspended_until,
+            "deletedAt": self.deleted_at,
+        })
+    }
+}
+
+/// A user along with the codes of what they were granted.
+impl Auditable for (users::Model, Vec<permissions::Model>, Vec<roles::Model>) {
+    const ENTITY: AuditEntity = AuditEntity::User;
+
+    fn id(&self) -> Id {
+        self.0.id()
+    }
+
+    fn snapshot(&self) -> Value {
+        let mut permissions = self
+            .1
+            .iter()
+            .map(|permission| permission.code.clone())
+            .collect::<Vec<_>>();
+        let mut roles = self
+            .2
+            .iter()
+            .map(|role| role.code.clone())
+            .collect::<Vec<_>>();
+
+        permissions.sort();
+        roles.sort();
+
+        let mut snapshot = self.0.snapshot();
+        snapshot["permissions"] = json!(permissions);
+        snapshot["roles"] = json!(roles);
+        snapshot
+    }
+}
+
+/// A user along with what they were granted, and for those granted for a
+/// while only, when that is.
+impl Auditable for Grants {
+    const ENTITY: AuditEntity = AuditEntity::User;
+
+    fn id(&self) -> Id {
+        self.0.id()
+    }
+
+    fn snapshot(&self) -> Value {
+        let period = |period: &Period| json!({ "startsAt": period.starts_at, "expiresAt": period.expires_at });
+        let permissions = self
+            .1
+            .iter()
+            .filter(|(_, p)| p.is_bounded())
+            .map(|(permission, p)| (permission.code.clone(), period(p)))
+            .collect::<Map<_, _>>();
+        let roles = self
+            .2
+            .iter()
+            .filter(|(_, p)| p.is_bounded())
+            .map(|(role, p)| (role.code.clone(), period(p)))
+            .collect::<Map<_, _>>();
+
+        let held: (users::Model, Vec<permissions::Model>, Vec<roles::Model>) = (
+            self.0.clone(),
+            self.1
+                .iter()
+                .map(|(permission, _)| permission.clone())
+                .collect(),
+            self.2.iter().map(|(role, _)| role.clone()).collect(),
+        );
+        let mut snapshot = held.snapshot();
+
+        if !permissions.is_empty() {
+            snapshot["permissionPeriods"] = Value::Object(permissions);
+        }
+
+        if !roles.is_empty() {
+            snapshot["rolePeriods"] = Value::Object(roles);
+        }
+
+        snapshot
+    }
+}
+
+/// A user along with the roles they hold within a scope, as
+/// `ROLE@scope_type:scope_id`.
+impl Auditable for (users::Model, Vec<(role_scopes::Model, roles::Model)>) {
+    const ENTITY: AuditEntity = AuditEntity::User;
+
+    fn id(&self) -> Id {
+        self.0.id()
+    }
+
+    fn snapshot(&self) -> Value {
+        let mut scoped = self
+            .1
+            .iter()
+            .map(|(scope, role)| format!("{}@{}:{}", role.code, scope.scope_type, scope.scope_id))
+            .collect::<Vec<_>>();
+
+        scoped.sort();
+
+        let mut snapshot = self.0.snapshot();
+        snapshot["scopedRoles"] = json!(scoped);
+        snapshot
+    }
+}
+
+impl Auditable for roles::Model {
+    const ENTITY: AuditEntity = AuditEntity::Role;
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn snapshot(&self) -> Value {
+        json!({ "code": self.code, "name": self.name })
+    }
+}
+
+/// A role along with the codes of the roles directly above it.
+impl Auditable for (roles::Model, Vec<roles::Model>) {
+    const ENTITY: AuditEntity = AuditEntity::Role;
+
+    fn id(&self) -> Id {
+        self.0.id()
+    }
+
+    fn snapshot(&self) -> Value {
+        let mut parents = self
+            .1
+            .iter()
+            .map(|parent| parent.code.clone())
+            .collect::<Vec<_>>();
+
+        parents.sort();
+
+        let mut snapshot = self.0.snapshot();
+        snapshot["parents"] = json!(parents);
+        snapshot
+    }
+}
+
+impl Auditable for permissions::Model {
+    const ENTITY: AuditEntity = AuditEntity::Permission;
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn snapshot(&self) -> Value {
+        json!({
+            "code": self.code,
+            "name": self.name,
+            "group": self.group,
+            "description": self.description,
+            "orphanedAt": self.orphaned_at,
+        })
+    }
+}
+
+impl Auditable for tenants::Model {
+    const ENTITY: AuditEntity = AuditEntity::Tenant;
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn snapshot(&self) -> Value {
+        json!({ "code": self.code, "name": self.name })
+    }
+}
+
+/// The fields of two snapshots that differ, as they were and as they became.
+pub fn diff(before: &Value, after: &Value) -> (Value, Value) {
+    let (Some(before), Some(after)) = (before.as_object(), after.as_object()) else {
+        return (before.clone(), after.clone());
+    };
+    let mut old = Map::new();
+    let mut new = Map::new();
+
+    for key in before.keys().chain(after.keys()) {
+        let was = before.get(key).unwrap_or(&Value::Null);
+        let is = after.get(key).unwrap_or(&Value::Null);
+
+        if was != is {
+            old.insert(key.clone(), was.clone());
+            new.insert(key.clone(), is.clone());
+        }
+    }
+
+    (Value::Object(old), Value::Object(new))
+}
+
+pub async fn record<C: ConnectionTrait>(
+    db: &C,
+    actor: &Actor,
+    action: AuditAction,
+    entity: AuditEntity,
+    entity_id: Id,
+    before: Option<Value>,
+    after: Option<Value>,
+) -> Result<audit_logs::Model, DbErr> {
+    audit_logs::ActiveModel::from(audit_logs::Model {
+        id: Uuid::new_v4(),
+        tenant_id: tenant::current(),
+        actor_id: actor.user_id,
+        impersonator_id: actor.impersonator_id,
+        action: action.as_str().to_string(),
+        entity_type: entity.as_str().to_string(),
+        entity_id,
+        before,
+        after,
+        ip: actor.ip.clone(),
+        user_agent: actor.user_agent.clone(),
+        created_at: time::now(),
+    })
+    .insert(db)
+    .await
+}
+
+/// Record the creation of `after`, the deletion of `before`, or the fields
+/// changed between them, skipping updates that changed nothing. Meant to run
+/// in the transaction of the change itself.
+pub async fn change<C: ConnectionTrait, T: Auditable>(
+    db: &C,
+    actor: &Actor,
+    before: Option<&T>,
+    after: Option<&T>,
+) -> Result<(), DbErr> {
+    let (action, id, before, after) = match (before, after) {
+        (None, Some(after)) => (
+            AuditAction::Create,
+            after.id(),
+            None,
+            Some(after.snapshot()),
+        ),
+        (Some(before), None) => (
+            AuditAction::Delete,
+            before.id(),
+            Some(before.snapshot()),
+            None,
+        ),
+        (Some(before), Some(after)) => {
+            let (old, new) = diff(&before.snapshot(), &after.snapshot());
+
+            if new.as_object().is_some_and(Map::is_empty) {
+                return Ok(());
+            }
+
+            (AuditAction::Update, after.id(), Some(old), Some(new))
+        }
+        (None, None) => return Ok(()),
+    };
+
+    record(db, actor, action, T::ENTITY, id, before, after).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod test {
+    use serde_json::json;
+
+    use super::diff;
+
+    #[test]
+    pub async fn diff_must_only_keep_changed_fields() {
+        let (before, after) = diff(
+            &json!({ "name": "john", "email": "john@local.id", "roles": ["ADMIN"] }),
+            &json!({ "name": "john", "email": "doe@local.id", "roles": [] }),
+        );
+
+        assert_eq!(
+            before,
+            json!({ "email": "john@local.id", "roles": ["ADMIN"] })
+        );
+        assert_eq!(after, json!({ "email": "doe@local.id", "roles": [] }));
+        assert_eq!(diff(&json!({ "a": 1 }), &json!({ "a": 1 })).1, json!({}));
+    }
+}
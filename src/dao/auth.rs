@@ -9,9 +9,10 @@ pub async fn generate(
     expired_at: Option<Timestamp>,
 ) -> Result<tokens::Model, DbErr> {
     let token = tokens::ActiveModel::from(tokens::Model {
-        id: Uuid::new_v4().into(),
-        user_id: user.id.clone(),
+        id: Uuid::new_v4(),
+        user_id: user.id,
         expired_at,
+        impersonator_id: None,
     });
 
     match token.insert(db).await {
@@ -34,3 +35,21 @@ pub async fn delete<I: Into<Id>>(db: &DatabaseConnection, user_id: I) -> Result<
 
     Ok(())
 }
+
+/// Delete every token of `user_id` but `except`, signing out its other sessions.
+pub async fn revoke<I: Into<Id>>(
+    db: &DatabaseConnection,
+    user_id: I,
+    except: Option<Id>,
+) -> Result<(), DbErr> {
+    let id: Id = user_id.into();
+    let mut query = tokens::Entity::delete_many().filter(tokens::Column::UserId.eq(id));
+
+    if let Some(except) = except {
+        query = query.filter(tokens::Column::Id.ne(except));
+    }
+
+    query.exec(db).await?;
+
+    Ok(())
+}
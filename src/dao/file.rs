@@ -0,0 +1,22 @@
+use sea_orm::prelude::*;
+
+use crate::common::log;
+use crate::models::{files, Id};
+use crate::tenant::Tenanted;
+
+pub async fn find<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Option<files::Model> {
+    let id: Id = id.into();
+
+    match files::Entity::find_by_id(id).tenanted().one(db).await {
+        Ok(file) => file,
+        Err(e) => {
+            log::error!(find, "{}", e);
+
+            None
+        }
+    }
+}
+
+pub async fn store(db: &DatabaseConnection, file: files::Model) -> Result<files::Model, DbErr> {
+    files::ActiveModel::from(file).insert(db).await
+}
@@ -0,0 +1,386 @@
+use sea_orm::prelude::*;
+use sea_orm::TransactionTrait;
+use sea_query::{Condition, Expr};
+
+use crate::common::log;
+use crate::dao;
+use crate::middlewares::audit::Actor;
+use crate::models::{permission_user, permissions, role_user, roles, users, Id, Timestamp};
+use crate::tenant;
+
+/// When a grant is in effect, from `starts_at` until `expires_at`, either
+/// end left open when empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Period {
+    pub starts_at: Option<Timestamp>,
+    pub expires_at: Option<Timestamp>,
+}
+
+impl Period {
+    pub fn is_bounded(&self) -> bool {
+        self.starts_at.is_some() || self.expires_at.is_some()
+    }
+
+    pub fn has_lapsed(&self, at: Timestamp) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= at)
+    }
+
+    /// The first moment after `at` the grant comes into or out of effect.
+    pub fn next_change(&self, at: Timestamp) -> Option<Timestamp> {
+        [self.starts_at, self.expires_at]
+            .into_iter()
+            .flatten()
+            .filter(|change| *change > at)
+            .min()
+    }
+}
+
+/// What a user is granted directly, each along with when it is in effect.
+pub type Grants = (
+    users::Model,
+    Vec<(permissions::Model, Period)>,
+    Vec<(roles::Model, Period)>,
+);
+
+/// Entities granting something to a user for a [`Period`].
+pub trait Bounded: EntityTrait {
+    fn user() -> Self::Column;
+
+    fn starts() -> Self::Column;
+
+    fn expires() -> Self::Column;
+
+    fn period(model: &Self::Model) -> Period;
+}
+
+impl Bounded for role_user::Entity {
+    fn user() -> Self::Column {
+        role_user::Column::UserId
+    }
+
+    fn starts() -> Self::Column {
+        role_user::Column::StartsAt
+    }
+
+    fn expires() -> Self::Column {
+        role_user::Column::ExpiresAt
+    }
+
+    fn period(model: &Self::Model) -> Period {
+        Period {
+            starts_at: model.starts_at,
+            expires_at: model.expires_at,
+        }
+    }
+}
+
+impl Bounded for permission_user::Entity {
+    fn user() -> Self::Column {
+        permission_user::Column::UserId
+    }
+
+    fn starts() -> Self::Column {
+        permission_user::Column::StartsAt
+    }
+
+    fn expires() -> Self::Column {
+        permission_user::Column::ExpiresAt
+    }
+
+    fn period(model: &Self::Model) -> Period {
+        Period {
+            starts_at: model.starts_at,
+            expires_at: model.expires_at,
+        }
+    }
+}
+
+/// Rows of `E` that started by `at` and have not lapsed yet.
+pub fn in_effect<E: Bounded>(at: Timestamp) -> Condition {
+    Condition::all()
+        .add(
+            Condition::any()
+                .add(E::starts().is_null())
+                .add(E::starts().lte(at)),
+        )
+        .add(
+            Condition::any()
+                .add(E::expires().is_null())
+                .add(E::expires().gt(at)),
+        )
+}
+
+/// The soonest moment after `at` any grant of `user_id` comes into or out
+/// of effect, after which whatever was resolved of the user is stale.
+pub async fn next_change<I: Into<Id>>(
+    db: &DatabaseConnection,
+    user_id: I,
+    at: Timestamp,
+) -> Result<Option<Timestamp>, DbErr> {
+    let user_id: Id = user_id.into();
+    let roles = upcoming::<role_user::Entity>(db, &user_id, at).await?;
+    let permissions = upcoming::<permission_user::Entity>(db, &user_id, at).await?;
+
+    Ok(roles.into_iter().chain(permissions).min())
+}
+
+async fn upcoming<E: Bounded>(
+    db: &DatabaseConnection,
+    user_id: &Id,
+    at: Timestamp,
+) -> Result<Vec<Timestamp>, DbErr> {
+    Ok(E::find()
+        .filter(E::user().eq(*user_id))
+        .filter(
+            Condition::any()
+                .add(E::starts().gt(at))
+                .add(E::expires().gt(at)),
+        )
+        .all(db)
+        .await?
+        .iter()
+        .filter_map(|row| E::period(row).next_change(at))
+        .collect())
+}
+
+/// Remove every grant that expired by `at`, recording what each user lost
+/// within their tenant, and answer how many users lost any. A superuser
+/// role nobody else holds is kept without an end instead, for the tenant
+/// not to be left without anyone to manage it.
+pub async fn lapse(db: &DatabaseConnection, at: Timestamp) -> Result<usize, DbErr> {
+    let lapsed = users::Entity::find()
+        .filter(
+            Condition::any()
+                .add(
+                    users::Column::Id.in_subquery(
+                        sea_query::Query::select()
+                            .column(permission_user::Column::UserId)
+                            .from(permission_user::Entity)
+                            .and_where(permission_user::Column::ExpiresAt.lte(at))
+                            .take(),
+                    ),
+                )
+                .add(
+                    users::Column::Id.in_subquery(
+                        sea_query::Query::select()
+                            .column(role_user::Column::UserId)
+                            .from(role_user::Entity)
+                            .and_where(role_user::Column::ExpiresAt.lte(at))
+                            .take(),
+                    ),
+                ),
+        )
+        .all(db)
+        .await?;
+    let count = lapsed.len();
+
+    for before in dao::user::grants(db, lapsed).await? {
+        let superuser = dao::role::superuser(db, before.0.tenant_id).await?;
+        let kept = match dao::user::holders(db, superuser.clone()).await?[..] {
+            [] => before
+                .2
+                .iter()
+                .filter(|(role, period)| period.has_lapsed(at) && superuser.contains(&role.id))
+                .map(|(role, _)| role.id)
+                .collect(),
+            _ => vec![],
+        };
+        let mut after = before.clone();
+
+        after.1.retain(|(_, period)| !period.has_lapsed(at));
+        after
+            .2
+            .retain(|(role, period)| !period.has_lapsed(at) || kept.contains(&role.id));
+        after
+            .2
+            .iter_mut()
+            .filter(|(role, _)| kept.contains(&role.id))
+            .for_each(|(_, period)| period.expires_at = None);
+
+        let tx = db.begin().await?;
+
+        if !kept.is_empty() {
+            log::error!(
+                lapse,
+                "{} is the last superuser of their tenant, their role is kept",
+                before.0.username
+            );
+
+            role_user::Entity::update_many()
+                .col_expr(role_user::Column::ExpiresAt, Expr::value(None::<Timestamp>))
+                .filter(role_user::Column::UserId.eq(before.0.id))
+                .filter(role_user::Column::RoleId.is_in(kept))
+                .exec(&tx)
+                .await?;
+        }
+
+        permission_user::Entity::delete_many()
+            .filter(permission_user::Column::UserId.eq(before.0.id))
+            .filter(permission_user::Column::ExpiresAt.lte(at))
+            .exec(&tx)
+            .await?;
+        role_user::Entity::delete_many()
+            .filter(role_user::Column::UserId.eq(before.0.id))
+            .filter(role_user::Column::ExpiresAt.lte(at))
+            .exec(&tx)
+            .await?;
+
+        tenant::scope(
+            before.0.tenant_id,
+            dao::audit::change(&tx, &Actor::system(), Some(&before), Some(&after)),
+        )
+        .await?;
+        tx.commit().await?;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+pub mod test {
+    use std::collections::HashSet;
+
+    use chrono::{Duration, NaiveDateTime};
+    use sea_orm::prelude::*;
+    use sea_orm::DatabaseConnection;
+
+    use crate::common::time;
+    use crate::dao;
+    use crate::models::{role_user, roles, users, Timestamp};
+
+    use super::Period;
+
+    /// A role of `code` in the tenant of `user`.
+    pub async fn role(db: &DatabaseConnection, user: &users::Model, code: &str) -> roles::Model {
+        roles::ActiveModel::from(roles::Model {
+            id: Uuid::new_v4(),
+            tenant_id: user.tenant_id,
+            code: code.to_string(),
+            name: code.to_lowercase(),
+            system: false,
+        })
+        .insert(db)
+        .await
+        .unwrap()
+    }
+
+    pub async fn assign(
+        db: &DatabaseConnection,
+        user: &users::Model,
+        role: &roles::Model,
+        period: Period,
+    ) {
+        role_user::ActiveModel::from(role_user::Model {
+            id: Uuid::new_v4(),
+            role_id: role.id,
+            user_id: user.id,
+            starts_at: period.starts_at,
+            expires_at: period.expires_at,
+        })
+        .insert(db)
+        .await
+        .unwrap();
+    }
+
+    fn at(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    pub async fn period_must_lapse_at_its_expiry() {
+        let term = Period {
+            starts_at: Some(at("2024-02-01 00:00")),
+            expires_at: Some(at("2024-06-30 00:00")),
+        };
+
+        assert!(!Period::default().has_lapsed(at("2024-01-01 00:00")));
+        assert!(!term.has_lapsed(at("2024-01-31 23:59")));
+        assert!(!term.has_lapsed(at("2024-06-29 23:59")));
+        assert!(term.has_lapsed(at("2024-06-30 00:00")));
+    }
+
+    #[test]
+    pub async fn period_must_change_at_its_next_bound() {
+        let term = Period {
+            starts_at: Some(at("2024-02-01 00:00")),
+            expires_at: Some(at("2024-06-30 00:00")),
+        };
+
+        assert_eq!(
+            term.next_change(at("2024-01-01 00:00")),
+            Some(at("2024-02-01 00:00"))
+        );
+        assert_eq!(
+            term.next_change(at("2024-02-01 00:00")),
+            Some(at("2024-06-30 00:00"))
+        );
+        assert_eq!(term.next_change(at("2024-07-01 00:00")), None);
+        assert_eq!(Period::default().next_change(at("2024-01-01 00:00")), None);
+    }
+
+    #[test]
+    pub async fn grant_must_only_be_in_effect_once_started() {
+        let db = dao::test::database().await;
+        let user = dao::user::test::user(&db).await;
+        let teacher = role(&db, &user, "TEACHER").await;
+        let tutor = role(&db, &user, "TUTOR").await;
+        let started = |role: &roles::Model| HashSet::from([role.id]);
+
+        assign(
+            &db,
+            &user,
+            &teacher,
+            Period {
+                starts_at: Some(time::now() - Duration::days(1)),
+                expires_at: None,
+            },
+        )
+        .await;
+        assign(
+            &db,
+            &user,
+            &tutor,
+            Period {
+                starts_at: Some(time::now() + Duration::days(1)),
+                expires_at: None,
+            },
+        )
+        .await;
+
+        assert_eq!(
+            dao::user::holders(&db, started(&teacher)).await.unwrap(),
+            vec![user.id]
+        );
+        assert!(dao::user::holders(&db, started(&tutor))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    pub async fn lapse_must_keep_the_last_superuser() {
+        let db = dao::test::database().await;
+        let user = dao::user::test::user(&db).await;
+        let superuser = role(&db, &user, "SUPERUSER").await;
+        let teacher = role(&db, &user, "TEACHER").await;
+        let lapsed = Period {
+            starts_at: None,
+            expires_at: Some(time::now() - Duration::hours(1)),
+        };
+
+        assign(&db, &user, &superuser, lapsed.clone()).await;
+        assign(&db, &user, &teacher, lapsed).await;
+
+        assert_eq!(super::lapse(&db, time::now()).await.unwrap(), 1);
+
+        let held = role_user::Entity::find()
+            .all(&db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|row| (row.role_id, row.expires_at))
+            .collect::<Vec<_>>();
+
+        assert_eq!(held, vec![(superuser.id, None::<Timestamp>)]);
+    }
+}
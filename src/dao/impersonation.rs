@@ -0,0 +1,134 @@
+use sea_orm::prelude::*;
+use sea_orm::TransactionTrait;
+use serde_json::json;
+
+use crate::common::time;
+use crate::dao;
+use crate::middlewares::audit::Actor;
+use crate::models::{impersonations, tokens, users, Id, Timestamp};
+use crate::requests::audit::{AuditAction, AuditEntity};
+
+/// Issue a token acting as `user` on behalf of `impersonator`, recording
+/// who started it and from where, in the audit log as well.
+pub async fn start(
+    db: &DatabaseConnection,
+    impersonator: &users::Model,
+    user: &users::Model,
+    expired_at: Timestamp,
+    actor: &Actor,
+) -> Result<(tokens::Model, impersonations::Model), DbErr> {
+    let tx = db.begin().await?;
+    let token = tokens::ActiveModel::from(tokens::Model {
+        id: Uuid::new_v4(),
+        user_id: user.id,
+        expired_at: Some(expired_at),
+        impersonator_id: Some(impersonator.id),
+    })
+    .insert(&tx)
+    .await?;
+
+    let impersonation = impersonations::ActiveModel::from(impersonations::Model {
+        id: Uuid::new_v4(),
+        impersonator_id: impersonator.id,
+        user_id: user.id,
+        token_id: token.id,
+        ip: actor.ip.clone(),
+        user_agent: actor.user_agent.clone(),
+        started_at: time::now(),
+        ended_at: None,
+    })
+    .insert(&tx)
+    .await?;
+
+    dao::audit::record(
+        &tx,
+        actor,
+        AuditAction::ImpersonationStarted,
+        AuditEntity::User,
+        user.id,
+        None,
+        Some(json!({ "impersonationId": impersonation.id, "expiredAt": expired_at })),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok((token, impersonation))
+}
+
+/// Delete the impersonation token of `user` and close its record, `actor`
+/// being the impersonator.
+pub async fn stop(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    user: &users::Model,
+    token: &Id,
+) -> Result<(), DbErr> {
+    let tx = db.begin().await?;
+
+    tokens::Entity::delete_by_id(*token).exec(&tx).await?;
+
+    impersonations::Entity::update_many()
+        .col_expr(impersonations::Column::EndedAt, Expr::value(time::now()))
+        .filter(impersonations::Column::TokenId.eq(*token))
+        .filter(impersonations::Column::EndedAt.is_null())
+        .exec(&tx)
+        .await?;
+
+    dao::audit::record(
+        &tx,
+        actor,
+        AuditAction::ImpersonationStopped,
+        AuditEntity::User,
+        user.id,
+        None,
+        None,
+    )
+    .await?;
+    tx.commit().await
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::Duration;
+    use sea_orm::prelude::*;
+    use sea_orm::QueryOrder;
+
+    use crate::common::time;
+    use crate::dao;
+    use crate::middlewares::audit::Actor;
+    use crate::models::audit_logs;
+
+    #[test]
+    pub async fn impersonation_must_be_audited() {
+        let db = dao::test::database().await;
+        let admin = dao::user::test::user(&db).await;
+        let user = dao::user::test::user(&db).await;
+        let actor = Actor {
+            user_id: Some(admin.id),
+            ..Actor::system()
+        };
+        let expired_at = time::now() + Duration::hours(1);
+        let (token, _) = super::start(&db, &admin, &user, expired_at, &actor)
+            .await
+            .unwrap();
+
+        super::stop(&db, &actor, &user, &token.id).await.unwrap();
+
+        let logs = audit_logs::Entity::find()
+            .order_by_asc(audit_logs::Column::CreatedAt)
+            .all(&db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|log| (log.action, log.actor_id, log.entity_id))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            logs,
+            vec![
+                ("impersonation.started".to_string(), Some(admin.id), user.id),
+                ("impersonation.stopped".to_string(), Some(admin.id), user.id),
+            ]
+        );
+    }
+}
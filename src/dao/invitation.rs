@@ -0,0 +1,158 @@
+use sea_orm::prelude::*;
+use sea_orm::{Set, TransactionTrait};
+
+use crate::common::{log, time};
+use crate::dao;
+use crate::middlewares::audit::Actor;
+use crate::models::{invitations, permissions, roles, users, Id, Timestamp};
+use crate::requests::invitation::{
+    InvitationAcceptRequest, InvitationStatus, InvitationStoreRequest,
+};
+use crate::requests::user::UserStoreRequest;
+use crate::tenant::{self, Tenanted};
+
+pub async fn find<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Option<invitations::Model> {
+    let id: Id = id.into();
+
+    match invitations::Entity::find_by_id(id).tenanted().one(db).await {
+        Ok(invitation) => invitation,
+        Err(e) => {
+            log::error!(find, "{}", e);
+
+            None
+        }
+    }
+}
+
+/// The invitation of `email` that can still be accepted, if any.
+pub async fn find_pending(
+    db: &DatabaseConnection,
+    email: &str,
+) -> Result<Option<invitations::Model>, DbErr> {
+    invitations::Entity::find()
+        .tenanted()
+        .filter(invitations::Column::Email.eq(email.trim().to_lowercase()))
+        .filter(invitations::Column::Status.eq(InvitationStatus::Pending.as_str()))
+        .filter(invitations::Column::ExpiredAt.gt(time::now()))
+        .one(db)
+        .await
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    request: InvitationStoreRequest,
+    invited_by: &users::Model,
+    expired_at: Timestamp,
+) -> Result<invitations::Model, DbErr> {
+    let invitation = invitations::Model {
+        id: Uuid::new_v4(),
+        tenant_id: tenant::required()?,
+        email: request.email.trim().to_lowercase(),
+        name: request.name.trim().to_string(),
+        roles: serde_json::json!(request.roles),
+        permissions: serde_json::json!(request.permissions),
+        status: InvitationStatus::Pending.as_str().to_string(),
+        invited_by: Some(invited_by.id),
+        user_id: None,
+        sent_count: 1,
+        sent_at: time::now(),
+        expired_at,
+        accepted_at: None,
+        revoked_at: None,
+        created_at: time::now(),
+        updated_at: time::now(),
+    };
+
+    invitations::ActiveModel::from(invitation).insert(db).await
+}
+
+/// Push the expiry of `invitation` to `expired_at`, which also voids the
+/// links sent before as they are signed with the previous expiry.
+pub async fn resend(
+    db: &DatabaseConnection,
+    invitation: invitations::Model,
+    expired_at: Timestamp,
+) -> Result<invitations::Model, DbErr> {
+    let sent_count = invitation.sent_count + 1;
+    let mut model = invitations::ActiveModel::from(invitation);
+    model.status = Set(InvitationStatus::Pending.as_str().to_string());
+    model.sent_count = Set(sent_count);
+    model.sent_at = Set(time::now());
+    model.expired_at = Set(expired_at);
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
+
+pub async fn revoke(
+    db: &DatabaseConnection,
+    invitation: invitations::Model,
+) -> Result<invitations::Model, DbErr> {
+    let mut model = invitations::ActiveModel::from(invitation);
+    model.status = Set(InvitationStatus::Revoked.as_str().to_string());
+    model.revoked_at = Set(Some(time::now()));
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
+
+/// Create the invited user and mark `invitation` accepted in one go.
+///
+/// Yields `None` when the invitation stopped being pending in the meantime,
+/// so a link can only ever be used once.
+pub async fn accept(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    invitation: invitations::Model,
+    request: InvitationAcceptRequest,
+) -> Result<Option<(users::Model, Vec<permissions::Model>, Vec<roles::Model>)>, DbErr> {
+    let tx = db.begin().await?;
+    let now = time::now();
+
+    let claimed = invitations::Entity::update_many()
+        .col_expr(
+            invitations::Column::Status,
+            Expr::value(InvitationStatus::Accepted.as_str()),
+        )
+        .col_expr(invitations::Column::AcceptedAt, Expr::value(now))
+        .col_expr(invitations::Column::UpdatedAt, Expr::value(now))
+        .filter(invitations::Column::Id.eq(invitation.id))
+        .filter(invitations::Column::Status.eq(InvitationStatus::Pending.as_str()))
+        .filter(invitations::Column::ExpiredAt.gt(now))
+        .exec(&tx)
+        .await?;
+
+    if claimed.rows_affected == 0 {
+        tx.rollback().await?;
+
+        return Ok(None);
+    }
+
+    let (user, permissions, roles) = dao::user::store(
+        &tx,
+        actor,
+        UserStoreRequest {
+            name: invitation.name.clone(),
+            email: invitation.email.clone(),
+            username: request.username,
+            password: request.password,
+            // grants removed since the invitation are skipped by the store
+            permissions: serde_json::from_value(invitation.permissions.clone()).unwrap_or_default(),
+            roles: serde_json::from_value(invitation.roles.clone()).unwrap_or_default(),
+        },
+    )
+    .await?;
+
+    // following the link proves the address belongs to the invitee
+    let mut model = users::ActiveModel::from(user);
+    model.email_verified_at = Set(Some(now));
+    let user = model.update(&tx).await?;
+
+    invitations::Entity::update_many()
+        .col_expr(invitations::Column::UserId, Expr::value(user.id))
+        .filter(invitations::Column::Id.eq(invitation.id))
+        .exec(&tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(Some((user, permissions, roles)))
+}
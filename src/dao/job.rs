@@ -0,0 +1,174 @@
+use chrono::Duration;
+use sea_orm::prelude::*;
+use sea_orm::{QueryOrder, QuerySelect, Set, TransactionTrait};
+use sea_query::Condition;
+
+use crate::common::{log, time};
+use crate::models::{jobs, Id, Timestamp};
+use crate::queue::Status;
+
+pub async fn find<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Option<jobs::Model> {
+    let id: Id = id.into();
+
+    match jobs::Entity::find_by_id(id).one(db).await {
+        Ok(job) => job,
+        Err(e) => {
+            log::error!(find, "{}", e);
+
+            None
+        }
+    }
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    queue: &str,
+    name: &str,
+    payload: Json,
+    max_attempts: i32,
+    available_at: Timestamp,
+) -> Result<jobs::Model, DbErr> {
+    let job = jobs::Model {
+        id: Uuid::new_v4(),
+        queue: queue.to_string(),
+        name: name.to_string(),
+        payload,
+        status: Status::Pending.to_string(),
+        attempts: 0,
+        max_attempts,
+        last_error: None,
+        available_at,
+        locked_until: None,
+        completed_at: None,
+        created_at: time::now(),
+        updated_at: time::now(),
+    };
+
+    jobs::ActiveModel::from(job).insert(db).await
+}
+
+/// Claim the next runnable job of `queue` for `visibility`.
+///
+/// A job is runnable when it is pending and due, or when it is running but
+/// its lock has expired because the worker holding it died.
+pub async fn reserve(
+    db: &DatabaseConnection,
+    queue: &str,
+    visibility: Duration,
+) -> Result<Option<jobs::Model>, DbErr> {
+    let now = time::now();
+    let mut query = jobs::Entity::find()
+        .filter(jobs::Column::Queue.eq(queue))
+        .filter(
+            Condition::any()
+                .add(
+                    Condition::all()
+                        .add(jobs::Column::Status.eq(Status::Pending.as_str()))
+                        .add(jobs::Column::AvailableAt.lte(now)),
+                )
+                .add(
+                    Condition::all()
+                        .add(jobs::Column::Status.eq(Status::Running.as_str()))
+                        .add(jobs::Column::LockedUntil.lt(now)),
+                ),
+        )
+        .order_by_asc(jobs::Column::AvailableAt)
+        .limit(1);
+
+    #[cfg(feature = "postgres")]
+    {
+        use sea_query::{LockBehavior, LockType};
+
+        QuerySelect::query(&mut query)
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked);
+    }
+
+    let tx = db.begin().await?;
+    let job = match query.one(&tx).await? {
+        None => {
+            tx.commit().await?;
+
+            return Ok(None);
+        }
+        Some(job) => job,
+    };
+
+    // sqlite has no row locks, the claim is a compare and swap on the
+    // previous state instead, losing the race simply yields no job.
+    let claimed = jobs::Entity::update_many()
+        .col_expr(jobs::Column::Status, Expr::value(Status::Running.as_str()))
+        .col_expr(jobs::Column::Attempts, Expr::value(job.attempts + 1))
+        .col_expr(jobs::Column::LockedUntil, Expr::value(now + visibility))
+        .col_expr(jobs::Column::UpdatedAt, Expr::value(now))
+        .filter(jobs::Column::Id.eq(job.id))
+        .filter(jobs::Column::Status.eq(job.status.clone()))
+        .filter(jobs::Column::Attempts.eq(job.attempts))
+        .exec(&tx)
+        .await?;
+
+    if claimed.rows_affected == 0 {
+        tx.rollback().await?;
+
+        return Ok(None);
+    }
+
+    tx.commit().await?;
+
+    Ok(Some(jobs::Model {
+        status: Status::Running.to_string(),
+        attempts: job.attempts + 1,
+        locked_until: Some(now + visibility),
+        updated_at: now,
+        ..job
+    }))
+}
+
+pub async fn complete(db: &DatabaseConnection, job: jobs::Model) -> Result<jobs::Model, DbErr> {
+    let mut model = jobs::ActiveModel::from(job);
+    model.status = Set(Status::Completed.to_string());
+    model.locked_until = Set(None);
+    model.completed_at = Set(Some(time::now()));
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
+
+/// Record a failed attempt, the job is retried at `retry_at` or
+/// dead-lettered when there is nothing left to retry.
+pub async fn fail<E: ToString>(
+    db: &DatabaseConnection,
+    job: jobs::Model,
+    error: E,
+    retry_at: Option<Timestamp>,
+) -> Result<jobs::Model, DbErr> {
+    let mut model = jobs::ActiveModel::from(job);
+    model.last_error = Set(Some(error.to_string()));
+    model.locked_until = Set(None);
+    model.updated_at = Set(time::now());
+
+    match retry_at {
+        None => model.status = Set(Status::Dead.to_string()),
+        Some(at) => {
+            model.status = Set(Status::Pending.to_string());
+            model.available_at = Set(at);
+        }
+    }
+
+    model.update(db).await
+}
+
+pub async fn retry(db: &DatabaseConnection, job: jobs::Model) -> Result<jobs::Model, DbErr> {
+    let mut model = jobs::ActiveModel::from(job);
+    model.status = Set(Status::Pending.to_string());
+    model.attempts = Set(0);
+    model.available_at = Set(time::now());
+    model.locked_until = Set(None);
+    model.completed_at = Set(None);
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
+
+pub async fn delete(db: &DatabaseConnection, job: jobs::Model) -> Result<jobs::Model, DbErr> {
+    jobs::ActiveModel::from(job.clone()).delete(db).await?;
+
+    Ok(job)
+}
@@ -1,4 +1,58 @@
+pub mod audit;
 pub mod auth;
+pub mod file;
+pub mod grant;
+pub mod impersonation;
+pub mod invitation;
+pub mod job;
 pub mod permission;
+pub mod preference;
 pub mod role;
+pub mod role_scope;
+pub mod schedule;
+pub mod tenant;
 pub mod user;
+
+#[cfg(test)]
+pub mod test {
+    use sea_orm::{ConnectionTrait, Database, DatabaseConnection, Schema};
+
+    use crate::models::{
+        audit_logs, files, impersonations, permission_user, permissions, preferences, role_parent,
+        role_scopes, role_user, roles, tokens, users,
+    };
+
+    /// An empty in-memory database with the tables of users, their grants,
+    /// scoped or not, the roles above roles, their preferences, sessions,
+    /// impersonations and files and the audit log, for what cannot be told
+    /// without running the sql.
+    pub async fn database() -> DatabaseConnection {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        let backend = db.get_database_backend();
+        let schema = Schema::new(backend);
+
+        // rows are made up table by table, without what they refer to
+        db.execute_unprepared("PRAGMA foreign_keys = OFF")
+            .await
+            .unwrap();
+
+        for table in [
+            schema.create_table_from_entity(users::Entity),
+            schema.create_table_from_entity(permissions::Entity),
+            schema.create_table_from_entity(permission_user::Entity),
+            schema.create_table_from_entity(roles::Entity),
+            schema.create_table_from_entity(role_user::Entity),
+            schema.create_table_from_entity(role_parent::Entity),
+            schema.create_table_from_entity(role_scopes::Entity),
+            schema.create_table_from_entity(preferences::Entity),
+            schema.create_table_from_entity(tokens::Entity),
+            schema.create_table_from_entity(impersonations::Entity),
+            schema.create_table_from_entity(audit_logs::Entity),
+            schema.create_table_from_entity(files::Entity),
+        ] {
+            db.execute(backend.build(&table)).await.unwrap();
+        }
+
+        db
+    }
+}
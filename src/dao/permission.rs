@@ -1,9 +1,15 @@
+use std::collections::HashMap;
+
 use sea_orm::prelude::*;
-use sea_orm::Set;
+use sea_orm::{QuerySelect, Set, TransactionTrait};
 
-use crate::common::log;
-use crate::models::{permissions, Id};
+use crate::catalogue;
+use crate::common::{log, time};
+use crate::dao;
+use crate::middlewares::audit::Actor;
+use crate::models::{permission_role, permission_user, permissions, role_user, roles, users, Id};
 use crate::requests::permission::{PermissionStoreRequest, PermissionUpdateRequest};
+use crate::tenant;
 
 pub async fn find<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Option<permissions::Model> {
     let id: Id = id.into();
@@ -18,21 +24,223 @@ pub async fn find<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Option<permiss
     }
 }
 
+/// How many users that are not deleted, of the current tenant within a
+/// request, each of `ids` is granted to directly, leaving out permissions
+/// granted to nobody.
+pub async fn members(db: &DatabaseConnection, ids: Vec<Id>) -> Result<HashMap<Id, u64>, DbErr> {
+    let mut query = permission_user::Entity::find()
+        .select_only()
+        .column(permission_user::Column::PermissionId)
+        .column_as(permission_user::Column::UserId.count(), "users")
+        .inner_join(users::Entity)
+        .filter(users::Column::DeletedAt.is_null())
+        .filter(permission_user::Column::PermissionId.is_in(ids))
+        .group_by(permission_user::Column::PermissionId);
+
+    if let Some(tenant) = tenant::current() {
+        query = query.filter(users::Column::TenantId.eq(tenant));
+    }
+
+    Ok(query
+        .into_tuple::<(Id, i64)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(id, count)| (id, count as u64))
+        .collect())
+}
+
+pub async fn find_by_code(
+    db: &DatabaseConnection,
+    code: &str,
+) -> Result<Option<permissions::Model>, DbErr> {
+    permissions::Entity::find()
+        .filter(permissions::Column::Code.eq(code))
+        .one(db)
+        .await
+}
+
+/// The permissions `user_id` was granted directly along with those of
+/// `roles`, which are expected to be resolved through the hierarchy.
+pub async fn effective<I: Into<Id>>(
+    db: &DatabaseConnection,
+    user_id: I,
+    roles: &[roles::Model],
+) -> Result<Vec<permissions::Model>, DbErr> {
+    let user_id: Id = user_id.into();
+    let mut permissions = permissions::Entity::find()
+        .inner_join(permission_user::Entity)
+        .filter(permission_user::Column::UserId.eq(user_id))
+        .filter(dao::grant::in_effect::<permission_user::Entity>(time::now()))
+        .all(db)
+        .await?;
+
+    let through_roles = permissions::Entity::find()
+        .inner_join(permission_role::Entity)
+        .filter(
+            permission_role::Column::RoleId
+                .is_in(roles.iter().map(|role| role.id).collect::<Vec<Id>>()),
+        )
+        .all(db)
+        .await?;
+
+    for permission in through_roles {
+        if !permissions.iter().any(|p| p.id == permission.id) {
+            permissions.push(permission);
+        }
+    }
+
+    Ok(permissions)
+}
+
+/// Where a user holds a permission from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    /// Granted to the user themselves.
+    Direct,
+    /// Granted to a role assigned to the user.
+    Role(roles::Model),
+    /// Granted to `role`, which sits below `through`, assigned to the user.
+    Inherited {
+        role: roles::Model,
+        through: roles::Model,
+    },
+}
+
+/// A permission a user holds along with everywhere they hold it from.
+#[derive(Clone, Debug)]
+pub struct Effective {
+    pub permission: permissions::Model,
+    pub sources: Vec<Source>,
+}
+
+/// The permissions granted `direct`ly or to any role of `granted` reached
+/// from the `assigned` ones through `edges`, sorted by code. `roles` are
+/// the assigned ones along with every role below them.
+pub fn sources(
+    direct: Vec<permissions::Model>,
+    assigned: &[roles::Model],
+    roles: &[roles::Model],
+    edges: &[(Id, Id)],
+    granted: Vec<(Id, permissions::Model)>,
+) -> Vec<Effective> {
+    let mut effective = direct
+        .into_iter()
+        .map(|permission| Effective {
+            permission,
+            sources: vec![Source::Direct],
+        })
+        .collect::<Vec<_>>();
+
+    for through in assigned {
+        let below = dao::role::descendants(edges, &[through.id]);
+
+        for (role_id, permission) in granted.iter().filter(|(id, _)| below.contains(id)) {
+            let source = match role_id == &through.id {
+                true => Source::Role(through.clone()),
+                false => match roles.iter().find(|role| &role.id == role_id) {
+                    Some(role) => Source::Inherited {
+                        role: role.clone(),
+                        through: through.clone(),
+                    },
+                    None => continue,
+                },
+            };
+
+            match effective
+                .iter_mut()
+                .find(|effective| effective.permission.id == permission.id)
+            {
+                Some(effective) if !effective.sources.contains(&source) => {
+                    effective.sources.push(source)
+                }
+                Some(_) => {}
+                None => effective.push(Effective {
+                    permission: permission.clone(),
+                    sources: vec![source],
+                }),
+            }
+        }
+    }
+
+    effective.sort_by(|a, b| a.permission.code.cmp(&b.permission.code));
+    effective
+}
+
+/// Every permission `user_id` holds along with where from, see [`sources`].
+pub async fn explain<I: Into<Id>>(
+    db: &DatabaseConnection,
+    user_id: I,
+) -> Result<(Vec<roles::Model>, Vec<Effective>), DbErr> {
+    let user_id: Id = user_id.into();
+    let direct = permissions::Entity::find()
+        .inner_join(permission_user::Entity)
+        .filter(permission_user::Column::UserId.eq(user_id))
+        .filter(dao::grant::in_effect::<permission_user::Entity>(time::now()))
+        .all(db)
+        .await?;
+    let assigned = roles::Entity::find()
+        .inner_join(role_user::Entity)
+        .filter(role_user::Column::UserId.eq(user_id))
+        .filter(dao::grant::in_effect::<role_user::Entity>(time::now()))
+        .all(db)
+        .await?;
+    let roles = dao::role::effective(db, assigned.iter().map(|role| role.id).collect()).await?;
+    let granted = permission_role::Entity::find()
+        .find_also_related(permissions::Entity)
+        .filter(
+            permission_role::Column::RoleId
+                .is_in(roles.iter().map(|role| role.id).collect::<Vec<Id>>()),
+        )
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(permission_role, permission)| {
+            permission.map(|permission| (permission_role.role_id, permission))
+        })
+        .collect();
+    let effective = sources(
+        direct,
+        &assigned,
+        &roles,
+        &dao::role::edges(db).await?,
+        granted,
+    );
+
+    Ok((roles, effective))
+}
+
 pub async fn store(
     db: &DatabaseConnection,
+    actor: &Actor,
     request: PermissionStoreRequest,
 ) -> Result<permissions::Model, DbErr> {
+    let tx = db.begin().await?;
+    let code = request.code.to_uppercase().replace(" ", "_");
+    let known = catalogue::find(&code);
     let permission = permissions::Model {
-        id: Uuid::new_v4().into(),
-        code: request.code.to_uppercase().replace(" ", "_"),
+        id: Uuid::new_v4(),
         name: request.name.to_lowercase(),
+        group: known.map(|permission| permission.group.to_string()),
+        description: known.map(|permission| permission.description.to_string()),
+        // nothing checks a code the catalogue does not define
+        orphaned_at: known.is_none().then(time::now),
+        code,
     };
 
-    permissions::ActiveModel::from(permission).insert(db).await
+    let permission = permissions::ActiveModel::from(permission)
+        .insert(&tx)
+        .await?;
+
+    dao::audit::change(&tx, actor, None, Some(&permission)).await?;
+    tx.commit().await?;
+
+    Ok(permission)
 }
 
 pub async fn update(
     db: &DatabaseConnection,
+    actor: &Actor,
     permission: permissions::Model,
     request: PermissionUpdateRequest,
 ) -> Result<permissions::Model, DbErr> {
@@ -46,16 +254,159 @@ pub async fn update(
         model.name = Set(request.name);
     }
 
-    model.update(db).await
+    let tx = db.begin().await?;
+    let updated = model.update(&tx).await?;
+
+    dao::audit::change(&tx, actor, Some(&permission), Some(&updated)).await?;
+    tx.commit().await?;
+
+    Ok(updated)
 }
 
 pub async fn delete(
     db: &DatabaseConnection,
+    actor: &Actor,
     permission: permissions::Model,
 ) -> Result<permissions::Model, DbErr> {
+    let tx = db.begin().await?;
+
     permissions::ActiveModel::from(permission.clone())
-        .delete(db)
+        .delete(&tx)
         .await?;
+    dao::audit::change(&tx, actor, Some(&permission), None).await?;
+    tx.commit().await?;
 
     Ok(permission)
 }
+
+/// Bring the `permissions` table in line with the catalogue, creating what
+/// it lacks, updating what differs and marking as orphaned whatever the
+/// catalogue does not define. Running it again changes nothing.
+pub async fn sync(db: &DatabaseConnection) -> Result<Synced, DbErr> {
+    let rows = permissions::Entity::find().all(db).await?;
+    let plan = catalogue::plan(&rows);
+    let synced = Synced {
+        created: plan.create.len(),
+        updated: plan.update.len(),
+        orphaned: plan.orphan.len(),
+    };
+
+    if plan.is_empty() {
+        return Ok(synced);
+    }
+
+    let actor = Actor::system();
+    let tx = db.begin().await?;
+
+    for permission in plan.create {
+        let created = permissions::ActiveModel::from(permissions::Model {
+            id: Uuid::new_v4(),
+            code: permission.code.to_string(),
+            name: permission.name.to_string(),
+            group: Some(permission.group.to_string()),
+            description: Some(permission.description.to_string()),
+            orphaned_at: None,
+        })
+        .insert(&tx)
+        .await?;
+
+        dao::audit::change(&tx, &actor, None, Some(&created)).await?;
+    }
+
+    for (row, permission) in plan.update {
+        let mut model = permissions::ActiveModel::from(row.clone());
+
+        model.name = Set(permission.name.to_string());
+        model.group = Set(Some(permission.group.to_string()));
+        model.description = Set(Some(permission.description.to_string()));
+        model.orphaned_at = Set(None);
+
+        let updated = model.update(&tx).await?;
+
+        dao::audit::change(&tx, &actor, Some(row), Some(&updated)).await?;
+    }
+
+    for row in plan.orphan {
+        let mut model = permissions::ActiveModel::from(row.clone());
+
+        model.orphaned_at = Set(Some(time::now()));
+
+        let updated = model.update(&tx).await?;
+
+        dao::audit::change(&tx, &actor, Some(row), Some(&updated)).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(synced)
+}
+
+/// How many permissions [`sync`] created, updated and marked as orphaned.
+#[derive(Debug)]
+pub struct Synced {
+    pub created: usize,
+    pub updated: usize,
+    pub orphaned: usize,
+}
+
+#[cfg(test)]
+pub mod test {
+    use uuid::Uuid;
+
+    use crate::models::{permissions, roles, Id};
+
+    use super::{sources, Source};
+
+    fn role(code: &str) -> roles::Model {
+        roles::Model {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            code: code.to_string(),
+            name: code.to_lowercase(),
+            system: false,
+        }
+    }
+
+    fn permission(code: &str) -> permissions::Model {
+        permissions::Model {
+            id: Uuid::new_v4(),
+            code: code.to_string(),
+            name: code.to_lowercase(),
+            group: None,
+            description: None,
+            orphaned_at: None,
+        }
+    }
+
+    #[test]
+    pub async fn sources_must_tell_where_each_permission_comes_from() {
+        // admin > teacher, both assigned
+        let (admin, teacher) = (role("ADMIN"), role("TEACHER"));
+        let (read, grade) = (permission("READ_USER"), permission("GRADE"));
+        let edges: Vec<(Id, Id)> = vec![(teacher.id, admin.id)];
+        let effective = sources(
+            vec![read.clone()],
+            &[admin.clone(), teacher.clone()],
+            &[admin.clone(), teacher.clone()],
+            &edges,
+            vec![(teacher.id, grade.clone()), (admin.id, read)],
+        );
+
+        assert_eq!(effective.len(), 2);
+        assert_eq!(effective[0].permission.code, "GRADE");
+        assert_eq!(
+            effective[0].sources,
+            vec![
+                Source::Inherited {
+                    role: teacher.clone(),
+                    through: admin.clone(),
+                },
+                Source::Role(teacher),
+            ]
+        );
+        assert_eq!(
+            effective[1].sources,
+            vec![Source::Direct, Source::Role(admin)]
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use sea_orm::prelude::*;
+use sea_orm::{Set, TransactionTrait};
+
+use crate::common::time;
+use crate::dao;
+use crate::i18n::Locale;
+use crate::middlewares::audit::Actor;
+use crate::models::{preferences, users, Id};
+use crate::requests::preference::{DateFormat, Notifications, PreferenceUpdateRequest};
+
+/// What a user who never saved their preferences gets.
+pub fn default(user_id: Id) -> preferences::Model {
+    preferences::Model {
+        user_id,
+        timezone: "UTC".to_string(),
+        date_format: DateFormat::default().as_str().to_string(),
+        notifications: serde_json::to_value(Notifications::default()).unwrap_or_default(),
+        created_at: time::now(),
+        updated_at: time::now(),
+    }
+}
+
+pub async fn find<C: ConnectionTrait, I: Into<Id>>(
+    db: &C,
+    user_id: I,
+) -> Result<preferences::Model, DbErr> {
+    let user_id: Id = user_id.into();
+    let preferences = preferences::Entity::find_by_id(user_id).one(db).await?;
+
+    Ok(preferences.unwrap_or_else(|| default(user_id)))
+}
+
+/// Save the preferences of `user`, the locale staying on the user itself.
+pub async fn save(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    user: users::Model,
+    request: PreferenceUpdateRequest,
+) -> Result<(users::Model, preferences::Model), DbErr> {
+    let tx = db.begin().await?;
+    let stored = preferences::Entity::find_by_id(user.id).one(&tx).await?;
+    let notifications = serde_json::to_value(&request.notifications).unwrap_or_default();
+
+    let preferences = match stored {
+        Some(preferences) => {
+            let mut model = preferences::ActiveModel::from(preferences);
+            model.timezone = Set(request.timezone);
+            model.date_format = Set(request.date_format.as_str().to_string());
+            model.notifications = Set(notifications);
+            model.updated_at = Set(time::now());
+            model.update(&tx).await?
+        }
+        None => {
+            preferences::ActiveModel::from(preferences::Model {
+                timezone: request.timezone,
+                date_format: request.date_format.as_str().to_string(),
+                notifications,
+                ..default(user.id)
+            })
+            .insert(&tx)
+            .await?
+        }
+    };
+
+    let locale = request
+        .locale
+        .and_then(|locale| locale.parse::<Locale>().ok())
+        .map(|locale| locale.to_string());
+
+    let updated = if user.locale != locale {
+        let mut model = users::ActiveModel::from(user.clone());
+        model.locale = Set(locale);
+        model.updated_at = Set(time::now());
+        let updated = model.update(&tx).await?;
+
+        dao::audit::change(&tx, actor, Some(&user), Some(&updated)).await?;
+
+        updated
+    } else {
+        user
+    };
+
+    tx.commit().await?;
+
+    Ok((updated, preferences))
+}
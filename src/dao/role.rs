@@ -1,14 +1,20 @@
+use std::collections::{HashMap, HashSet};
+
 use sea_orm::prelude::*;
-use sea_orm::Set;
+use sea_orm::{QuerySelect, Set, TransactionTrait};
 
 use crate::common::log;
-use crate::models::{roles, Id};
+use crate::dao;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::superusers;
+use crate::models::{role_parent, role_user, roles, users, Id};
 use crate::requests::role::{RoleStoreRequest, RoleUpdateRequest};
+use crate::tenant::{self, Tenanted};
 
 pub async fn find<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Option<roles::Model> {
     let id: Id = id.into();
 
-    match roles::Entity::find_by_id(id).one(db).await {
+    match roles::Entity::find_by_id(id).tenanted().one(db).await {
         Ok(role) => role,
         Err(e) => {
             log::error!(find, "{}", e);
@@ -18,21 +24,196 @@ pub async fn find<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Option<roles::
     }
 }
 
+/// Every `(role_id, parent_id)` edge of the role hierarchy.
+pub async fn edges<C: ConnectionTrait>(db: &C) -> Result<Vec<(Id, Id)>, DbErr> {
+    Ok(role_parent::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|edge| (edge.role_id, edge.parent_id))
+        .collect())
+}
+
+/// `roles` along with every role below them, as a parent holds whatever
+/// its children are granted.
+pub fn descendants(edges: &[(Id, Id)], roles: &[Id]) -> HashSet<Id> {
+    let mut found: HashSet<Id> = roles.iter().cloned().collect();
+    let mut pending: Vec<Id> = roles.to_vec();
+
+    while let Some(parent) = pending.pop() {
+        for (role, _) in edges.iter().filter(|(_, id)| id == &parent) {
+            if found.insert(*role) {
+                pending.push(*role);
+            }
+        }
+    }
+
+    found
+}
+
+/// `roles` along with every role above them, which hold whatever they are
+/// granted.
+pub fn ancestors(edges: &[(Id, Id)], roles: &[Id]) -> HashSet<Id> {
+    let mut found: HashSet<Id> = roles.iter().cloned().collect();
+    let mut pending: Vec<Id> = roles.to_vec();
+
+    while let Some(child) = pending.pop() {
+        for (_, parent) in edges.iter().filter(|(id, _)| id == &child) {
+            if found.insert(*parent) {
+                pending.push(*parent);
+            }
+        }
+    }
+
+    found
+}
+
+/// Whether placing `role` right below `parents` would close a loop, which
+/// is when one of them already sits below `role` or is `role` itself.
+pub fn cyclic(edges: &[(Id, Id)], role: &Id, parents: &[Id]) -> bool {
+    let edges = edges
+        .iter()
+        .filter(|(id, _)| id != role)
+        .cloned()
+        .collect::<Vec<_>>();
+    let below = descendants(&edges, &[*role]);
+
+    parents.iter().any(|parent| below.contains(parent))
+}
+
+/// The roles `ids` along with every role below them.
+pub async fn effective(db: &DatabaseConnection, ids: Vec<Id>) -> Result<Vec<roles::Model>, DbErr> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let ids = descendants(&edges(db).await?, &ids);
+
+    roles::Entity::find()
+        .filter(roles::Column::Id.is_in(ids))
+        .all(db)
+        .await
+}
+
+/// Roles of `tenant_id` holding a superuser role, as one of
+/// [`superusers`] or through sitting above one.
+pub async fn superuser<I: Into<Id>>(
+    db: &DatabaseConnection,
+    tenant_id: I,
+) -> Result<HashSet<Id>, DbErr> {
+    let tenant_id: Id = tenant_id.into();
+    let ids = roles::Entity::find()
+        .select_only()
+        .column(roles::Column::Id)
+        .filter(roles::Column::TenantId.eq(tenant_id))
+        .filter(roles::Column::Code.is_in(superusers()))
+        .into_tuple::<Id>()
+        .all(db)
+        .await?;
+
+    Ok(ancestors(&edges(db).await?, &ids))
+}
+
+/// How many users that are not deleted each of `ids` is assigned to
+/// directly, leaving out roles assigned to nobody.
+pub async fn members(db: &DatabaseConnection, ids: Vec<Id>) -> Result<HashMap<Id, u64>, DbErr> {
+    Ok(role_user::Entity::find()
+        .select_only()
+        .column(role_user::Column::RoleId)
+        .column_as(role_user::Column::UserId.count(), "users")
+        .inner_join(users::Entity)
+        .filter(users::Column::DeletedAt.is_null())
+        .filter(role_user::Column::RoleId.is_in(ids))
+        .group_by(role_user::Column::RoleId)
+        .into_tuple::<(Id, i64)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(id, count)| (id, count as u64))
+        .collect())
+}
+
+/// The roles directly above `role`.
+pub async fn parents(
+    db: &DatabaseConnection,
+    role: &roles::Model,
+) -> Result<Vec<roles::Model>, DbErr> {
+    let ids = role_parent::Entity::find()
+        .filter(role_parent::Column::RoleId.eq(role.id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|edge| edge.parent_id)
+        .collect::<Vec<Id>>();
+
+    roles::Entity::find()
+        .filter(roles::Column::Id.is_in(ids))
+        .all(db)
+        .await
+}
+
+/// Replace the roles directly above `role` with `parents`, callers make
+/// sure it does not close a loop.
+pub async fn set_parents(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    role: roles::Model,
+    parents: Vec<roles::Model>,
+) -> Result<Vec<roles::Model>, DbErr> {
+    let before = (role.clone(), dao::role::parents(db, &role).await?);
+    let tx = db.begin().await?;
+
+    role_parent::Entity::delete_many()
+        .filter(role_parent::Column::RoleId.eq(role.id))
+        .exec(&tx)
+        .await?;
+
+    if !parents.is_empty() {
+        role_parent::Entity::insert_many(parents.iter().map(|parent| {
+            role_parent::ActiveModel::from(role_parent::Model {
+                id: Uuid::new_v4(),
+                role_id: role.id,
+                parent_id: parent.id,
+            })
+        }))
+        .exec(&tx)
+        .await?;
+    }
+
+    let after = (role, parents);
+
+    dao::audit::change(&tx, actor, Some(&before), Some(&after)).await?;
+    tx.commit().await?;
+
+    Ok(after.1)
+}
+
 pub async fn store(
     db: &DatabaseConnection,
+    actor: &Actor,
     request: RoleStoreRequest,
 ) -> Result<roles::Model, DbErr> {
+    let tenant_id = tenant::required()?;
+    let tx = db.begin().await?;
     let role = roles::Model {
-        id: Uuid::new_v4().into(),
+        id: Uuid::new_v4(),
+        tenant_id,
         code: request.code.to_uppercase().replace(" ", "_"),
         name: request.name.to_lowercase(),
+        system: false,
     };
 
-    roles::ActiveModel::from(role).insert(db).await
+    let role = roles::ActiveModel::from(role).insert(&tx).await?;
+
+    dao::audit::change(&tx, actor, None, Some(&role)).await?;
+    tx.commit().await?;
+
+    Ok(role)
 }
 
 pub async fn update(
     db: &DatabaseConnection,
+    actor: &Actor,
     role: roles::Model,
     request: RoleUpdateRequest,
 ) -> Result<roles::Model, DbErr> {
@@ -46,11 +227,82 @@ pub async fn update(
         model.name = Set(request.name);
     }
 
-    model.update(db).await
+    let tx = db.begin().await?;
+    let updated = model.update(&tx).await?;
+
+    dao::audit::change(&tx, actor, Some(&role), Some(&updated)).await?;
+    tx.commit().await?;
+
+    Ok(updated)
 }
 
-pub async fn delete(db: &DatabaseConnection, role: roles::Model) -> Result<roles::Model, DbErr> {
-    roles::ActiveModel::from(role.clone()).delete(db).await?;
+pub async fn delete(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    role: roles::Model,
+) -> Result<roles::Model, DbErr> {
+    let tx = db.begin().await?;
+
+    roles::ActiveModel::from(role.clone()).delete(&tx).await?;
+    dao::audit::change(&tx, actor, Some(&role), None).await?;
+    tx.commit().await?;
 
     Ok(role)
 }
+
+#[cfg(test)]
+pub mod test {
+    use uuid::Uuid;
+
+    use crate::models::Id;
+
+    use super::{ancestors, cyclic, descendants};
+
+    /// superuser > admin > (teacher, staff), teacher > assistant
+    fn hierarchy() -> (Vec<Id>, Vec<(Id, Id)>) {
+        let ids: Vec<Id> = (0..5).map(|_| Uuid::new_v4()).collect();
+        let edges = vec![
+            (ids[1], ids[0]),
+            (ids[2], ids[1]),
+            (ids[3], ids[1]),
+            (ids[4], ids[2]),
+        ];
+
+        (ids, edges)
+    }
+
+    #[test]
+    pub async fn descendants_must_walk_down_the_whole_hierarchy() {
+        let (ids, edges) = hierarchy();
+
+        assert_eq!(descendants(&edges, &[ids[0]]).len(), 5);
+        assert_eq!(
+            descendants(&edges, &[ids[2]]),
+            [ids[2], ids[4]].into_iter().collect()
+        );
+        assert_eq!(descendants(&edges, &[ids[4]]).len(), 1);
+    }
+
+    #[test]
+    pub async fn ancestors_must_walk_up_every_branch() {
+        let (ids, edges) = hierarchy();
+
+        assert_eq!(
+            ancestors(&edges, &[ids[4]]),
+            [ids[0], ids[1], ids[2], ids[4]].into_iter().collect()
+        );
+        assert_eq!(ancestors(&edges, &[ids[0]]).len(), 1);
+    }
+
+    #[test]
+    pub async fn cyclic_must_reject_parents_below_the_role() {
+        let (ids, edges) = hierarchy();
+
+        assert!(cyclic(&edges, &ids[1], &[ids[1]]));
+        assert!(cyclic(&edges, &ids[1], &[ids[4]]));
+        assert!(cyclic(&edges, &ids[0], &[ids[3]]));
+        assert!(!cyclic(&edges, &ids[4], &[ids[3]]));
+        // a role may sit below several others
+        assert!(!cyclic(&edges, &ids[3], &[ids[0]]));
+    }
+}
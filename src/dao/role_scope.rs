@@ -0,0 +1,175 @@
+use sea_orm::prelude::*;
+use sea_orm::{QueryOrder, TransactionTrait};
+
+use crate::common::time;
+use crate::dao;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::policy::{Grant, Scope};
+use crate::models::{permission_role, permissions, role_scopes, roles, users, Id};
+use crate::requests::role_scope::RoleScopeStoreRequest;
+use crate::tenant::{self, Tenanted};
+
+/// The roles assigned to `user_id` within a scope, oldest first.
+pub async fn all<I: Into<Id>>(
+    db: &DatabaseConnection,
+    user_id: I,
+) -> Result<Vec<(role_scopes::Model, roles::Model)>, DbErr> {
+    let user_id: Id = user_id.into();
+
+    Ok(role_scopes::Entity::find()
+        .tenanted()
+        .find_also_related(roles::Entity)
+        .filter(role_scopes::Column::UserId.eq(user_id))
+        .order_by_asc(role_scopes::Column::CreatedAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(scope, role)| role.map(|role| (scope, role)))
+        .collect())
+}
+
+pub async fn find<U: Into<Id>, I: Into<Id>>(
+    db: &DatabaseConnection,
+    user_id: U,
+    id: I,
+) -> Result<Option<(role_scopes::Model, roles::Model)>, DbErr> {
+    let (user_id, id): (Id, Id) = (user_id.into(), id.into());
+
+    Ok(role_scopes::Entity::find_by_id(id)
+        .tenanted()
+        .find_also_related(roles::Entity)
+        .filter(role_scopes::Column::UserId.eq(user_id))
+        .one(db)
+        .await?
+        .and_then(|(scope, role)| role.map(|role| (scope, role))))
+}
+
+/// Whether `user` already holds `role` within the scope of `request`.
+pub async fn assigned(
+    db: &DatabaseConnection,
+    user: &users::Model,
+    request: &RoleScopeStoreRequest,
+) -> Result<bool, DbErr> {
+    let count = role_scopes::Entity::find()
+        .tenanted()
+        .filter(role_scopes::Column::UserId.eq(user.id))
+        .filter(role_scopes::Column::RoleId.eq(request.role_id))
+        .filter(role_scopes::Column::ScopeType.eq(request.scope_type.clone()))
+        .filter(role_scopes::Column::ScopeId.eq(request.scope_id))
+        .count(db)
+        .await?;
+
+    Ok(count > 0)
+}
+
+/// What `user_id` was granted within each of their scopes, resolved
+/// through the role hierarchy.
+pub async fn grants<I: Into<Id>>(db: &DatabaseConnection, user_id: I) -> Result<Vec<Grant>, DbErr> {
+    let user_id: Id = user_id.into();
+    let scopes = role_scopes::Entity::find()
+        .filter(role_scopes::Column::UserId.eq(user_id))
+        .all(db)
+        .await?;
+
+    if scopes.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let edges = dao::role::edges(db).await?;
+    let roles = scopes
+        .iter()
+        .map(|scope| {
+            let roles = dao::role::descendants(&edges, &[scope.role_id]);
+
+            (scope, roles)
+        })
+        .collect::<Vec<_>>();
+
+    let permissions = permission_role::Entity::find()
+        .find_also_related(permissions::Entity)
+        .filter(
+            permission_role::Column::RoleId.is_in(
+                roles
+                    .iter()
+                    .flat_map(|(_, roles)| roles.iter().cloned())
+                    .collect::<Vec<Id>>(),
+            ),
+        )
+        .all(db)
+        .await?;
+
+    Ok(roles
+        .into_iter()
+        .map(|(scope, roles)| {
+            let mut codes = permissions
+                .iter()
+                .filter(|(permission_role, _)| roles.contains(&permission_role.role_id))
+                .filter_map(|(_, permission)| {
+                    permission
+                        .as_ref()
+                        .map(|permission| permission.code.clone())
+                })
+                .collect::<Vec<_>>();
+
+            codes.sort();
+            codes.dedup();
+
+            Grant {
+                scope: Scope::new(&scope.scope_type, scope.scope_id),
+                permissions: codes,
+            }
+        })
+        .collect())
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    user: users::Model,
+    role: roles::Model,
+    request: RoleScopeStoreRequest,
+) -> Result<role_scopes::Model, DbErr> {
+    let before = (user.clone(), all(db, user.id).await?);
+    let tx = db.begin().await?;
+    let scope = role_scopes::ActiveModel::from(role_scopes::Model {
+        id: Uuid::new_v4(),
+        tenant_id: tenant::required()?,
+        user_id: user.id,
+        role_id: role.id,
+        scope_type: request.scope_type,
+        scope_id: request.scope_id,
+        created_at: time::now(),
+    })
+    .insert(&tx)
+    .await?;
+
+    let mut after = before.clone();
+    after.1.push((scope.clone(), role));
+
+    dao::audit::change(&tx, actor, Some(&before), Some(&after)).await?;
+    tx.commit().await?;
+
+    Ok(scope)
+}
+
+pub async fn delete(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    user: users::Model,
+    scope: role_scopes::Model,
+) -> Result<role_scopes::Model, DbErr> {
+    let before = (user.clone(), all(db, user.id).await?);
+    let tx = db.begin().await?;
+
+    role_scopes::ActiveModel::from(scope.clone())
+        .delete(&tx)
+        .await?;
+
+    let mut after = before.clone();
+    after.1.retain(|(assigned, _)| assigned.id != scope.id);
+
+    dao::audit::change(&tx, actor, Some(&before), Some(&after)).await?;
+    tx.commit().await?;
+
+    Ok(scope)
+}
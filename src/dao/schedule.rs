@@ -0,0 +1,192 @@
+use sea_orm::prelude::*;
+use sea_orm::{QueryOrder, Set};
+
+use crate::common::{log, time};
+use crate::models::{scheduled_tasks, Timestamp};
+
+/// A task claimed for the current tick. On postgres the transaction
+/// holding the advisory lock stays open until the run is recorded.
+pub struct Claim {
+    pub task: scheduled_tasks::Model,
+    #[cfg(feature = "postgres")]
+    tx: sea_orm::DatabaseTransaction,
+}
+
+pub async fn all(db: &DatabaseConnection) -> Result<Vec<scheduled_tasks::Model>, DbErr> {
+    scheduled_tasks::Entity::find()
+        .order_by_asc(scheduled_tasks::Column::Name)
+        .all(db)
+        .await
+}
+
+pub async fn find<T: ToString>(db: &DatabaseConnection, name: T) -> Option<scheduled_tasks::Model> {
+    let task = scheduled_tasks::Entity::find()
+        .filter(scheduled_tasks::Column::Name.eq(name.to_string()))
+        .one(db)
+        .await;
+
+    match task {
+        Ok(task) => task,
+        Err(e) => {
+            log::error!(find, "{}", e);
+
+            None
+        }
+    }
+}
+
+/// Make sure the task has a row, rescheduling it when the expression
+/// declared in code changed.
+pub async fn sync(
+    db: &DatabaseConnection,
+    name: &str,
+    expression: &str,
+    next_run_at: Option<Timestamp>,
+) -> Result<scheduled_tasks::Model, DbErr> {
+    match find(db, name).await {
+        Some(task) if task.expression == expression && task.next_run_at.is_some() => Ok(task),
+        Some(task) => {
+            let mut model = scheduled_tasks::ActiveModel::from(task);
+            model.expression = Set(expression.to_string());
+            model.next_run_at = Set(next_run_at);
+            model.updated_at = Set(time::now());
+            model.update(db).await
+        }
+        None => {
+            let task = scheduled_tasks::Model {
+                id: Uuid::new_v4(),
+                name: name.to_string(),
+                expression: expression.to_string(),
+                next_run_at,
+                last_run_at: None,
+                last_status: None,
+                last_error: None,
+                last_duration: None,
+                created_at: time::now(),
+                updated_at: time::now(),
+            };
+
+            scheduled_tasks::ActiveModel::from(task).insert(db).await
+        }
+    }
+}
+
+/// Claim the task when it is due, moving its next run to `next_run_at`
+/// so no other replica picks up the same slot.
+#[cfg(feature = "postgres")]
+pub async fn claim(
+    db: &DatabaseConnection,
+    name: &str,
+    lock: i64,
+    next_run_at: Option<Timestamp>,
+) -> Result<Option<Claim>, DbErr> {
+    use sea_orm::{DbBackend, Statement, TransactionTrait};
+
+    let tx = db.begin().await?;
+    let locked = tx
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT pg_try_advisory_xact_lock($1) AS locked",
+            [lock.into()],
+        ))
+        .await?
+        .map(|row| row.try_get::<bool>("", "locked"))
+        .transpose()?
+        .unwrap_or(false);
+
+    if !locked {
+        tx.rollback().await?;
+
+        return Ok(None);
+    }
+
+    let task = scheduled_tasks::Entity::find()
+        .filter(scheduled_tasks::Column::Name.eq(name))
+        .filter(scheduled_tasks::Column::NextRunAt.lte(time::now()))
+        .one(&tx)
+        .await?;
+
+    let task = match task {
+        None => {
+            tx.rollback().await?;
+
+            return Ok(None);
+        }
+        Some(task) => task,
+    };
+
+    let mut model = scheduled_tasks::ActiveModel::from(task);
+    model.next_run_at = Set(next_run_at);
+    let task = model.update(&tx).await?;
+
+    Ok(Some(Claim { task, tx }))
+}
+
+/// Claim the task when it is due, sqlite has a single writer so a
+/// compare and swap on the previous slot is enough.
+#[cfg(feature = "sqlite")]
+pub async fn claim(
+    db: &DatabaseConnection,
+    name: &str,
+    _: i64,
+    next_run_at: Option<Timestamp>,
+) -> Result<Option<Claim>, DbErr> {
+    let task = match find(db, name).await {
+        Some(task) if task.next_run_at.is_some_and(|at| at <= time::now()) => task,
+        _ => return Ok(None),
+    };
+
+    let claimed = scheduled_tasks::Entity::update_many()
+        .col_expr(scheduled_tasks::Column::NextRunAt, Expr::value(next_run_at))
+        .filter(scheduled_tasks::Column::Id.eq(task.id.clone()))
+        .filter(scheduled_tasks::Column::NextRunAt.eq(task.next_run_at))
+        .exec(db)
+        .await?;
+
+    if claimed.rows_affected == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(Claim {
+        task: scheduled_tasks::Model {
+            next_run_at,
+            ..task
+        },
+    }))
+}
+
+pub async fn finish<E: ToString>(
+    #[allow(unused_variables)] db: &DatabaseConnection,
+    claim: Claim,
+    ran_at: Timestamp,
+    duration: i64,
+    error: Option<E>,
+) -> Result<scheduled_tasks::Model, DbErr> {
+    let mut model = scheduled_tasks::ActiveModel::from(claim.task);
+    model.last_run_at = Set(Some(ran_at));
+    model.last_duration = Set(Some(duration));
+    model.updated_at = Set(time::now());
+
+    match error {
+        None => {
+            model.last_status = Set(Some("succeeded".to_string()));
+            model.last_error = Set(None);
+        }
+        Some(e) => {
+            model.last_status = Set(Some("failed".to_string()));
+            model.last_error = Set(Some(e.to_string()));
+        }
+    }
+
+    #[cfg(feature = "postgres")]
+    {
+        let task = model.update(&claim.tx).await?;
+
+        claim.tx.commit().await?;
+
+        Ok(task)
+    }
+
+    #[cfg(feature = "sqlite")]
+    model.update(db).await
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use sea_orm::prelude::*;
+use sea_orm::{QueryOrder, Set, TransactionTrait};
+
+use crate::common::{log, time};
+use crate::dao;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::ADMINISTRATORS;
+use crate::models::{permission_role, role_parent, roles, tenants, Id};
+use crate::requests::tenant::{TenantStoreRequest, TenantUpdateRequest};
+use crate::tenant;
+
+pub async fn all(db: &DatabaseConnection) -> Result<Vec<tenants::Model>, DbErr> {
+    tenants::Entity::find()
+        .order_by_asc(tenants::Column::Code)
+        .all(db)
+        .await
+}
+
+pub async fn find<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Option<tenants::Model> {
+    let id: Id = id.into();
+
+    match tenants::Entity::find_by_id(id).one(db).await {
+        Ok(tenant) => tenant,
+        Err(e) => {
+            log::error!(find, "{}", e);
+
+            None
+        }
+    }
+}
+
+pub async fn find_by_code(
+    db: &DatabaseConnection,
+    code: &str,
+) -> Result<Option<tenants::Model>, DbErr> {
+    tenants::Entity::find()
+        .filter(tenants::Column::Code.eq(code))
+        .one(db)
+        .await
+}
+
+/// Create a tenant along with copies of the platform's administrator
+/// roles, granted the same permissions and ranked the same way, so its
+/// first users can be managed from the start.
+pub async fn store(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    request: TenantStoreRequest,
+) -> Result<tenants::Model, DbErr> {
+    let platform = roles::Entity::find()
+        .inner_join(tenants::Entity)
+        .filter(tenants::Column::Code.eq(tenant::platform()))
+        .filter(roles::Column::Code.is_in(ADMINISTRATORS))
+        .all(db)
+        .await?;
+    let ids = platform.iter().map(|role| role.id).collect::<Vec<Id>>();
+    let permissions = permission_role::Entity::find()
+        .filter(permission_role::Column::RoleId.is_in(ids.clone()))
+        .all(db)
+        .await?;
+    let edges = role_parent::Entity::find()
+        .filter(role_parent::Column::RoleId.is_in(ids.clone()))
+        .filter(role_parent::Column::ParentId.is_in(ids))
+        .all(db)
+        .await?;
+
+    let tx = db.begin().await?;
+    let tenant = tenants::ActiveModel::from(tenants::Model {
+        id: Uuid::new_v4(),
+        code: request.code,
+        name: request.name,
+        created_at: time::now(),
+        updated_at: time::now(),
+    })
+    .insert(&tx)
+    .await?;
+
+    let copies = platform
+        .into_iter()
+        .map(|role| {
+            let copy = roles::Model {
+                id: Uuid::new_v4(),
+                tenant_id: tenant.id,
+                code: role.code,
+                name: role.name,
+                system: role.system,
+            };
+
+            (role.id, copy)
+        })
+        .collect::<HashMap<Id, roles::Model>>();
+
+    if !copies.is_empty() {
+        roles::Entity::insert_many(copies.values().cloned().map(roles::ActiveModel::from))
+            .exec(&tx)
+            .await?;
+    }
+
+    if !permissions.is_empty() {
+        permission_role::Entity::insert_many(permissions.iter().map(|permission| {
+            permission_role::ActiveModel::from(permission_role::Model {
+                id: Uuid::new_v4(),
+                permission_id: permission.permission_id,
+                role_id: copies[&permission.role_id].id,
+            })
+        }))
+        .exec(&tx)
+        .await?;
+    }
+
+    if !edges.is_empty() {
+        role_parent::Entity::insert_many(edges.iter().map(|edge| {
+            role_parent::ActiveModel::from(role_parent::Model {
+                id: Uuid::new_v4(),
+                role_id: copies[&edge.role_id].id,
+                parent_id: copies[&edge.parent_id].id,
+            })
+        }))
+        .exec(&tx)
+        .await?;
+    }
+
+    dao::audit::change(&tx, actor, None, Some(&tenant)).await?;
+    tx.commit().await?;
+
+    Ok(tenant)
+}
+
+pub async fn update(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    tenant: tenants::Model,
+    request: TenantUpdateRequest,
+) -> Result<tenants::Model, DbErr> {
+    if tenant.name == request.name {
+        return Ok(tenant);
+    }
+
+    let mut model = tenants::ActiveModel::from(tenant.clone());
+
+    model.name = Set(request.name);
+    model.updated_at = Set(time::now());
+
+    let tx = db.begin().await?;
+    let updated = model.update(&tx).await?;
+
+    dao::audit::change(&tx, actor, Some(&tenant), Some(&updated)).await?;
+    tx.commit().await?;
+
+    Ok(updated)
+}
@@ -1,17 +1,31 @@
+use std::collections::HashSet;
+
 use sea_orm::prelude::*;
+use sea_orm::QuerySelect;
+use sea_orm::Select;
 use sea_orm::Set;
 use sea_orm::TransactionTrait;
 use sea_query::Condition;
+use serde_json::json;
 
 use crate::common::hash;
 use crate::common::log;
 use crate::common::time;
+use crate::dao;
+use crate::dao::grant::{Bounded, Grants, Period};
+use crate::i18n::Locale;
+use crate::middlewares::audit::Actor;
 use crate::models::permission_user;
 use crate::models::permissions;
 use crate::models::role_user;
 use crate::models::roles;
-use crate::models::{users, Id};
-use crate::requests::user::{UserStoreRequest, UserUpdateGeneralInformationRequest};
+use crate::models::{users, Id, Timestamp};
+use crate::requests::audit::{AuditAction, AuditEntity};
+use crate::requests::auth::Register;
+use crate::requests::user::{
+    AccountStatus, UserStoreRequest, UserUpdateGeneralInformationRequest, UserUpdateProfileRequest,
+};
+use crate::tenant::{self, Tenanted};
 
 pub async fn find<I: Into<Id>>(
     db: &DatabaseConnection,
@@ -19,6 +33,7 @@ pub async fn find<I: Into<Id>>(
 ) -> Option<(users::Model, Vec<permissions::Model>, Vec<roles::Model>)> {
     let id: Id = id.into();
     let user = users::Entity::find_by_id(id)
+        .tenanted()
         .filter(users::Column::DeletedAt.is_null())
         .one(db)
         .await;
@@ -38,7 +53,7 @@ pub async fn find<I: Into<Id>>(
     let user = user.unwrap();
     let permissions = permissions::Entity::find()
         .find_with_related(permission_user::Entity)
-        .filter(permission_user::Column::UserId.eq(user.id.clone()))
+        .filter(permission_user::Column::UserId.eq(user.id))
         .all(db)
         .await;
 
@@ -50,7 +65,7 @@ pub async fn find<I: Into<Id>>(
 
     let roles = roles::Entity::find()
         .find_with_related(role_user::Entity)
-        .filter(users::Column::Id.eq(user.id.clone()))
+        .filter(role_user::Column::UserId.eq(user.id))
         .all(db)
         .await;
 
@@ -81,6 +96,7 @@ pub async fn find_by_email_or_username(
 ) -> Option<(users::Model, Vec<permissions::Model>, Vec<roles::Model>)> {
     let email_or_username = email_or_username.trim().to_lowercase();
     let user = users::Entity::find()
+        .tenanted()
         .filter(users::Column::DeletedAt.is_null())
         .filter(
             Condition::any()
@@ -105,7 +121,7 @@ pub async fn find_by_email_or_username(
     let user = user.unwrap();
     let permissions = permissions::Entity::find()
         .find_with_related(permission_user::Entity)
-        .filter(permission_user::Column::UserId.eq(user.id.clone()))
+        .filter(permission_user::Column::UserId.eq(user.id))
         .all(db)
         .await;
 
@@ -117,7 +133,7 @@ pub async fn find_by_email_or_username(
 
     let roles = roles::Entity::find()
         .find_with_related(role_user::Entity)
-        .filter(role_user::Column::UserId.eq(user.id.clone()))
+        .filter(role_user::Column::UserId.eq(user.id))
         .all(db)
         .await;
 
@@ -142,20 +158,28 @@ pub async fn find_by_email_or_username(
     Some((user, permissions, roles))
 }
 
-pub async fn store(
-    db: &DatabaseConnection,
+pub async fn store<C: ConnectionTrait + TransactionTrait>(
+    db: &C,
+    actor: &Actor,
     request: UserStoreRequest,
 ) -> Result<(users::Model, Vec<permissions::Model>, Vec<roles::Model>), DbErr> {
+    let tenant_id = tenant::required()?;
     let tx = db.begin().await?;
     let id = Uuid::new_v4();
     let user = users::Model {
-        id: id.into(),
+        id,
+        tenant_id,
         name: request.name.trim().to_lowercase(),
         email: request.email.trim().to_lowercase(),
         email_verified_at: None,
         username: request.username.trim().to_lowercase(),
-        password: hash::make(id, request.password).to_string(),
+        password: hash::make(id.to_string(), request.password).to_string(),
         profile_photo_id: None,
+        locale: None,
+        registered_at: None,
+        status: "active".to_string(),
+        status_reason: None,
+        suspended_until: None,
         created_at: time::now(),
         updated_at: time::now(),
         deleted_at: None,
@@ -186,22 +210,28 @@ pub async fn store(
         .clone()
         .iter()
         .map(|permission| permission_user::Model {
-            id: Uuid::new_v4().into(),
-            user_id: user.id.clone(),
-            permission_id: permission.id.clone(),
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            permission_id: permission.id,
+            starts_at: None,
+            expires_at: None,
         })
         .map(|permission_user| permission_user::ActiveModel::from(permission_user))
         .collect::<Vec<_>>();
 
-    let permission_users = permission_user::Entity::insert_many(permission_users);
+    // an empty insert is not valid sql
+    if !permission_users.is_empty() {
+        let permission_users = permission_user::Entity::insert_many(permission_users);
 
-    if let Err(e) = permission_users.exec(&tx).await {
-        tx.rollback().await?;
+        if let Err(e) = permission_users.exec(&tx).await {
+            tx.rollback().await?;
 
-        return Err(e);
+            return Err(e);
+        }
     }
 
     let roles = roles::Entity::find()
+        .tenanted()
         .filter(roles::Column::Id.is_in(request.roles))
         .all(&tx)
         .await;
@@ -217,58 +247,99 @@ pub async fn store(
         .clone()
         .iter()
         .map(|role| role_user::Model {
-            id: Uuid::new_v4().into(),
-            user_id: user.id.clone(),
-            role_id: role.id.clone(),
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            role_id: role.id,
+            starts_at: None,
+            expires_at: None,
         })
         .map(|role_user| role_user::ActiveModel::from(role_user))
         .collect::<Vec<_>>();
 
-    let role_users = role_user::Entity::insert_many(role_users);
+    // an empty insert is not valid sql
+    if !role_users.is_empty() {
+        let role_users = role_user::Entity::insert_many(role_users);
 
-    if let Err(e) = role_users.exec(&tx).await {
-        tx.rollback().await?;
+        if let Err(e) = role_users.exec(&tx).await {
+            tx.rollback().await?;
 
-        return Err(e);
+            return Err(e);
+        }
     }
 
+    let user = (user, permissions, roles);
+
+    dao::audit::change(&tx, actor, None, Some(&user)).await?;
     tx.commit().await?;
 
-    Ok((user, permissions, roles))
+    Ok(user)
 }
 
-pub async fn update_general_information(
+/// Store a user signing up themselves, with the given roles.
+pub async fn register(
     db: &DatabaseConnection,
-    user: users::Model,
-    request: UserUpdateGeneralInformationRequest,
+    actor: &Actor,
+    request: Register,
+    roles: Vec<Id>,
 ) -> Result<(users::Model, Vec<permissions::Model>, Vec<roles::Model>), DbErr> {
     let tx = db.begin().await?;
-    let name = request.name.trim().to_lowercase();
-    let email = request.email.trim().to_lowercase();
-    let username = request.username.trim().to_lowercase();
-    let mut model = users::ActiveModel::from(user.clone());
+    let (user, permissions, roles) = store(
+        &tx,
+        actor,
+        UserStoreRequest {
+            name: request.name,
+            email: request.email,
+            username: request.username,
+            password: request.password,
+            permissions: vec![],
+            roles,
+        },
+    )
+    .await?;
 
-    if user.name != name {
-        model.name = Set(name);
-    }
+    let mut model = users::ActiveModel::from(user);
+    model.registered_at = Set(Some(time::now()));
+    let user = model.update(&tx).await?;
 
-    if user.email != email {
-        model.email = Set(email);
-    }
+    tx.commit().await?;
 
-    if user.username != username {
-        model.username = Set(username);
-    }
+    Ok((user, permissions, roles))
+}
 
-    if let Some(at) = user.email_verified_at {
-        model.email_verified_at = Set(Some(at));
-    }
+pub async fn verify_email(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    user: users::Model,
+) -> Result<users::Model, DbErr> {
+    let tx = db.begin().await?;
+    let mut model = users::ActiveModel::from(user.clone());
+    model.email_verified_at = Set(Some(time::now()));
+    model.updated_at = Set(time::now());
+    let updated = model.update(&tx).await?;
 
-    if let Some(id) = user.profile_photo_id {
-        model.profile_photo_id = Set(Some(id));
-    }
+    dao::audit::change(&tx, actor, Some(&user), Some(&updated)).await?;
+    tx.commit().await?;
 
-    model.updated_at = Set(time::now());
+    Ok(updated)
+}
+
+pub async fn update_general_information(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    user: (users::Model, Vec<permissions::Model>, Vec<roles::Model>),
+    request: UserUpdateGeneralInformationRequest,
+) -> Result<(users::Model, Vec<permissions::Model>, Vec<roles::Model>), DbErr> {
+    let before = user.clone();
+    let (user, _, _) = user;
+    let tx = db.begin().await?;
+    let model = profile(
+        &user,
+        &request.name,
+        &request.email,
+        &request.username,
+        request.profile_photo_id.as_ref(),
+        request.locale.as_deref(),
+    );
 
     let user = model.update(&tx).await;
 
@@ -291,8 +362,17 @@ pub async fn update_general_information(
     }
 
     let permissions = permissions?;
+    // what is kept keeps when it is in effect
     let delete = permission_user::Entity::delete_many()
-        .filter(permission_user::Column::UserId.eq(user.id.clone()));
+        .filter(permission_user::Column::UserId.eq(user.id))
+        .filter(
+            permission_user::Column::PermissionId.is_not_in(
+                permissions
+                    .iter()
+                    .map(|permission| permission.id)
+                    .collect::<Vec<_>>(),
+            ),
+        );
 
     if let Err(e) = delete.exec(&tx).await {
         tx.rollback().await?;
@@ -303,24 +383,31 @@ pub async fn update_general_information(
     let permission_user = permissions
         .clone()
         .iter()
+        .filter(|permission| !before.1.iter().any(|held| held.id == permission.id))
         .map(|permission| {
             permission_user::ActiveModel::from(permission_user::Model {
-                id: Uuid::new_v4().into(),
-                permission_id: permission.id.clone(),
-                user_id: user.id.clone(),
+                id: Uuid::new_v4(),
+                permission_id: permission.id,
+                user_id: user.id,
+                starts_at: None,
+                expires_at: None,
             })
         })
         .collect::<Vec<_>>();
 
-    let permission_user = permission_user::Entity::insert_many(permission_user);
+    // an empty insert is not valid sql
+    if !permission_user.is_empty() {
+        let permission_user = permission_user::Entity::insert_many(permission_user);
 
-    if let Err(e) = permission_user.exec(&tx).await {
-        tx.rollback().await?;
+        if let Err(e) = permission_user.exec(&tx).await {
+            tx.rollback().await?;
 
-        return Err(e);
+            return Err(e);
+        }
     }
 
     let roles = roles::Entity::find()
+        .tenanted()
         .filter(roles::Column::Id.is_in(request.roles))
         .all(&tx)
         .await;
@@ -332,8 +419,12 @@ pub async fn update_general_information(
     }
 
     let roles = roles?;
-    let delete =
-        role_user::Entity::delete_many().filter(role_user::Column::UserId.eq(user.id.clone()));
+    let delete = role_user::Entity::delete_many()
+        .filter(role_user::Column::UserId.eq(user.id))
+        .filter(
+            role_user::Column::RoleId
+                .is_not_in(roles.iter().map(|role| role.id).collect::<Vec<_>>()),
+        );
 
     if let Err(e) = delete.exec(&tx).await {
         tx.rollback().await?;
@@ -344,117 +435,498 @@ pub async fn update_general_information(
     let role_user = roles
         .clone()
         .iter()
+        .filter(|role| !before.2.iter().any(|held| held.id == role.id))
         .map(|role| {
             role_user::ActiveModel::from(role_user::Model {
-                id: Uuid::new_v4().into(),
-                role_id: role.id.clone(),
-                user_id: user.id.clone(),
+                id: Uuid::new_v4(),
+                role_id: role.id,
+                user_id: user.id,
+                starts_at: None,
+                expires_at: None,
             })
         })
         .collect::<Vec<_>>();
 
-    let role_user = role_user::Entity::insert_many(role_user);
+    // an empty insert is not valid sql
+    if !role_user.is_empty() {
+        let role_user = role_user::Entity::insert_many(role_user);
 
-    if let Err(e) = role_user.exec(&tx).await {
-        tx.rollback().await?;
+        if let Err(e) = role_user.exec(&tx).await {
+            tx.rollback().await?;
 
-        return Err(e);
+            return Err(e);
+        }
     }
 
-    Ok((user, permissions, roles))
+    let user = (user, permissions, roles);
+
+    dao::audit::change(&tx, actor, Some(&before), Some(&user)).await?;
+    tx.commit().await?;
+
+    Ok(user)
+}
+
+pub async fn update_profile(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    user: users::Model,
+    request: UserUpdateProfileRequest,
+) -> Result<users::Model, DbErr> {
+    let tx = db.begin().await?;
+    let updated = profile(
+        &user,
+        &request.name,
+        &request.email,
+        &request.username,
+        request.profile_photo_id.as_ref(),
+        request.locale.as_deref(),
+    )
+    .update(&tx)
+    .await?;
+
+    dao::audit::change(&tx, actor, Some(&user), Some(&updated)).await?;
+    tx.commit().await?;
+
+    Ok(updated)
+}
+
+/// The changed profile fields of `user`, normalised like on store.
+fn profile(
+    user: &users::Model,
+    name: &str,
+    email: &str,
+    username: &str,
+    profile_photo_id: Option<&Id>,
+    locale: Option<&str>,
+) -> users::ActiveModel {
+    let name = name.trim().to_lowercase();
+    let email = email.trim().to_lowercase();
+    let username = username.trim().to_lowercase();
+    let mut model = users::ActiveModel::from(user.clone());
+
+    if user.name != name {
+        model.name = Set(name);
+    }
+
+    if user.email != email {
+        model.email = Set(email);
+    }
+
+    if user.username != username {
+        model.username = Set(username);
+    }
+
+    if let Some(at) = user.email_verified_at {
+        model.email_verified_at = Set(Some(at));
+    }
+
+    let profile_photo_id = profile_photo_id.map(ToString::to_string);
+
+    if user.profile_photo_id != profile_photo_id {
+        model.profile_photo_id = Set(profile_photo_id);
+    }
+
+    let locale = locale
+        .and_then(|locale| locale.parse::<Locale>().ok())
+        .map(|locale| locale.to_string());
+
+    if user.locale != locale {
+        model.locale = Set(locale);
+    }
+
+    model.updated_at = Set(time::now());
+    model
 }
 
 pub async fn update_password(
     db: &DatabaseConnection,
+    actor: &Actor,
     user: users::Model,
     password: String,
 ) -> Result<users::Model, DbErr> {
-    let password = hash::make(user.id.clone(), password);
+    let tx = db.begin().await?;
+    let password = hash::make(user.id.to_string(), password);
     let mut model = users::ActiveModel::from(user);
     model.password = Set(password.to_string());
     model.updated_at = Set(time::now());
-    model.update(db).await
-}
+    let user = model.update(&tx).await?;
+
+    // the hash has no business in the log, only the fact it changed
+    dao::audit::record(
+        &tx,
+        actor,
+        AuditAction::Update,
+        AuditEntity::User,
+        user.id,
+        None,
+        Some(json!({ "password": "[redacted]" })),
+    )
+    .await?;
+    tx.commit().await?;
 
-pub async fn delete(db: &DatabaseConnection, user: users::Model) -> Result<users::Model, DbErr> {
-    let mut model = users::ActiveModel::from(user);
-    model.deleted_at = Set(Some(time::now()));
-    model.update(db).await
+    Ok(user)
 }
 
-pub async fn email_exist<T: ToString>(db: &DatabaseConnection, email: T) -> Result<bool, DbErr> {
-    let email = email.to_string().trim().to_lowercase();
-    let exist = users::Entity::find()
-        .filter(users::Column::Email.eq(email))
-        .count(db)
-        .await;
+/// Move `user` to `status`, keeping why and, for suspensions, until when.
+pub async fn set_status(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    user: users::Model,
+    status: AccountStatus,
+    reason: Option<String>,
+    until: Option<Timestamp>,
+) -> Result<users::Model, DbErr> {
+    let tx = db.begin().await?;
+    let mut model = users::ActiveModel::from(user.clone());
+    model.status = Set(status.as_str().to_string());
+    model.status_reason = Set(reason);
+    model.suspended_until = Set(until);
+    model.updated_at = Set(time::now());
+    let updated = model.update(&tx).await?;
 
-    if let Err(e) = exist {
-        log::error!(email_exist, "{}", e);
+    dao::audit::change(&tx, actor, Some(&user), Some(&updated)).await?;
+    tx.commit().await?;
 
-        return Err(e);
-    }
+    Ok(updated)
+}
 
-    Ok(exist? > 0)
+/// What each of `users` is granted directly.
+pub async fn grants(
+    db: &DatabaseConnection,
+    users: Vec<users::Model>,
+) -> Result<Vec<Grants>, DbErr> {
+    let ids = users.iter().map(|user| user.id).collect::<Vec<_>>();
+    let permission_user = permission_user::Entity::find()
+        .find_also_related(permissions::Entity)
+        .filter(permission_user::Column::UserId.is_in(ids.clone()))
+        .all(db)
+        .await?;
+    let role_user = role_user::Entity::find()
+        .find_also_related(roles::Entity)
+        .filter(role_user::Column::UserId.is_in(ids))
+        .all(db)
+        .await?;
+
+    Ok(users
+        .into_iter()
+        .map(|user| {
+            let permissions = permission_user
+                .iter()
+                .filter(|(row, _)| row.user_id == user.id)
+                .filter_map(|(row, permission)| {
+                    permission
+                        .clone()
+                        .map(|permission| (permission, permission_user::Entity::period(row)))
+                })
+                .collect();
+            let roles = role_user
+                .iter()
+                .filter(|(row, _)| row.user_id == user.id)
+                .filter_map(|(row, role)| {
+                    role.clone()
+                        .map(|role| (role, role_user::Entity::period(row)))
+                })
+                .collect();
+
+            (user, permissions, roles)
+        })
+        .collect())
 }
 
-pub async fn email_exist_except<T: ToString, I: Into<Id>>(
+/// Change what each of `users` is granted directly into what `change` makes
+/// of their permissions and roles along with when those are in effect,
+/// recording every user it changed, and answer how many those are.
+pub async fn regrant<F>(
     db: &DatabaseConnection,
-    email: T,
-    id: I,
-) -> Result<bool, DbErr> {
-    let email = email.to_string().trim().to_lowercase();
-    let exist = users::Entity::find()
-        .filter(users::Column::Email.eq(email))
-        .filter(users::Column::Id.ne(id.into()))
-        .count(db)
-        .await;
+    actor: &Actor,
+    users: Vec<users::Model>,
+    change: F,
+) -> Result<usize, DbErr>
+where
+    F: Fn(&mut Vec<(permissions::Model, Period)>, &mut Vec<(roles::Model, Period)>),
+{
+    let grants = grants(db, users).await?;
+    let tx = db.begin().await?;
+    let mut changed = 0;
+
+    for before in grants {
+        let mut after = before.clone();
+
+        change(&mut after.1, &mut after.2);
+
+        if after == before {
+            continue;
+        }
+
+        let user_id = before.0.id;
+
+        for (permission, period) in &before.1 {
+            if !after.1.contains(&(permission.clone(), period.clone())) {
+                permission_user::Entity::delete_many()
+                    .filter(permission_user::Column::UserId.eq(user_id))
+                    .filter(permission_user::Column::PermissionId.eq(permission.id))
+                    .exec(&tx)
+                    .await?;
+            }
+        }
+
+        for (role, period) in &before.2 {
+            if !after.2.contains(&(role.clone(), period.clone())) {
+                role_user::Entity::delete_many()
+                    .filter(role_user::Column::UserId.eq(user_id))
+                    .filter(role_user::Column::RoleId.eq(role.id))
+                    .exec(&tx)
+                    .await?;
+            }
+        }
+
+        // whatever changed its period was removed above along with what
+        // was revoked, and comes back in here
+        let granted = after
+            .1
+            .iter()
+            .filter(|grant| !before.1.contains(grant))
+            .map(|(permission, period)| {
+                permission_user::ActiveModel::from(permission_user::Model {
+                    id: Uuid::new_v4(),
+                    permission_id: permission.id,
+                    user_id,
+                    starts_at: period.starts_at,
+                    expires_at: period.expires_at,
+                })
+            })
+            .collect::<Vec<_>>();
+        let assigned = after
+            .2
+            .iter()
+            .filter(|grant| !before.2.contains(grant))
+            .map(|(role, period)| {
+                role_user::ActiveModel::from(role_user::Model {
+                    id: Uuid::new_v4(),
+                    role_id: role.id,
+                    user_id,
+                    starts_at: period.starts_at,
+                    expires_at: period.expires_at,
+                })
+            })
+            .collect::<Vec<_>>();
 
-    if let Err(e) = exist {
-        log::error!(email_exist, "{}", e);
+        // an empty insert is not valid sql
+        if !granted.is_empty() {
+            permission_user::Entity::insert_many(granted)
+                .exec(&tx)
+                .await?;
+        }
 
-        return Err(e);
+        if !assigned.is_empty() {
+            role_user::Entity::insert_many(assigned).exec(&tx).await?;
+        }
+
+        dao::audit::change(&tx, actor, Some(&before), Some(&after)).await?;
+        changed += 1;
     }
 
-    Ok(exist? > 0)
+    tx.commit().await?;
+
+    Ok(changed)
 }
 
-pub async fn username_exist<T: ToString>(
+/// Whether any of `users` is assigned any of `roles` directly, whether in
+/// effect or not.
+pub async fn assigned(
     db: &DatabaseConnection,
-    username: T,
+    users: &[Id],
+    roles: HashSet<Id>,
 ) -> Result<bool, DbErr> {
-    let username = username.to_string().trim().to_lowercase();
-    let exist = users::Entity::find()
-        .filter(users::Column::Username.eq(username))
+    let count = role_user::Entity::find()
+        .filter(role_user::Column::UserId.is_in(users.to_vec()))
+        .filter(role_user::Column::RoleId.is_in(roles))
         .count(db)
-        .await;
+        .await?;
 
-    if let Err(e) = exist {
-        log::error!(username_exist, "{}", e);
+    Ok(count > 0)
+}
 
-        return Err(e);
-    }
+/// Ids of the active users holding any of `roles` directly right now.
+pub async fn holders(db: &DatabaseConnection, roles: HashSet<Id>) -> Result<Vec<Id>, DbErr> {
+    holding(roles).into_tuple::<Id>().all(db).await
+}
+
+/// Ids of the active users holding any of `roles` directly right now, with
+/// no expiry to end it.
+pub async fn lasting_holders(
+    db: &DatabaseConnection,
+    roles: HashSet<Id>,
+) -> Result<Vec<Id>, DbErr> {
+    holding(roles)
+        .filter(role_user::Column::ExpiresAt.is_null())
+        .into_tuple::<Id>()
+        .all(db)
+        .await
+}
 
-    Ok(exist? > 0)
+fn holding(roles: HashSet<Id>) -> Select<users::Entity> {
+    users::Entity::find()
+        .select_only()
+        .column(users::Column::Id)
+        .distinct()
+        .inner_join(role_user::Entity)
+        .filter(role_user::Column::RoleId.is_in(roles))
+        .filter(dao::grant::in_effect::<role_user::Entity>(time::now()))
+        .filter(users::Column::Status.eq(AccountStatus::Active.as_str()))
+        .filter(users::Column::DeletedAt.is_null())
 }
 
-pub async fn username_exist_except<T: ToString, I: Into<Id>>(
+pub async fn delete(
     db: &DatabaseConnection,
-    username: T,
-    id: I,
-) -> Result<bool, DbErr> {
-    let username = username.to_string().trim().to_lowercase();
-    let exist = users::Entity::find()
-        .filter(users::Column::Username.eq(username))
-        .filter(users::Column::Id.ne(id.into()))
-        .count(db)
-        .await;
+    actor: &Actor,
+    user: users::Model,
+) -> Result<users::Model, DbErr> {
+    let tx = db.begin().await?;
+    let mut model = users::ActiveModel::from(user.clone());
+    model.deleted_at = Set(Some(time::now()));
+    let deleted = model.update(&tx).await?;
 
-    if let Err(e) = exist {
-        log::error!(username_exist, "{}", e);
+    dao::audit::change(&tx, actor, Some(&user), None).await?;
+    tx.commit().await?;
 
-        return Err(e);
+    Ok(deleted)
+}
+
+#[cfg(test)]
+pub mod test {
+    use sea_orm::prelude::*;
+    use sea_orm::DatabaseConnection;
+
+    use crate::common::hash::{self, Hash};
+    use crate::common::time;
+    use crate::dao;
+    use crate::middlewares::audit::Actor;
+    use crate::models::{role_user, roles, users};
+    use crate::requests::user::{UserStoreRequest, UserUpdateGeneralInformationRequest};
+    use crate::tenant;
+
+    /// An active user of a tenant of their own.
+    pub async fn user(db: &DatabaseConnection) -> users::Model {
+        users::ActiveModel::from(users::Model {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            name: "john doe".to_string(),
+            email: "john@local.id".to_string(),
+            email_verified_at: None,
+            username: "john".to_string(),
+            password: String::new(),
+            profile_photo_id: None,
+            locale: None,
+            registered_at: None,
+            status: "active".to_string(),
+            status_reason: None,
+            suspended_until: None,
+            created_at: time::now(),
+            updated_at: time::now(),
+            deleted_at: None,
+        })
+        .insert(db)
+        .await
+        .unwrap()
     }
 
-    Ok(exist? > 0)
+    #[test]
+    pub async fn update_general_information_must_be_kept() {
+        let db = dao::test::database().await;
+        let user = user(&db).await;
+        let request = UserUpdateGeneralInformationRequest {
+            name: "Jane Doe".to_string(),
+            email: "jane@local.id".to_string(),
+            username: "jane".to_string(),
+            profile_photo_id: None,
+            locale: None,
+            permissions: vec![],
+            roles: vec![],
+        };
+
+        super::update_general_information(
+            &db,
+            &Actor::system(),
+            (user.clone(), vec![], vec![]),
+            request,
+        )
+        .await
+        .unwrap();
+
+        let user = users::Entity::find_by_id(user.id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(user.name, "jane doe");
+        assert_eq!(user.email, "jane@local.id");
+    }
+
+    #[test]
+    pub async fn password_must_be_salted_as_sign_in_checks_it() {
+        let db = dao::test::database().await;
+        let request = UserStoreRequest {
+            name: "John Doe".to_string(),
+            email: "john@local.id".to_string(),
+            username: "john".to_string(),
+            password: "secret".to_string(),
+            permissions: vec![],
+            roles: vec![],
+        };
+        let (user, _, _) =
+            tenant::scope(Uuid::new_v4(), super::store(&db, &Actor::system(), request))
+                .await
+                .unwrap();
+
+        assert!(hash::verify(
+            Hash::from(&user.password),
+            user.id.to_string(),
+            "secret"
+        ));
+
+        let user = super::update_password(&db, &Actor::system(), user, "changed".to_string())
+            .await
+            .unwrap();
+
+        assert!(hash::verify(
+            Hash::from(&user.password),
+            user.id.to_string(),
+            "changed"
+        ));
+    }
+
+    #[test]
+    pub async fn find_must_load_the_roles_of_the_user() {
+        let db = dao::test::database().await;
+        let user = user(&db).await;
+        let role = roles::ActiveModel::from(roles::Model {
+            id: Uuid::new_v4(),
+            tenant_id: user.tenant_id,
+            code: "teacher".to_string(),
+            name: "Teacher".to_string(),
+            system: false,
+        })
+        .insert(&db)
+        .await
+        .unwrap();
+
+        role_user::ActiveModel::from(role_user::Model {
+            id: Uuid::new_v4(),
+            role_id: role.id,
+            user_id: user.id,
+            starts_at: None,
+            expires_at: None,
+        })
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let (_, _, roles) = tenant::scope(user.tenant_id, super::find(&db, user.id))
+            .await
+            .unwrap();
+
+        assert_eq!(roles, vec![role]);
+    }
 }
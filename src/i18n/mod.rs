@@ -0,0 +1,248 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+#[macro_export]
+macro_rules! i18n_t {
+    ($key:expr) => {
+        $crate::i18n::translate::<&str, &str>($crate::i18n::current(), $key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate(
+            $crate::i18n::current(),
+            $key,
+            &[$((stringify!($name), $value.to_string())),+],
+        )
+    };
+}
+
+pub use i18n_t as t;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Id,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Id];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Id => "id",
+        }
+    }
+
+    /// The most preferred supported locale of an `Accept-Language` header.
+    pub fn negotiate(header: &str) -> Option<Self> {
+        let mut ranges = header
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|part| part.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+
+                Some((tag, quality))
+            })
+            .filter(|(_, quality)| *quality > 0.0)
+            .collect::<Vec<_>>();
+
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranges.into_iter().find_map(|(tag, _)| match tag {
+            "*" => Some(Self::default()),
+            tag => tag.parse().ok(),
+        })
+    }
+}
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let language = s.split(['-', '_']).next().unwrap_or_default();
+
+        match language.trim().to_lowercase().as_str() {
+            "en" => Ok(Self::En),
+            // "in" is the deprecated code for indonesian
+            "id" | "in" => Ok(Self::Id),
+            _ => Err(format!("unsupported locale {}", s)),
+        }
+    }
+}
+
+tokio::task_local! {
+    static LOCALE: Cell<Locale>;
+}
+
+/// Locale of the request being handled, the default one outside of a request.
+pub fn current() -> Locale {
+    LOCALE.try_with(Cell::get).unwrap_or_default()
+}
+
+/// Switch the locale for the rest of the current request.
+pub fn set(locale: Locale) {
+    let _ = LOCALE.try_with(|current| current.set(locale));
+}
+
+pub async fn scope<F: Future>(locale: Locale, f: F) -> F::Output {
+    LOCALE.scope(Cell::new(locale), f).await
+}
+
+pub fn sync_scope<F: FnOnce() -> R, R>(locale: Locale, f: F) -> R {
+    LOCALE.sync_scope(Cell::new(locale), f)
+}
+
+type Catalogue = HashMap<String, String>;
+
+fn catalogues() -> &'static HashMap<Locale, Catalogue> {
+    static CATALOGUES: OnceLock<HashMap<Locale, Catalogue>> = OnceLock::new();
+
+    CATALOGUES.get_or_init(|| {
+        Locale::ALL
+            .into_iter()
+            .map(|locale| {
+                let source = match locale {
+                    Locale::En => include_str!("../../locales/en.json"),
+                    Locale::Id => include_str!("../../locales/id.json"),
+                };
+                let value = serde_json::from_str(source)
+                    .unwrap_or_else(|e| panic!("invalid catalogue {}: {}", locale, e));
+                let mut catalogue = Catalogue::new();
+
+                flatten(&mut catalogue, String::new(), value);
+
+                (locale, catalogue)
+            })
+            .collect()
+    })
+}
+
+fn flatten(catalogue: &mut Catalogue, prefix: String, value: Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let key = match prefix.is_empty() {
+                    true => key,
+                    false => format!("{}.{}", prefix, key),
+                };
+
+                flatten(catalogue, key, value);
+            }
+        }
+        Value::String(message) => {
+            catalogue.insert(prefix, message);
+        }
+        value => {
+            catalogue.insert(prefix, value.to_string());
+        }
+    }
+}
+
+/// The raw message of `key`, falling back to the default locale.
+pub fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    let catalogues = catalogues();
+
+    catalogues
+        .get(&locale)
+        .and_then(|catalogue| catalogue.get(key))
+        .or_else(|| catalogues.get(&Locale::default())?.get(key))
+        .map(String::as_str)
+}
+
+/// The message of `key` with every `{name}` replaced by its parameter,
+/// or the key itself when no catalogue knows it.
+pub fn translate<K: AsRef<str>, V: AsRef<str>>(
+    locale: Locale,
+    key: &str,
+    params: &[(K, V)],
+) -> String {
+    let mut message = lookup(locale, key).unwrap_or(key).to_string();
+
+    for (name, value) in params {
+        message = message.replace(&format!("{{{}}}", name.as_ref()), value.as_ref());
+    }
+
+    message
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    #[test]
+    pub async fn negotiate_must_respect_quality() {
+        assert_eq!(
+            Locale::negotiate("id-ID,id;q=0.9,en;q=0.8"),
+            Some(Locale::Id)
+        );
+        assert_eq!(
+            Locale::negotiate("fr;q=1,en;q=0.5,id;q=0.7"),
+            Some(Locale::Id)
+        );
+        assert_eq!(Locale::negotiate("fr,de;q=0.5"), None);
+        assert_eq!(Locale::negotiate("id;q=0,*"), Some(Locale::En));
+    }
+
+    #[test]
+    pub async fn translate_must_interpolate_and_fall_back() {
+        assert_eq!(
+            translate(Locale::Id, "user.deleted", &[("username", "john")]),
+            "Pengguna john telah dihapus"
+        );
+        assert_eq!(
+            translate::<&str, &str>(Locale::Id, "missing.key", &[]),
+            "missing.key"
+        );
+    }
+
+    #[test]
+    pub async fn catalogues_must_have_the_same_keys() {
+        let catalogues = catalogues();
+        let default = &catalogues[&Locale::default()];
+
+        for locale in Locale::ALL {
+            let catalogue = &catalogues[&locale];
+            let mut missing = default
+                .keys()
+                .filter(|key| !catalogue.contains_key(*key))
+                .chain(catalogue.keys().filter(|key| !default.contains_key(*key)))
+                .collect::<Vec<_>>();
+
+            missing.sort();
+
+            assert!(missing.is_empty(), "{} is missing {:?}", locale, missing);
+        }
+    }
+
+    #[test]
+    pub async fn scope_must_set_current_locale() {
+        assert_eq!(current(), Locale::En);
+        assert_eq!(scope(Locale::Id, async { current() }).await, Locale::Id);
+        assert_eq!(
+            scope(Locale::Id, async {
+                set(Locale::En);
+                t!("user.not_found")
+            })
+            .await,
+            "User not found"
+        );
+    }
+}
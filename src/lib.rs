@@ -3,13 +3,23 @@ extern crate actix_web;
 
 pub mod api;
 pub mod app;
+pub mod captcha;
+pub mod catalogue;
 pub mod common;
 pub mod controllers;
 pub mod dao;
+pub mod i18n;
+pub mod mail;
 pub mod middlewares;
 pub mod models;
+pub mod queue;
 pub mod requests;
 pub mod responses;
 pub mod route;
+pub mod scheduler;
 pub mod services;
+pub mod sheet;
+pub mod storage;
+pub mod tenant;
 pub mod types;
+pub mod validation;
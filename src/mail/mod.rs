@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use lettre::message::Mailbox;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{env, log};
+use crate::dao;
+use crate::i18n::{self, Locale};
+use crate::models::{jobs, users, Id};
+use crate::queue::{self, Error, Job};
+use crate::requests::preference::Notifications;
+
+/// Templates of the notices about the security of an account, sent even to
+/// users who turned mail notifications off.
+pub const SECURITY: [&str; 2] = ["password_changed", "verify_email"];
+
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Deliver through the smtp server of `MAIL_URL`, or only log the mail
+/// when it is not configured.
+pub async fn send(mail: Mail) -> Result<(), Error> {
+    let url: String = env::var("MAIL_URL", String::new());
+
+    if url.is_empty() {
+        log::info!(Mail, "to {}: {}\n{}", mail.to, mail.subject, mail.body);
+
+        return Ok(());
+    }
+
+    let from: Mailbox = env::var("MAIL_FROM", "no-reply@localhost".to_string()).parse()?;
+    let message = Message::builder()
+        .from(from)
+        .to(mail.to.parse()?)
+        .subject(mail.subject)
+        .body(mail.body)?;
+
+    AsyncSmtpTransport::<Tokio1Executor>::from_url(&url)?
+        .build()
+        .send(message)
+        .await?;
+
+    Ok(())
+}
+
+/// A mail rendered from the `mail.{template}` catalogue entries in the
+/// recipient's locale when the job runs.
+#[derive(Serialize, Deserialize)]
+pub struct SendMail {
+    pub to: String,
+    /// The user the mail is for, `None` for someone without an account.
+    #[serde(default)]
+    pub user_id: Option<Id>,
+    pub locale: Locale,
+    pub template: String,
+    pub params: HashMap<String, String>,
+}
+
+impl SendMail {
+    pub fn new<T: ToString>(user: &users::Model, template: T) -> Self {
+        Self {
+            to: user.email.clone(),
+            user_id: Some(user.id),
+            locale: user
+                .locale
+                .as_deref()
+                .and_then(|locale| locale.parse().ok())
+                .unwrap_or_default(),
+            template: template.to_string(),
+            params: HashMap::from([
+                ("name".to_string(), user.name.clone()),
+                ("username".to_string(), user.username.clone()),
+            ]),
+        }
+    }
+
+    /// A mail to someone without an account yet, in the current locale.
+    pub fn guest<T: ToString>(email: &str, name: &str, template: T) -> Self {
+        Self {
+            to: email.to_string(),
+            user_id: None,
+            locale: i18n::current(),
+            template: template.to_string(),
+            params: HashMap::from([("name".to_string(), name.to_string())]),
+        }
+    }
+
+    pub fn param<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.params.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn render(&self) -> Mail {
+        let params = self.params.iter().collect::<Vec<_>>();
+        let subject = format!("mail.{}.subject", self.template);
+        let body = format!("mail.{}.body", self.template);
+
+        Mail {
+            to: self.to.clone(),
+            subject: i18n::translate(self.locale, &subject, &params),
+            body: i18n::translate(self.locale, &body, &params),
+        }
+    }
+
+    /// Whether the recipient still accepts the mail by the time it is sent,
+    /// which security notices and mails to guests always are.
+    pub async fn wanted(&self, db: &DatabaseConnection) -> Result<bool, DbErr> {
+        let Some(user_id) = self.user_id else {
+            return Ok(true);
+        };
+
+        if SECURITY.contains(&self.template.as_str()) {
+            return Ok(true);
+        }
+
+        let preferences = dao::preference::find(db, user_id).await?;
+        let notifications: Notifications =
+            serde_json::from_value(preferences.notifications).unwrap_or_default();
+
+        Ok(notifications.email)
+    }
+
+    pub async fn dispatch(self, db: &DatabaseConnection) -> Result<jobs::Model, DbErr> {
+        queue::dispatch(db, self).await
+    }
+}
+
+impl Job for SendMail {
+    const NAME: &'static str = "send_mail";
+
+    async fn handle(self, db: DatabaseConnection) -> Result<(), Error> {
+        if !self.wanted(&db).await? {
+            log::info!(
+                Mail,
+                "{} to {} skipped, notifications are off",
+                self.template,
+                self.to
+            );
+
+            return Ok(());
+        }
+
+        send(self.render()).await
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use sea_orm::prelude::*;
+
+    use crate::dao;
+    use crate::models::preferences;
+
+    use super::SendMail;
+
+    #[test]
+    pub async fn mail_must_only_be_wanted_as_notifications_allow() {
+        let db = dao::test::database().await;
+        let user = dao::user::test::user(&db).await;
+
+        assert!(SendMail::new(&user, "invite").wanted(&db).await.unwrap());
+
+        preferences::ActiveModel::from(preferences::Model {
+            notifications: serde_json::json!({ "email": false }),
+            ..dao::preference::default(user.id)
+        })
+        .insert(&db)
+        .await
+        .unwrap();
+
+        assert!(!SendMail::new(&user, "invite").wanted(&db).await.unwrap());
+        assert!(SendMail::new(&user, "password_changed")
+            .wanted(&db)
+            .await
+            .unwrap());
+        assert!(SendMail::guest(&user.email, &user.name, "invitation")
+            .wanted(&db)
+            .await
+            .unwrap());
+    }
+}
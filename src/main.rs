@@ -5,31 +5,74 @@ use sea_orm::Database;
 
 mod api;
 mod app;
+mod captcha;
+mod catalogue;
 mod common;
 mod controllers;
 mod dao;
+mod i18n;
+mod mail;
 mod middlewares;
 mod models;
+mod queue;
 mod requests;
 mod responses;
 mod route;
+mod scheduler;
 mod services;
+mod sheet;
+mod storage;
+mod tenant;
 mod types;
+mod validation;
 
 #[cfg(not(feature = "shuttle"))]
 #[actix_web::main]
 pub async fn main() -> std::io::Result<()> {
     use actix_web::{App, HttpServer};
 
+    // `dump-spec [json|yaml]` prints the api spec for generating clients,
+    // needing neither the environment nor a database
+    if std::env::args().nth(1).as_deref() == Some("dump-spec") {
+        let format = std::env::args().nth(2).unwrap_or_else(|| "json".into());
+
+        println!("{}", api::dump(&format)?);
+
+        return Ok(());
+    }
+
     dotenv::dotenv().ok();
 
+    if let Err(e) = common::sign::check() {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+    }
+
     let host = std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
     let port = std::env::var("PORT").unwrap_or_else(|_| "8000".to_string());
     let db = Database::connect(std::env::var("DATABASE_URL").unwrap())
         .await
         .unwrap();
+    let synced = dao::permission::sync(&db).await.unwrap();
+
+    common::log::info!(
+        main,
+        "permissions synced, {} created, {} updated, {} orphaned",
+        synced.created,
+        synced.updated,
+        synced.orphaned
+    );
+
+    // `sync-permissions` only syncs the catalogue, as deployments may run
+    // before starting the server
+    if std::env::args().nth(1).as_deref() == Some("sync-permissions") {
+        return Ok(());
+    }
 
-    HttpServer::new(move || App::new().configure(app::configure(db.clone())))
+    app::spawn(&db);
+
+    let configure = app::configure(db.clone());
+
+    HttpServer::new(move || App::new().configure(configure.clone()))
         .workers(4)
         .bind((host, port.parse().unwrap()))?
         .run()
@@ -40,10 +83,26 @@ pub async fn main() -> std::io::Result<()> {
 #[shuttle_runtime::main]
 async fn main(
     #[shuttle_secrets::Secrets] store: shuttle_secrets::SecretStore,
-) -> shuttle_actix_web::ShuttleActixWeb<impl FnOnce(&mut ServiceConfig) + Send + Clone + 'static> {
-    let db = Database::connect(store.get("DATABASE_URL").unwrap())
+) -> shuttle_actix_web::ShuttleActixWeb<
+    impl FnOnce(&mut actix_web::web::ServiceConfig) + Send + Clone + 'static,
+> {
+    // the secrets configure what the environment does elsewhere
+    for (key, value) in store {
+        std::env::set_var(key, value);
+    }
+
+    if let Err(e) = common::sign::check() {
+        return Err(shuttle_runtime::Error::Custom(
+            shuttle_runtime::CustomError::msg(e),
+        ));
+    }
+
+    let db = Database::connect(std::env::var("DATABASE_URL").unwrap())
         .await
         .unwrap();
 
+    dao::permission::sync(&db).await.unwrap();
+    app::spawn(&db);
+
     Ok(app::configure(db).into())
 }
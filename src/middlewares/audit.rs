@@ -0,0 +1,53 @@
+use actix_web::http::header;
+use actix_web::HttpRequest;
+
+use crate::middlewares::auth::Auth;
+use crate::models::Id;
+
+/// Who a change is recorded against in the audit log, and from where.
+#[derive(Clone)]
+pub struct Actor {
+    pub user_id: Option<Id>,
+    /// Whoever impersonated `user_id` when the change was made.
+    pub impersonator_id: Option<Id>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl Actor {
+    /// Somebody not signed in, like a user signing up or following a link.
+    pub fn guest(req: &HttpRequest) -> Self {
+        Self {
+            user_id: None,
+            impersonator_id: None,
+            ip: req
+                .connection_info()
+                .realip_remote_addr()
+                .map(str::to_string),
+            user_agent: req
+                .headers()
+                .get(header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+        }
+    }
+
+    /// The application itself, such as when it syncs the permission
+    /// catalogue on startup.
+    pub fn system() -> Self {
+        Self {
+            user_id: None,
+            impersonator_id: None,
+            ip: None,
+            user_agent: None,
+        }
+    }
+
+    pub fn of(auth: &Auth, req: &HttpRequest) -> Self {
+        Self {
+            user_id: Some(auth.user.id),
+            impersonator_id: auth.impersonator.as_ref().map(|user| user.id),
+            ..Self::guest(req)
+        }
+    }
+}
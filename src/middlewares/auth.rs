@@ -1,26 +1,57 @@
 use core::future::Future;
 use std::collections::HashMap;
+use std::ops::Deref;
 use std::pin::Pin;
 use std::sync::Mutex;
 
 use actix_web::dev::Payload;
 use actix_web::http::header::HeaderValue;
 use actix_web::web::Data;
-use actix_web::{FromRequest, HttpRequest};
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
 use sea_orm::prelude::*;
 use uuid::Uuid;
 
-use crate::common::{base58, time};
-use crate::models::{permission_user, permissions, role_user, roles, tokens, users, Id};
-use crate::responses::Unauthorized;
+use crate::common::{base58, env, time};
+use crate::dao;
+use crate::i18n::{self, t};
+use crate::middlewares::impersonation::Impersonator;
+use crate::middlewares::policy::Grant;
+use crate::models::{permissions, preferences, role_user, roles, tenants, tokens, users, Id};
+use crate::requests::user::AccountStatus;
+use crate::responses::{AppError, Unauthorized};
+use crate::tenant;
 
 const CACHE: u64 = 1000 * 60 * 5;
 
+/// Codes of the roles allowed to manage other users.
+pub const ADMINISTRATORS: [&str; 2] = ["SUPERUSER", "ADMIN"];
+
+/// Codes of the roles let past every check, `SUPERUSER_ROLES` separated by
+/// commas.
+pub fn superusers() -> Vec<String> {
+    env::var("SUPERUSER_ROLES", "SUPERUSER".to_string())
+        .split(',')
+        .map(|code| code.trim().to_uppercase())
+        .filter(|code| !code.is_empty())
+        .collect()
+}
+
 #[derive(Clone)]
 pub struct Auth {
+    /// Id of the token the request was authenticated with.
+    pub token: Id,
     pub user: users::Model,
+    /// The tenant the user belongs to, not always the one of the request.
+    pub tenant: tenants::Model,
+    /// Granted directly or through any of `roles`.
     pub permissions: Vec<permissions::Model>,
+    /// Assigned along with every role below them in the hierarchy.
     pub roles: Vec<roles::Model>,
+    /// What the user holds within single resources, see [`policy`](super::policy).
+    pub grants: Vec<Grant>,
+    /// The one really behind the request while they impersonate `user`.
+    pub impersonator: Option<users::Model>,
+    pub preferences: preferences::Model,
 }
 
 impl Auth {
@@ -28,15 +59,11 @@ impl Auth {
         db: Data<DatabaseConnection>,
         cache: Data<Authenticated>,
         header: Option<HeaderValue>,
-    ) -> Result<Self, Unauthorized> {
-        if header.is_none() {
-            return Err(Unauthorized {
-                message: "Token not found".to_string(),
-            });
-        }
-
-        let header = header.unwrap();
-        let header = header.to_str().unwrap();
+    ) -> Result<Self, AppError> {
+        let header = header.ok_or_else(|| AppError::Unauthorized(t!("auth.token_missing")))?;
+        let header = header
+            .to_str()
+            .map_err(|_| AppError::Unauthorized(t!("auth.token_invalid")))?;
         let id = Auth::parse(header)?;
 
         if let Some((_, auth)) = cache.clear().get(&id) {
@@ -46,78 +73,123 @@ impl Auth {
         let db: &DatabaseConnection = &db;
         let token = tokens::Entity::find()
             .find_also_related(users::Entity)
-            .filter(tokens::Column::Id.eq(id.clone()))
+            .filter(tokens::Column::Id.eq(id))
             .one(db)
-            .await;
+            .await?;
 
-        if let Err(e) = token {
-            return Err(Unauthorized {
-                message: e.to_string(),
-            });
+        let (token, user) = match token {
+            Some((token, Some(user))) => (token, user),
+            _ => return Err(AppError::Unauthorized(t!("auth.token_missing"))),
+        };
+
+        if let Some(message) = blocked(&user) {
+            return Err(AppError::Unauthorized(message));
         }
+        let mut expired = time::unix() + CACHE;
 
-        let token = token.unwrap();
+        if let Some(expired_at) = token.expired_at {
+            if expired_at <= time::now() {
+                return Err(AppError::Unauthorized(t!("auth.token_expired")));
+            }
 
-        if token.is_none() {
-            return Err(Unauthorized {
-                message: "Token not found".to_string(),
-            });
+            expired = expired.min(expired_at.timestamp_millis() as u64);
         }
 
-        let user = token.unwrap().1.unwrap();
-        let permissions = permissions::Entity::find()
-            .find_with_related(permission_user::Entity)
-            .filter(permission_user::Column::UserId.eq(user.id.clone()))
+        let tenant = tenants::Entity::find_by_id(user.tenant_id)
+            .one(db)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized(t!("auth.foreign_tenant")))?;
+
+        let impersonator = match token.impersonator_id {
+            Some(id) => users::Entity::find_by_id(id).one(db).await?,
+            None => None,
+        };
+
+        let now = time::now();
+        let assigned = role_user::Entity::find()
+            .filter(role_user::Column::UserId.eq(user.id))
+            .filter(dao::grant::in_effect::<role_user::Entity>(now))
             .all(db)
-            .await;
+            .await?
+            .into_iter()
+            .map(|role_user| role_user.role_id)
+            .collect();
 
-        if let Err(e) = permissions {
-            return Err(Unauthorized {
-                message: e.to_string(),
-            });
-        }
+        let roles = dao::role::effective(db, assigned).await?;
+        let permissions = dao::permission::effective(db, user.id, &roles).await?;
+        let grants = dao::role_scope::grants(db, user.id).await?;
 
-        let roles = roles::Entity::find()
-            .find_with_related(role_user::Entity)
-            .filter(role_user::Column::UserId.eq(user.id.clone()))
-            .all(db)
-            .await;
+        let preferences = dao::preference::find(db, user.id).await?;
 
-        if let Err(e) = roles {
-            return Err(Unauthorized {
-                message: e.to_string(),
-            });
+        // grants coming into or out of effect change what the user holds
+        if let Some(change) = dao::grant::next_change(db, user.id, now).await? {
+            expired = expired.min(change.timestamp_millis() as u64);
         }
 
-        let (permissions, roles) = (permissions.unwrap(), roles.unwrap());
-
         Ok(cache.set(
             id,
-            time::unix() + CACHE,
+            expired,
             Auth {
+                token: id,
                 user,
-                permissions: permissions
-                    .iter()
-                    .map(|(permission, _)| permission.clone())
-                    .collect(),
-                roles: roles.iter().map(|(role, _)| role.clone()).collect(),
+                tenant,
+                impersonator,
+                permissions,
+                roles,
+                grants,
+                preferences,
             },
         ))
     }
 
+    pub fn is_superuser(&self) -> bool {
+        let superusers = superusers();
+
+        self.roles
+            .iter()
+            .any(|role| superusers.contains(&role.code))
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.is_superuser()
+            || self
+                .roles
+                .iter()
+                .any(|role| ADMINISTRATORS.contains(&role.code.as_str()))
+    }
+
+    /// A superuser of the [`platform`](tenant::platform) tenant, who may act
+    /// within any other tenant.
+    pub fn is_platform_admin(&self) -> bool {
+        self.is_superuser() && self.tenant.code == tenant::platform()
+    }
+
+    /// Whether the user holds the permission `code`, always for superusers.
+    pub fn can(&self, code: &str) -> bool {
+        self.is_superuser()
+            || self
+                .permissions
+                .iter()
+                .any(|permission| permission.code == code)
+    }
+
+    pub fn is_impersonating(&self) -> bool {
+        self.impersonator.is_some()
+    }
+
     fn parse<T: ToString>(token: T) -> Result<Id, Unauthorized> {
         let token = token.to_string();
         let token = token.split(" ").collect::<Vec<&str>>();
 
         if token.len() != 2 {
             return Err(Unauthorized {
-                message: "Invalid token".to_string(),
+                message: t!("auth.token_invalid"),
             });
         }
 
         if token[0].trim().to_lowercase() != "bearer" {
             return Err(Unauthorized {
-                message: "Invalid token type".to_string(),
+                message: t!("auth.token_type"),
             });
         }
 
@@ -144,11 +216,29 @@ impl Auth {
                 message: e.to_string(),
             })?;
 
-            Ok(uuid.into())
+            Ok(uuid)
         }
     }
 }
 
+/// Why `user` may not sign in, `None` while their account is active.
+pub fn blocked(user: &users::Model) -> Option<String> {
+    let reason = user.status_reason.clone().unwrap_or_default();
+
+    match AccountStatus::of(user) {
+        AccountStatus::Active => None,
+        AccountStatus::Suspended => Some(match user.suspended_until {
+            Some(until) => t!(
+                "auth.suspended",
+                until = time::localize(&until).to_rfc3339(),
+                reason = reason
+            ),
+            None => t!("auth.suspended_indefinitely", reason = reason),
+        }),
+        AccountStatus::Deactivated => Some(t!("auth.deactivated", reason = reason)),
+    }
+}
+
 pub struct Authenticated(pub Mutex<HashMap<Id, (u64, Auth)>>);
 
 impl Authenticated {
@@ -174,6 +264,21 @@ impl Authenticated {
         self.0.lock().unwrap().remove(id);
     }
 
+    /// Drop every cached session, for changes reaching past a single user
+    /// such as the role hierarchy.
+    pub fn flush(&self) {
+        self.0.lock().unwrap().clear();
+    }
+
+    /// Drop the cached sessions of `user_id` but the one of `except`, so
+    /// revoked tokens stop working right away.
+    pub fn forget(&self, user_id: &Id, except: Option<&Id>) {
+        self.0
+            .lock()
+            .unwrap()
+            .retain(|id, (_, auth)| &auth.user.id != user_id || Some(id) == except);
+    }
+
     pub fn clear(&self) -> &Self {
         for (id, (expired, _)) in self.all() {
             if time::unix() > expired {
@@ -186,14 +291,238 @@ impl Authenticated {
 }
 
 impl FromRequest for Auth {
-    type Error = Unauthorized;
-    type Future = Pin<Box<dyn Future<Output = Result<Auth, Unauthorized>>>>;
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Auth, AppError>>>>;
 
     fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
         let db = req.app_data::<Data<DatabaseConnection>>().cloned().unwrap();
         let cache = req.app_data::<Data<Authenticated>>().cloned().unwrap();
         let authorization = req.headers().get("Authorization").cloned();
+        let req = req.clone();
+
+        Box::pin(async move {
+            let auth = Auth::authenticate(db, cache, authorization).await?;
+            let foreign = req
+                .extensions()
+                .get::<tenants::Model>()
+                .is_some_and(|tenant| tenant.id != auth.user.tenant_id);
+
+            if foreign && !auth.is_platform_admin() {
+                return Err(AppError::Unauthorized(t!("auth.foreign_tenant")));
+            }
+
+            if let Some(impersonator) = &auth.impersonator {
+                req.extensions_mut()
+                    .insert(Impersonator(impersonator.username.clone()));
+            }
+
+            // the user's own choice wins over the negotiated locale
+            if let Some(locale) = auth.user.locale.as_deref().and_then(|l| l.parse().ok()) {
+                i18n::set(locale);
+            }
+
+            if let Ok(timezone) = auth.preferences.timezone.parse() {
+                time::set_timezone(timezone);
+            }
+
+            Ok(auth)
+        })
+    }
+}
+
+/// An authenticated user holding one of the [`ADMINISTRATORS`] roles.
+pub struct Admin(pub Auth);
+
+impl Deref for Admin {
+    type Target = Auth;
+
+    fn deref(&self) -> &Auth {
+        &self.0
+    }
+}
+
+impl FromRequest for Admin {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Admin, AppError>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let auth = Auth::from_request(req, payload);
+
+        Box::pin(async move {
+            let auth = auth.await?;
+
+            if !auth.is_admin() {
+                return Err(AppError::Forbidden(t!("auth.forbidden")));
+            }
+
+            Ok(Admin(auth))
+        })
+    }
+}
+
+/// A superuser of the platform tenant, managing the deployment as a
+/// whole rather than a single tenant.
+pub struct PlatformAdmin(pub Auth);
+
+impl Deref for PlatformAdmin {
+    type Target = Auth;
+
+    fn deref(&self) -> &Auth {
+        &self.0
+    }
+}
+
+impl FromRequest for PlatformAdmin {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<PlatformAdmin, AppError>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let auth = Auth::from_request(req, payload);
+
+        Box::pin(async move {
+            let auth = auth.await?;
+
+            if !auth.is_platform_admin() {
+                return Err(AppError::Forbidden(t!("auth.forbidden")));
+            }
+
+            Ok(PlatformAdmin(auth))
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    use crate::common::time;
+    use crate::dao;
+    use crate::models::{permissions, roles, tenants, users, Id};
+
+    use super::{blocked, Auth, Authenticated};
+
+    pub fn auth(token: Id, user: Id, roles: &[&str]) -> Auth {
+        let tenant = tenants::Model {
+            id: Uuid::new_v4(),
+            code: "default".to_string(),
+            name: "default".to_string(),
+            created_at: time::now(),
+            updated_at: time::now(),
+        };
+
+        Auth {
+            token,
+            user: users::Model {
+                id: user,
+                tenant_id: tenant.id,
+                name: "john".to_string(),
+                email: "john@local.id".to_string(),
+                email_verified_at: None,
+                username: "john".to_string(),
+                password: String::new(),
+                profile_photo_id: None,
+                locale: None,
+                registered_at: None,
+                status: "active".to_string(),
+                status_reason: None,
+                suspended_until: None,
+                created_at: time::now(),
+                updated_at: time::now(),
+                deleted_at: None,
+            },
+            permissions: vec![],
+            grants: vec![],
+            impersonator: None,
+            preferences: dao::preference::default(user),
+            roles: roles
+                .iter()
+                .map(|code| roles::Model {
+                    id: Uuid::new_v4(),
+                    tenant_id: tenant.id,
+                    code: code.to_string(),
+                    name: code.to_lowercase(),
+                    system: false,
+                })
+                .collect(),
+            tenant,
+        }
+    }
+
+    #[test]
+    pub async fn forget_must_keep_the_excepted_session() {
+        let cache = Authenticated::new();
+        let (john, jane): (Id, Id) = (Uuid::new_v4(), Uuid::new_v4());
+        let tokens: Vec<Id> = (0..3).map(|_| Uuid::new_v4()).collect();
+
+        cache.set(tokens[0], u64::MAX, auth(tokens[0], john, &[]));
+        cache.set(tokens[1], u64::MAX, auth(tokens[1], john, &[]));
+        cache.set(tokens[2], u64::MAX, auth(tokens[2], jane, &[]));
+        cache.forget(&john, Some(&tokens[0]));
+
+        assert!(cache.get(&tokens[0]).is_some());
+        assert!(cache.get(&tokens[1]).is_none());
+        assert!(cache.get(&tokens[2]).is_some());
+    }
+
+    #[test]
+    pub async fn admin_must_hold_an_administrator_role() {
+        let id: Id = Uuid::new_v4();
+
+        assert!(auth(id, id, &["ADMIN"]).is_admin());
+        assert!(auth(id, id, &["SUPERUSER", "STUDENT"]).is_admin());
+        assert!(!auth(id, id, &["STUDENT"]).is_admin());
+    }
+
+    #[test]
+    pub async fn superuser_must_be_granted_everything() {
+        let id: Id = Uuid::new_v4();
+        let mut student = auth(id, id, &["STUDENT"]);
+
+        assert!(!student.can("DELETE_USER"));
+
+        student.permissions.push(permissions::Model {
+            id: Uuid::new_v4(),
+            code: "DELETE_USER".to_string(),
+            name: "delete user".to_string(),
+            group: Some("user".to_string()),
+            description: None,
+            orphaned_at: None,
+        });
+
+        assert!(student.can("DELETE_USER"));
+        assert!(auth(id, id, &["SUPERUSER"]).can("DELETE_USER"));
+    }
+
+    #[test]
+    pub async fn platform_admin_must_be_a_superuser_of_the_platform_tenant() {
+        let id: Id = Uuid::new_v4();
+        let mut superuser = auth(id, id, &["SUPERUSER"]);
+
+        assert!(superuser.is_platform_admin());
+        assert!(!auth(id, id, &["ADMIN"]).is_platform_admin());
+
+        superuser.tenant.code = "north-high".to_string();
+        assert!(!superuser.is_platform_admin());
+    }
+
+    #[test]
+    pub async fn blocked_must_let_an_expired_suspension_through() {
+        let id: Id = Uuid::new_v4();
+        let mut user = auth(id, id, &[]).user;
+
+        assert!(blocked(&user).is_none());
+
+        user.status = "suspended".to_string();
+        assert!(blocked(&user).is_some());
+
+        user.suspended_until = Some(time::now() + Duration::hours(1));
+        assert!(blocked(&user).is_some());
+
+        user.suspended_until = Some(time::now() - Duration::hours(1));
+        assert!(blocked(&user).is_none());
 
-        Box::pin(Auth::authenticate(db, cache, authorization))
+        user.status = "deactivated".to_string();
+        assert!(blocked(&user).is_some());
     }
 }
@@ -0,0 +1,70 @@
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::{Error, HttpMessage};
+
+/// Username of whoever impersonates the authenticated user, left in the
+/// request extensions by [`Auth`](super::auth::Auth).
+#[derive(Clone)]
+pub struct Impersonator(pub String);
+
+/// Flags every response given to an impersonated request with an
+/// `X-Impersonated-By` header, so clients can show it.
+pub struct Impersonation;
+
+impl<S, B> Transform<S, ServiceRequest> for Impersonation
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ImpersonationMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ImpersonationMiddleware { service }))
+    }
+}
+
+pub struct ImpersonationMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for ImpersonationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let response = self.service.call(req);
+
+        Box::pin(async move {
+            let mut response = response.await?;
+            let impersonator = response
+                .request()
+                .extensions()
+                .get::<Impersonator>()
+                .cloned();
+
+            if let Some(Impersonator(username)) = impersonator {
+                if let Ok(value) = HeaderValue::from_str(&username) {
+                    response
+                        .headers_mut()
+                        .insert(HeaderName::from_static("x-impersonated-by"), value);
+                }
+            }
+
+            Ok(response)
+        })
+    }
+}
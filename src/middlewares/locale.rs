@@ -0,0 +1,74 @@
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::Error;
+use chrono_tz::Tz;
+
+use crate::common::time;
+use crate::i18n::{self, Locale};
+
+/// Handles every request in the locale negotiated from its
+/// `Accept-Language` header, and in UTC. The authenticated user's own
+/// locale, when set, and timezone take over once
+/// [`Auth`](super::auth::Auth) is extracted.
+pub struct Localize;
+
+impl<S, B> Transform<S, ServiceRequest> for Localize
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = LocalizeMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(LocalizeMiddleware { service }))
+    }
+}
+
+pub struct LocalizeMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for LocalizeMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let locale = req
+            .headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Locale::negotiate)
+            .unwrap_or_default();
+        let response = i18n::sync_scope(locale, || {
+            time::sync_scope(Tz::UTC, || self.service.call(req))
+        });
+
+        Box::pin(i18n::scope(
+            locale,
+            time::scope(Tz::UTC, async move {
+                let mut response = response.await?;
+
+                response.headers_mut().insert(
+                    header::CONTENT_LANGUAGE,
+                    HeaderValue::from_static(i18n::current().as_str()),
+                );
+
+                Ok(response)
+            }),
+        ))
+    }
+}
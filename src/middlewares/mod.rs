@@ -1 +1,6 @@
+pub mod audit;
 pub mod auth;
+pub mod impersonation;
+pub mod locale;
+pub mod policy;
+pub mod tenant;
@@ -0,0 +1,181 @@
+use crate::i18n::t;
+use crate::middlewares::auth::Auth;
+use crate::models::{files, users, Id};
+use crate::responses::AppError;
+
+/// A single resource permissions can be granted within, such as a course.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scope {
+    pub kind: String,
+    pub id: Id,
+}
+
+impl Scope {
+    pub fn new<K: ToString>(kind: K, id: Id) -> Self {
+        Self {
+            kind: kind.to_string(),
+            id,
+        }
+    }
+
+    pub fn of<R: Resource>(resource: &R) -> Self {
+        Self::new(R::SCOPE, resource.id())
+    }
+}
+
+/// Permission codes a user holds within `scope` only, through the roles
+/// assigned to them there and every role below those.
+#[derive(Clone, Debug)]
+pub struct Grant {
+    pub scope: Scope,
+    pub permissions: Vec<String>,
+}
+
+/// Something an action is checked against once it is loaded.
+pub trait Resource {
+    /// Type of the scope the resource opens, as roles are assigned within.
+    const SCOPE: &'static str;
+
+    fn id(&self) -> Id;
+
+    /// Who may do anything to the resource without being granted a thing.
+    fn owner(&self) -> Option<Id> {
+        None
+    }
+
+    /// Scopes the resource sits within, whose grants reach into it, such as
+    /// the course of a section.
+    fn within(&self) -> Vec<Scope> {
+        vec![]
+    }
+}
+
+/// Whether `auth` may perform `action` on `resource`, as a superuser,
+/// through a global grant, as its owner or through a role assigned within
+/// the resource or any scope enclosing it.
+pub fn can<R: Resource>(auth: &Auth, action: &str, resource: &R) -> bool {
+    if auth.can(action) {
+        return true;
+    }
+
+    if resource.owner().is_some_and(|owner| owner == auth.user.id) {
+        return true;
+    }
+
+    let mut scopes = resource.within();
+    scopes.push(Scope::of(resource));
+
+    auth.grants.iter().any(|grant| {
+        scopes.contains(&grant.scope) && grant.permissions.iter().any(|code| code == action)
+    })
+}
+
+impl Auth {
+    /// Fail as forbidden unless [`can`] lets `action` through on `resource`,
+    /// for handlers to call on what they loaded.
+    pub fn authorize<R: Resource>(&self, action: &str, resource: &R) -> Result<(), AppError> {
+        match can(self, action, resource) {
+            true => Ok(()),
+            false => Err(AppError::Forbidden(t!("auth.forbidden"))),
+        }
+    }
+}
+
+impl Resource for users::Model {
+    const SCOPE: &'static str = "user";
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn owner(&self) -> Option<Id> {
+        Some(self.id)
+    }
+}
+
+impl Resource for files::Model {
+    const SCOPE: &'static str = "file";
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn owner(&self) -> Option<Id> {
+        self.uploaded_by
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use uuid::Uuid;
+
+    use crate::middlewares::auth::test::auth;
+    use crate::models::Id;
+
+    use super::{can, Grant, Resource, Scope};
+
+    struct Section {
+        id: Id,
+        course: Id,
+        author: Id,
+    }
+
+    impl Resource for Section {
+        const SCOPE: &'static str = "section";
+
+        fn id(&self) -> Id {
+            self.id
+        }
+
+        fn owner(&self) -> Option<Id> {
+            Some(self.author)
+        }
+
+        fn within(&self) -> Vec<Scope> {
+            vec![Scope::new("course", self.course)]
+        }
+    }
+
+    fn sample() -> Section {
+        Section {
+            id: Uuid::new_v4(),
+            course: Uuid::new_v4(),
+            author: Uuid::new_v4(),
+        }
+    }
+
+    #[test]
+    pub async fn can_must_let_owners_and_superusers_through() {
+        let section = sample();
+        let id: Id = Uuid::new_v4();
+
+        assert!(!can(&auth(id, id, &[]), "GRADE", &section));
+        assert!(can(&auth(id, id, &["SUPERUSER"]), "GRADE", &section));
+        assert!(can(
+            &auth(section.author, section.author, &[]),
+            "GRADE",
+            &section
+        ));
+    }
+
+    #[test]
+    pub async fn can_must_honour_grants_of_enclosing_scopes_only() {
+        let section = sample();
+        let id: Id = Uuid::new_v4();
+        let mut teacher = auth(id, id, &[]);
+
+        teacher.grants.push(Grant {
+            scope: Scope::new("course", section.course),
+            permissions: vec!["GRADE".to_string()],
+        });
+
+        assert!(can(&teacher, "GRADE", &section));
+        assert!(!can(&teacher, "DELETE", &section));
+        assert!(!can(&teacher, "GRADE", &sample()));
+
+        // the same id within another type of scope grants nothing
+        teacher.grants[0].scope.kind = "program".to_string();
+
+        assert!(!can(&teacher, "GRADE", &section));
+    }
+}
@@ -0,0 +1,82 @@
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::web::Data;
+use actix_web::{Error, HttpMessage};
+use sea_orm::DatabaseConnection;
+
+use crate::common::env;
+use crate::dao;
+use crate::i18n::t;
+use crate::responses::AppError;
+use crate::tenant;
+
+/// Handles every request within the tenant it names, see
+/// [`tenant::code`], falling back to `TENANT_DEFAULT`. Subdomains are
+/// looked for under `TENANT_DOMAIN`, and the tenant is left in the request
+/// extensions.
+pub struct Tenancy;
+
+impl<S, B> Transform<S, ServiceRequest> for Tenancy
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = TenancyMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TenancyMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct TenancyMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for TenancyMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let db = req.app_data::<Data<DatabaseConnection>>().cloned();
+        let code = tenant::code(
+            req.headers()
+                .get(tenant::HEADER)
+                .and_then(|value| value.to_str().ok()),
+            req.connection_info().host(),
+            &env::var("TENANT_DOMAIN", String::new()),
+        )
+        .unwrap_or_else(tenant::fallback);
+
+        Box::pin(async move {
+            let db =
+                db.ok_or_else(|| AppError::Internal("no database to resolve tenants".into()))?;
+            let found = dao::tenant::find_by_code(&db, &code)
+                .await
+                .map_err(AppError::from)?
+                .ok_or_else(|| AppError::NotFound(t!("tenant.not_found")))?;
+
+            req.extensions_mut().insert(found.clone());
+
+            let response = tenant::sync_scope(found.id, || service.call(req));
+
+            tenant::scope(found.id, response).await
+        })
+    }
+}
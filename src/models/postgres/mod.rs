@@ -1,11 +1,23 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.2
 
+// only the library hands it out, the binary declares the models privately
+#[allow(unused_imports)]
 pub mod prelude;
 
+pub mod audit_logs;
+pub mod files;
+pub mod impersonations;
+pub mod invitations;
+pub mod jobs;
 pub mod permission_role;
 pub mod permission_user;
 pub mod permissions;
+pub mod preferences;
+pub mod role_parent;
+pub mod role_scopes;
 pub mod role_user;
 pub mod roles;
+pub mod scheduled_tasks;
+pub mod tenants;
 pub mod tokens;
 pub mod users;
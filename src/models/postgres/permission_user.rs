@@ -9,6 +9,8 @@ pub struct Model {
     pub id: Uuid,
     pub permission_id: Uuid,
     pub user_id: Uuid,
+    pub starts_at: Option<DateTime>,
+    pub expires_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -10,6 +10,10 @@ pub struct Model {
     #[sea_orm(unique)]
     pub code: String,
     pub name: String,
+    pub group: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub description: Option<String>,
+    pub orphaned_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
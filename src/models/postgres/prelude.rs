@@ -1,9 +1,19 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.2
 
+pub use super::audit_logs::Entity as AuditLogs;
+pub use super::files::Entity as Files;
+pub use super::impersonations::Entity as Impersonations;
+pub use super::invitations::Entity as Invitations;
+pub use super::jobs::Entity as Jobs;
 pub use super::permission_role::Entity as PermissionRole;
 pub use super::permission_user::Entity as PermissionUser;
 pub use super::permissions::Entity as Permissions;
+pub use super::preferences::Entity as Preferences;
+pub use super::role_parent::Entity as RoleParent;
+pub use super::role_scopes::Entity as RoleScopes;
 pub use super::role_user::Entity as RoleUser;
 pub use super::roles::Entity as Roles;
+pub use super::scheduled_tasks::Entity as ScheduledTasks;
+pub use super::tenants::Entity as Tenants;
 pub use super::tokens::Entity as Tokens;
 pub use super::users::Entity as Users;
@@ -7,9 +7,10 @@ use sea_orm::entity::prelude::*;
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: Uuid,
-    #[sea_orm(unique)]
+    pub tenant_id: Uuid,
     pub code: String,
     pub name: String,
+    pub system: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -18,6 +19,14 @@ pub enum Relation {
     PermissionRole,
     #[sea_orm(has_many = "super::role_user::Entity")]
     RoleUser,
+    #[sea_orm(
+        belongs_to = "super::tenants::Entity",
+        from = "Column::TenantId",
+        to = "super::tenants::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Tenants,
 }
 
 impl Related<super::permission_role::Entity> for Entity {
@@ -32,4 +41,10 @@ impl Related<super::role_user::Entity> for Entity {
     }
 }
 
+impl Related<super::tenants::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenants.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
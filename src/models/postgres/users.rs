@@ -7,14 +7,19 @@ use sea_orm::entity::prelude::*;
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: Uuid,
+    pub tenant_id: Uuid,
     pub name: String,
-    #[sea_orm(unique)]
     pub email: String,
     pub email_verified_at: Option<DateTime>,
-    #[sea_orm(unique)]
     pub username: String,
     pub password: String,
     pub profile_photo_id: Option<String>,
+    pub locale: Option<String>,
+    pub registered_at: Option<DateTime>,
+    pub status: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub status_reason: Option<String>,
+    pub suspended_until: Option<DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
     pub deleted_at: Option<DateTime>,
@@ -24,8 +29,18 @@ pub struct Model {
 pub enum Relation {
     #[sea_orm(has_many = "super::permission_user::Entity")]
     PermissionUser,
+    #[sea_orm(has_one = "super::preferences::Entity")]
+    Preferences,
     #[sea_orm(has_many = "super::role_user::Entity")]
     RoleUser,
+    #[sea_orm(
+        belongs_to = "super::tenants::Entity",
+        from = "Column::TenantId",
+        to = "super::tenants::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Tenants,
     #[sea_orm(has_many = "super::tokens::Entity")]
     Tokens,
 }
@@ -36,12 +51,24 @@ impl Related<super::permission_user::Entity> for Entity {
     }
 }
 
+impl Related<super::preferences::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Preferences.def()
+    }
+}
+
 impl Related<super::role_user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::RoleUser.def()
     }
 }
 
+impl Related<super::tenants::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenants.def()
+    }
+}
+
 impl Related<super::tokens::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Tokens.def()
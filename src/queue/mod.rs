@@ -0,0 +1,141 @@
+pub mod worker;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use sea_orm::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::dao;
+use crate::mail;
+use crate::models::{jobs, Timestamp};
+
+pub use worker::Worker;
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+type Handler = Box<
+    dyn Fn(Json, DatabaseConnection) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>
+        + Send
+        + Sync,
+>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Pending,
+    Running,
+    Completed,
+    Dead,
+}
+
+impl Status {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Dead => "dead",
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(Self::Pending),
+            "running" => Ok(Self::Running),
+            "completed" => Ok(Self::Completed),
+            "dead" => Ok(Self::Dead),
+            _ => Err(format!("invalid job status {}", s)),
+        }
+    }
+}
+
+/// A unit of work that can be deferred to the background worker.
+///
+/// The payload is the job itself, stored as json in the `jobs` table,
+/// so anything the handler needs must be serializable.
+pub trait Job: Serialize + DeserializeOwned + Send + 'static {
+    const NAME: &'static str;
+    const QUEUE: &'static str = "default";
+    const MAX_ATTEMPTS: i32 = 5;
+
+    fn handle(self, db: DatabaseConnection) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+#[derive(Default)]
+pub struct Registry(HashMap<&'static str, Handler>);
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<J: Job>(mut self) -> Self {
+        self.0.insert(
+            J::NAME,
+            Box::new(|payload, db| {
+                Box::pin(async move {
+                    let job: J = serde_json::from_value(payload)?;
+
+                    job.handle(db).await
+                })
+            }),
+        );
+
+        self
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.0.keys().copied().collect()
+    }
+
+    pub async fn handle(&self, job: &jobs::Model, db: DatabaseConnection) -> Result<(), Error> {
+        match self.0.get(job.name.as_str()) {
+            None => Err(format!("no handler registered for job {}", job.name).into()),
+            Some(handler) => handler(job.payload.clone(), db).await,
+        }
+    }
+}
+
+/// Every job this service knows how to run.
+pub fn registry() -> Arc<Registry> {
+    Arc::new(Registry::new().register::<mail::SendMail>())
+}
+
+pub async fn dispatch<J: Job>(db: &DatabaseConnection, job: J) -> Result<jobs::Model, DbErr> {
+    dispatch_at(db, job, crate::common::time::now()).await
+}
+
+pub async fn dispatch_at<J: Job>(
+    db: &DatabaseConnection,
+    job: J,
+    available_at: Timestamp,
+) -> Result<jobs::Model, DbErr> {
+    let payload = serde_json::to_value(job).map_err(|e| DbErr::Json(e.to_string()))?;
+
+    dao::job::store(
+        db,
+        J::QUEUE,
+        J::NAME,
+        payload,
+        J::MAX_ATTEMPTS,
+        available_at,
+    )
+    .await
+}
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use chrono::Duration;
+use sea_orm::{DatabaseConnection, DbErr};
+
+use crate::common::{log, time};
+use crate::dao;
+
+use super::Registry;
+
+const INTERVAL: u64 = 1000;
+const VISIBILITY: i64 = 60 * 5;
+const BACKOFF: i64 = 10;
+const BACKOFF_MAX: i64 = 60 * 60;
+
+pub struct Worker {
+    db: DatabaseConnection,
+    registry: Arc<Registry>,
+    queue: String,
+}
+
+impl Worker {
+    pub fn new(db: DatabaseConnection, registry: Arc<Registry>) -> Self {
+        Self {
+            db,
+            registry,
+            queue: "default".to_string(),
+        }
+    }
+
+    pub async fn run(self) {
+        log::info!(
+            Worker,
+            "listening on queue {} for {:?}",
+            self.queue,
+            self.registry.names()
+        );
+
+        loop {
+            match self.work().await {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) => log::error!(Worker, "{}", e),
+            }
+
+            actix_web::rt::time::sleep(std::time::Duration::from_millis(INTERVAL)).await;
+        }
+    }
+
+    /// Run a single job, returns whether there was one to run.
+    pub async fn work(&self) -> Result<bool, DbErr> {
+        let visibility = Duration::seconds(VISIBILITY);
+        let job = match dao::job::reserve(&self.db, &self.queue, visibility).await? {
+            None => return Ok(false),
+            Some(job) => job,
+        };
+
+        match self.registry.handle(&job, self.db.clone()).await {
+            Ok(_) => {
+                dao::job::complete(&self.db, job).await?;
+            }
+            Err(e) => {
+                log::error!(Worker, "job {} ({}) failed: {}", job.id, job.name, e);
+
+                let retry_at = if job.attempts < job.max_attempts {
+                    Some(time::now() + backoff(job.attempts))
+                } else {
+                    None
+                };
+
+                dao::job::fail(&self.db, job, e, retry_at).await?;
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+/// Exponential delay before the next attempt, capped at one hour.
+pub fn backoff(attempts: i32) -> Duration {
+    let exponent = attempts.clamp(1, 16) as u32 - 1;
+
+    Duration::seconds((BACKOFF * 2i64.pow(exponent)).min(BACKOFF_MAX))
+}
+
+#[cfg(test)]
+pub mod test {
+    #[test]
+    pub async fn backoff_must_grow_exponentially() {
+        use super::backoff;
+        use chrono::Duration;
+
+        assert_eq!(backoff(1), Duration::seconds(10));
+        assert_eq!(backoff(2), Duration::seconds(20));
+        assert_eq!(backoff(4), Duration::seconds(80));
+    }
+
+    #[test]
+    pub async fn backoff_must_be_capped() {
+        use super::backoff;
+        use chrono::Duration;
+
+        assert_eq!(backoff(12), Duration::hours(1));
+        assert_eq!(backoff(i32::MAX), Duration::hours(1));
+    }
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::models::{audit_logs, Id, Timestamp};
+
+sortable!(audit_logs::Column as "AuditLog" {
+    "action" => Action,
+    "entityType" => EntityType,
+    "createdAt" => CreatedAt,
+});
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    /// Somebody started acting as the user.
+    #[serde(rename = "impersonation.started")]
+    ImpersonationStarted,
+    #[serde(rename = "impersonation.stopped")]
+    ImpersonationStopped,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+            Self::ImpersonationStarted => "impersonation.started",
+            Self::ImpersonationStopped => "impersonation.stopped",
+        }
+    }
+}
+
+/// Kinds of entity the audit log keeps a history of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditEntity {
+    User,
+    Role,
+    Permission,
+    Tenant,
+}
+
+impl AuditEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Role => "role",
+            Self::Permission => "permission",
+            Self::Tenant => "tenant",
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, ToSchema, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct AuditFilterRequest {
+    /// Changes made by this user, impersonated or not.
+    #[schema()]
+    pub actor_id: Option<Id>,
+    #[schema(example = "update")]
+    pub action: Option<AuditAction>,
+    #[schema(example = "user")]
+    pub entity_type: Option<AuditEntity>,
+    #[schema()]
+    pub entity_id: Option<Id>,
+    /// Changes made at or after this time.
+    #[schema()]
+    pub from: Option<Timestamp>,
+    /// Changes made before this time.
+    #[schema()]
+    pub to: Option<Timestamp>,
+}
@@ -1,10 +1,101 @@
 use serde::Deserialize;
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
 
-#[derive(Clone, Deserialize, ToSchema)]
+use crate::models::users;
+use crate::validation::{self, Rule, Rules};
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
 pub struct Login {
-    #[schema(example = "john")]
+    #[validate(custom = "validation::required")]
+    #[schema(example = "john", min_length = 1)]
     pub email_or_username: String,
-    #[schema(example = "Password123")]
+    #[validate(custom = "validation::required")]
+    #[schema(example = "Password123", format = Password, min_length = 1)]
     pub password: String,
 }
+
+impl Rules for Login {}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct Register {
+    #[validate(custom = "validation::required")]
+    #[schema(example = "John Doe", min_length = 1)]
+    pub name: String,
+    /// Must not belong to another user, and be of an allowed domain when
+    /// registration is restricted to some.
+    #[validate(custom = "validation::required", custom = "validation::email")]
+    #[schema(example = "john@local.id", format = "email")]
+    pub email: String,
+    /// Must not belong to another user.
+    #[validate(custom = "validation::required")]
+    #[schema(example = "john", min_length = 1)]
+    pub username: String,
+    /// At least 6 characters with an uppercase letter, a digit and a special character.
+    #[validate(
+        custom = "validation::required",
+        custom = "validation::password_length",
+        custom = "validation::password_uppercase",
+        custom = "validation::password_digit",
+        custom = "validation::password_special"
+    )]
+    #[schema(example = "Password123!", format = Password, min_length = 6)]
+    pub password: String,
+    #[validate(
+        custom = "validation::required",
+        must_match(other = "password", message = "validation.confirmed")
+    )]
+    #[schema(example = "Password123!", format = Password)]
+    pub password_confirmation: String,
+    /// Response token of the CAPTCHA widget, required when one is configured.
+    #[schema()]
+    pub captcha: Option<String>,
+}
+
+impl Rules for Register {
+    fn rules(&self, _: &()) -> Vec<Rule> {
+        vec![
+            Rule::unique(
+                "email",
+                users::Column::Email,
+                self.email.trim().to_lowercase(),
+            ),
+            Rule::unique(
+                "username",
+                users::Column::Username,
+                self.username.trim().to_lowercase(),
+            ),
+        ]
+    }
+}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+pub struct ResendVerification {
+    #[validate(custom = "validation::required", custom = "validation::email")]
+    #[schema(example = "john@local.id", format = "email")]
+    pub email: String,
+}
+
+impl Rules for ResendVerification {}
+
+/// The signature of a link choosing the password of an account, as found in
+/// the mailed url.
+#[derive(Clone, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PasswordLinkRequest {
+    #[param(example = 1700000000)]
+    pub expires: u64,
+    #[param()]
+    pub signature: String,
+}
+
+/// The signature of an email verification link, as found in the mailed url.
+#[derive(Clone, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct VerifyEmailRequest {
+    #[param(example = 1700000000)]
+    pub expires: u64,
+    #[param()]
+    pub signature: String,
+}
@@ -0,0 +1,24 @@
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::storage::Variant;
+
+/// The multipart body of an upload, read field by field by the service.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct FileUploadRequest {
+    /// A png, jpeg, gif or webp image.
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+}
+
+#[derive(Clone, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FileDownloadRequest {
+    #[param(inline)]
+    pub variant: Option<Variant>,
+    #[param(example = 1700000000)]
+    pub expires: u64,
+    #[param()]
+    pub signature: String,
+}
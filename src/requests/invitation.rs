@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::common::time;
+use crate::models::{invitations, permissions, roles, users, Id};
+use crate::validation::{self, Rule, Rules};
+
+sortable!(invitations::Column as "Invitation" {
+    "email" => Email,
+    "name" => Name,
+    "sentAt" => SentAt,
+    "expiredAt" => ExpiredAt,
+    "createdAt" => CreatedAt,
+});
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum InvitationStatus {
+    Pending,
+    Accepted,
+    Revoked,
+    /// Pending past its expiry, never stored but derived from `expired_at`.
+    Expired,
+}
+
+impl InvitationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Accepted => "accepted",
+            Self::Revoked => "revoked",
+            Self::Expired => "expired",
+        }
+    }
+
+    pub fn of(invitation: &invitations::Model) -> Self {
+        match invitation.status.as_str() {
+            "accepted" => Self::Accepted,
+            "revoked" => Self::Revoked,
+            _ if invitation.expired_at <= time::now() => Self::Expired,
+            _ => Self::Pending,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, ToSchema, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct InvitationFilterRequest {
+    #[schema(example = "pending")]
+    pub status: Option<InvitationStatus>,
+}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+pub struct InvitationStoreRequest {
+    #[validate(custom = "validation::required")]
+    #[schema(example = "John Doe", min_length = 1)]
+    pub name: String,
+    /// Must not belong to a user yet.
+    #[validate(custom = "validation::required", custom = "validation::email")]
+    #[schema(example = "john@local.id", format = "email")]
+    pub email: String,
+    /// Ids of existing permissions granted on acceptance.
+    #[schema()]
+    pub permissions: Vec<Id>,
+    /// Ids of existing roles granted on acceptance.
+    #[schema()]
+    pub roles: Vec<Id>,
+}
+
+impl Rules for InvitationStoreRequest {
+    fn rules(&self, _: &()) -> Vec<Rule> {
+        vec![
+            Rule::unique(
+                "email",
+                users::Column::Email,
+                self.email.trim().to_lowercase(),
+            ),
+            Rule::exists(
+                "permissions",
+                permissions::Column::Id,
+                self.permissions.clone(),
+            ),
+            Rule::exists("roles", roles::Column::Id, self.roles.clone()),
+        ]
+    }
+}
+
+/// The signature of an invite link, as found in the mailed url.
+#[derive(Clone, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct InvitationLinkRequest {
+    #[param(example = 1700000000)]
+    pub expires: u64,
+    #[param()]
+    pub signature: String,
+}
+
+/// What the invitee chooses, the rest of the account comes from the invitation.
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct InvitationAcceptRequest {
+    /// Must not belong to another user.
+    #[validate(custom = "validation::required")]
+    #[schema(example = "john", min_length = 1)]
+    pub username: String,
+    /// At least 6 characters with an uppercase letter, a digit and a special character.
+    #[validate(
+        custom = "validation::required",
+        custom = "validation::password_length",
+        custom = "validation::password_uppercase",
+        custom = "validation::password_digit",
+        custom = "validation::password_special"
+    )]
+    #[schema(example = "Password123!", format = Password, min_length = 6)]
+    pub password: String,
+    #[validate(
+        custom = "validation::required",
+        must_match(other = "password", message = "validation.confirmed")
+    )]
+    #[schema(example = "Password123!", format = Password)]
+    pub password_confirmation: String,
+}
+
+/// The invited email may have been taken since the invitation was sent.
+impl Rules<invitations::Model> for InvitationAcceptRequest {
+    fn rules(&self, invitation: &invitations::Model) -> Vec<Rule> {
+        vec![
+            Rule::unique("email", users::Column::Email, invitation.email.clone()),
+            Rule::unique(
+                "username",
+                users::Column::Username,
+                self.username.trim().to_lowercase(),
+            ),
+        ]
+    }
+}
@@ -0,0 +1,24 @@
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::models::jobs;
+use crate::queue::Status;
+
+sortable!(jobs::Column as "Job" {
+    "name" => Name,
+    "queue" => Queue,
+    "attempts" => Attempts,
+    "availableAt" => AvailableAt,
+    "createdAt" => CreatedAt,
+    "updatedAt" => UpdatedAt,
+});
+
+#[derive(Clone, Deserialize, ToSchema, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct JobFilterRequest {
+    #[schema(example = "default")]
+    pub queue: Option<String>,
+    #[schema(example = "dead")]
+    pub status: Option<Status>,
+}
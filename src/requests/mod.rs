@@ -1,29 +1,150 @@
+/// Document `$column` as the `{$resource}Column` enum of the names it can be
+/// ordered by, and only accept those names for it.
+macro_rules! sortable {
+    ($column:ty as $resource:literal { $($name:literal => $variant:ident),+ $(,)? }) => {
+        impl $crate::requests::Sortable for $column {
+            const PAGINATION: &'static str = concat!($resource, "PaginationRequest");
+        }
+
+        impl utoipa::ToSchema<'_> for $column {
+            fn schema() -> (
+                &'static str,
+                utoipa::openapi::RefOr<utoipa::openapi::schema::Schema>,
+            ) {
+                let schema = utoipa::openapi::ObjectBuilder::new()
+                    .schema_type(utoipa::openapi::schema::SchemaType::String)
+                    .enum_values(Some([$($name),+]))
+                    .build();
+
+                (concat!($resource, "Column"), schema.into())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $column {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+
+                match s.as_str() {
+                    $($name => Ok(<$column>::$variant),)+
+                    _ => Err(serde::de::Error::unknown_variant(&s, &[$($name),+])),
+                }
+            }
+        }
+    };
+}
+
+pub mod audit;
 pub mod auth;
+pub mod file;
+pub mod invitation;
+pub mod job;
 pub mod permission;
+pub mod preference;
 pub mod role;
+pub mod role_scope;
+pub mod tenant;
 pub mod user;
 
 use sea_orm::ColumnTrait;
 use sea_query::Order;
 use serde::Deserialize;
+use serde_json::json;
+use utoipa::openapi::path::{Parameter, ParameterBuilder, ParameterIn};
+use utoipa::openapi::schema::{AllOfBuilder, Schema, SchemaType};
+use utoipa::openapi::{ObjectBuilder, Ref, RefOr, Required};
 use utoipa::{IntoParams, ToSchema};
 
-#[derive(Clone, Deserialize, ToSchema, IntoParams)]
+use crate::sheet::ExportFormat;
+
+/// Columns a [`PaginationRequest`] can be ordered by, see `sortable!`.
+pub trait Sortable: ColumnTrait + for<'s> ToSchema<'s> {
+    /// Name of the schema of `PaginationRequest<Self>`.
+    const PAGINATION: &'static str;
+}
+
+#[derive(Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
-#[into_params(parameter_in = Query)]
 pub struct PaginationRequest<T> {
-    #[schema(example = "1")]
     pub page: Option<u64>,
-    #[schema(example = "10")]
     pub limit: Option<u64>,
-    #[schema(example = "a")]
     pub search: Option<String>,
-    #[schema()]
     pub order: Option<T>,
-    #[schema(example = "asc")]
     pub sort: Option<Sort>,
 }
 
+impl<T: Sortable> PaginationRequest<T> {
+    /// Every field along with its schema, documented both as query
+    /// parameters and as a schema of its own.
+    fn fields() -> Vec<(&'static str, RefOr<Schema>)> {
+        let integer = |description: &str, example: u64| {
+            ObjectBuilder::new()
+                .schema_type(SchemaType::Integer)
+                .minimum(Some(1.0))
+                .description(Some(description))
+                .example(Some(json!(example)))
+                .nullable(true)
+                .build()
+                .into()
+        };
+        let reference = |name: &str| {
+            RefOr::T(Schema::AllOf(
+                AllOfBuilder::new()
+                    .item(Ref::from_schema_name(name))
+                    .nullable(true)
+                    .build(),
+            ))
+        };
+
+        vec![
+            ("page", integer("The first when empty.", 1)),
+            ("limit", integer("Between 10 and 100, 10 when empty.", 10)),
+            (
+                "search",
+                ObjectBuilder::new()
+                    .schema_type(SchemaType::String)
+                    .example(Some(json!("a")))
+                    .nullable(true)
+                    .build()
+                    .into(),
+            ),
+            ("order", reference(T::schema().0)),
+            ("sort", reference(Sort::schema().0)),
+        ]
+    }
+}
+
+impl<T: Sortable> ToSchema<'_> for PaginationRequest<T> {
+    fn schema() -> (&'static str, RefOr<Schema>) {
+        let schema = Self::fields()
+            .into_iter()
+            .fold(ObjectBuilder::new(), |schema, (name, field)| {
+                schema.property(name, field)
+            })
+            .build();
+
+        (T::PAGINATION, schema.into())
+    }
+}
+
+impl<T: Sortable> IntoParams for PaginationRequest<T> {
+    fn into_params(parameter_in: impl Fn() -> Option<ParameterIn>) -> Vec<Parameter> {
+        Self::fields()
+            .into_iter()
+            .map(|(name, schema)| {
+                ParameterBuilder::new()
+                    .name(name)
+                    .parameter_in(parameter_in().unwrap_or(ParameterIn::Query))
+                    .required(Required::False)
+                    .schema(Some(schema))
+                    .build()
+            })
+            .collect()
+    }
+}
+
 impl<T: ColumnTrait> PaginationRequest<T> {
     pub fn page(&self) -> u64 {
         if let Some(page) = self.page {
@@ -63,7 +184,7 @@ impl<T: ColumnTrait> PaginationRequest<T> {
 
     pub fn order(&self, default: T) -> T {
         if let Some(order) = self.order {
-            order.into()
+            order
         } else {
             default
         }
@@ -80,11 +201,19 @@ pub enum Sort {
     Desc,
 }
 
-impl Into<Order> for Sort {
-    fn into(self) -> Order {
-        match self {
-            Self::Asc => Order::Asc,
-            Self::Desc => Order::Desc,
+impl From<Sort> for Order {
+    fn from(sort: Sort) -> Self {
+        match sort {
+            Sort::Asc => Self::Asc,
+            Sort::Desc => Self::Desc,
         }
     }
 }
+
+#[derive(Clone, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportRequest {
+    /// Csv when empty.
+    #[param(inline)]
+    pub format: Option<ExportFormat>,
+}
@@ -1,49 +1,97 @@
+use chrono::{DateTime, FixedOffset};
 use serde::Deserialize;
-use utoipa::openapi::schema::{Schema, SchemaType};
-use utoipa::openapi::{ObjectBuilder, RefOr};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
 
-use crate::models::{permissions, Id};
+use crate::dao::grant::Period;
+use crate::models::{permissions, users, Id};
+use crate::responses::AppError;
+use crate::validation::{self, Rule, Rules};
 
-impl ToSchema<'_> for permissions::Column {
-    fn schema() -> (&'static str, RefOr<Schema>) {
-        let schema = ObjectBuilder::new().schema_type(SchemaType::String).build();
+sortable!(permissions::Column as "Permission" {
+    "code" => Code,
+    "name" => Name,
+    "group" => Group,
+});
 
-        ("PermissionColumn", schema.into())
-    }
-}
-
-impl<'de> Deserialize<'de> for permissions::Column {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let s = String::deserialize(deserializer)?;
-
-        match s.as_str() {
-            "code" => Ok(permissions::Column::Code),
-            "name" => Ok(permissions::Column::Name),
-            _ => Err(serde::de::Error::custom("invalid permission column")),
-        }
-    }
+#[derive(Clone, Deserialize, ToSchema, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct PermissionFilterRequest {
+    #[schema(example = "user")]
+    pub group: Option<String>,
+    /// Only permissions the application no longer defines, or only those it
+    /// does.
+    #[schema(example = true)]
+    pub orphaned: Option<bool>,
 }
 
-#[derive(Clone, Deserialize, ToSchema)]
+#[derive(Clone, Deserialize, ToSchema, Validate)]
 pub struct PermissionStoreRequest {
-    #[schema(example = "CREATE_USER")]
+    /// Must not belong to another permission.
+    #[validate(custom = "validation::required")]
+    #[schema(example = "CREATE_USER", min_length = 1)]
     pub code: String,
-    #[schema(example = "create user")]
+    #[validate(custom = "validation::required")]
+    #[schema(example = "create user", min_length = 1)]
     pub name: String,
 }
 
-#[derive(Clone, Deserialize, ToSchema)]
+impl Rules for PermissionStoreRequest {
+    fn rules(&self, _: &()) -> Vec<Rule> {
+        vec![Rule::unique(
+            "code",
+            permissions::Column::Code,
+            self.code.to_uppercase().replace(' ', "_"),
+        )]
+    }
+}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
 pub struct PermissionUpdateRequest {
-    #[schema(example = "create user")]
+    #[validate(custom = "validation::required")]
+    #[schema(example = "create user", min_length = 1)]
     pub name: String,
 }
 
-#[derive(Clone, Deserialize, ToSchema)]
+impl Rules for PermissionUpdateRequest {}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
 pub struct PermissionBulkRequest {
+    #[validate(custom = "validation::filled")]
     #[schema()]
     pub permissions: Vec<Id>,
+    /// Ids of the users to grant every permission of `permissions` to, or
+    /// revoke them from.
+    #[validate(custom = "validation::filled")]
+    #[schema()]
+    pub users: Vec<Id>,
+    /// When the permissions come into effect, right away when empty. Ignored
+    /// when revoking, as is `expiresAt`.
+    #[schema(value_type = Option<String>, format = DateTime, example = "2024-02-01T00:00:00+07:00")]
+    pub starts_at: Option<DateTime<FixedOffset>>,
+    /// When the permissions lapse, never when empty. Granting them again
+    /// replaces both ends.
+    #[schema(value_type = Option<String>, format = DateTime, example = "2024-06-30T00:00:00+07:00")]
+    pub expires_at: Option<DateTime<FixedOffset>>,
+}
+
+impl PermissionBulkRequest {
+    pub fn period(&self) -> Result<Period, AppError> {
+        validation::period(self.starts_at, self.expires_at)
+    }
+}
+
+impl Rules for PermissionBulkRequest {
+    fn rules(&self, _: &()) -> Vec<Rule> {
+        vec![
+            Rule::exists(
+                "permissions",
+                permissions::Column::Id,
+                self.permissions.clone(),
+            ),
+            Rule::exists("users", users::Column::Id, self.users.clone()),
+        ]
+    }
 }
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::validation::{self, Rules};
+
+/// How the user wants dates written, left to clients to apply.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum DateFormat {
+    #[default]
+    #[serde(rename = "YYYY-MM-DD")]
+    Iso,
+    #[serde(rename = "DD/MM/YYYY")]
+    DayMonthYear,
+    #[serde(rename = "MM/DD/YYYY")]
+    MonthDayYear,
+    #[serde(rename = "DD.MM.YYYY")]
+    Dotted,
+}
+
+impl DateFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Iso => "YYYY-MM-DD",
+            Self::DayMonthYear => "DD/MM/YYYY",
+            Self::MonthDayYear => "MM/DD/YYYY",
+            Self::Dotted => "DD.MM.YYYY",
+        }
+    }
+
+    /// The stored format, the default one when it is not known anymore.
+    pub fn of(value: &str) -> Self {
+        match value {
+            "DD/MM/YYYY" => Self::DayMonthYear,
+            "MM/DD/YYYY" => Self::MonthDayYear,
+            "DD.MM.YYYY" => Self::Dotted,
+            _ => Self::Iso,
+        }
+    }
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Channels the user accepts to be notified through. Security notices,
+/// like a changed password, are sent regardless.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Notifications {
+    #[serde(default = "enabled")]
+    #[schema(example = true)]
+    pub email: bool,
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self { email: enabled() }
+    }
+}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct PreferenceUpdateRequest {
+    /// IANA name of the timezone timestamps are answered in.
+    #[validate(custom = "validation::required")]
+    #[validate(custom = "validation::timezone")]
+    #[schema(example = "Asia/Jakarta")]
+    pub timezone: String,
+    /// Language of messages and mails sent to the user, `Accept-Language` is used when empty.
+    #[validate(custom = "validation::locale")]
+    #[schema(example = "id")]
+    pub locale: Option<String>,
+    #[schema(example = "DD/MM/YYYY")]
+    pub date_format: DateFormat,
+    #[schema()]
+    pub notifications: Notifications,
+}
+
+impl Rules for PreferenceUpdateRequest {}
@@ -1,49 +1,98 @@
+use chrono::{DateTime, FixedOffset};
 use serde::Deserialize;
-use utoipa::openapi::schema::{Schema, SchemaType};
-use utoipa::openapi::{ObjectBuilder, RefOr};
 use utoipa::ToSchema;
+use validator::Validate;
 
-use crate::models::{roles, Id};
+use crate::dao::grant::Period;
+use crate::models::{roles, users, Id};
+use crate::responses::AppError;
+use crate::validation::{self, Rule, Rules};
 
-impl ToSchema<'_> for roles::Column {
-    fn schema() -> (&'static str, RefOr<Schema>) {
-        let schema = ObjectBuilder::new().schema_type(SchemaType::String).build();
+sortable!(roles::Column as "Role" {
+    "code" => Code,
+    "name" => Name,
+});
 
-        ("PermissionColumn", schema.into())
-    }
-}
-
-impl<'de> Deserialize<'de> for roles::Column {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let s = String::deserialize(deserializer)?;
-
-        match s.as_str() {
-            "code" => Ok(roles::Column::Code),
-            "name" => Ok(roles::Column::Name),
-            _ => Err(serde::de::Error::custom("invalid permission column")),
-        }
-    }
-}
-
-#[derive(Clone, Deserialize, ToSchema)]
+#[derive(Clone, Deserialize, ToSchema, Validate)]
 pub struct RoleStoreRequest {
-    #[schema(example = "AREA_MANAGER")]
+    /// Must not belong to another role.
+    #[validate(custom = "validation::required")]
+    #[schema(example = "AREA_MANAGER", min_length = 1)]
     pub code: String,
-    #[schema(example = "area manager")]
+    #[validate(custom = "validation::required")]
+    #[schema(example = "area manager", min_length = 1)]
     pub name: String,
 }
 
-#[derive(Clone, Deserialize, ToSchema)]
+impl Rules for RoleStoreRequest {
+    fn rules(&self, _: &()) -> Vec<Rule> {
+        vec![Rule::unique(
+            "code",
+            roles::Column::Code,
+            self.code.to_uppercase().replace(' ', "_"),
+        )]
+    }
+}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
 pub struct RoleUpdateRequest {
-    #[schema(example = "area manager")]
+    #[validate(custom = "validation::required")]
+    #[schema(example = "area manager", min_length = 1)]
     pub name: String,
 }
 
-#[derive(Clone, Deserialize, ToSchema)]
+impl Rules for RoleUpdateRequest {}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
 pub struct RoleBulkRequest {
+    #[validate(custom = "validation::filled")]
     #[schema()]
     pub roles: Vec<Id>,
+    /// Ids of the users to assign every role of `roles` to, or revoke them
+    /// from.
+    #[validate(custom = "validation::filled")]
+    #[schema()]
+    pub users: Vec<Id>,
+    /// When the roles come into effect, right away when empty. Ignored
+    /// when revoking, as is `expiresAt`.
+    #[schema(value_type = Option<String>, format = DateTime, example = "2024-02-01T00:00:00+07:00")]
+    pub starts_at: Option<DateTime<FixedOffset>>,
+    /// When the roles lapse, never when empty. Assigning them again
+    /// replaces both ends.
+    #[schema(value_type = Option<String>, format = DateTime, example = "2024-06-30T00:00:00+07:00")]
+    pub expires_at: Option<DateTime<FixedOffset>>,
+}
+
+impl RoleBulkRequest {
+    pub fn period(&self) -> Result<Period, AppError> {
+        validation::period(self.starts_at, self.expires_at)
+    }
+}
+
+impl Rules for RoleBulkRequest {
+    fn rules(&self, _: &()) -> Vec<Rule> {
+        vec![
+            Rule::exists("roles", roles::Column::Id, self.roles.clone()),
+            Rule::exists("users", users::Column::Id, self.users.clone()),
+        ]
+    }
+}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+pub struct RoleParentRequest {
+    /// Ids of existing roles to place right above the role, replacing the
+    /// current ones. They inherit whatever the role is granted.
+    #[schema()]
+    pub parents: Vec<Id>,
+}
+
+impl Rules for RoleParentRequest {
+    fn rules(&self, _: &()) -> Vec<Rule> {
+        vec![Rule::exists(
+            "parents",
+            roles::Column::Id,
+            self.parents.clone(),
+        )]
+    }
 }
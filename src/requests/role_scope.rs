@@ -0,0 +1,30 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::models::{roles, Id};
+use crate::validation::{self, Rule, Rules};
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleScopeStoreRequest {
+    /// Id of an existing role, granted within the scope only.
+    #[schema()]
+    pub role_id: Id,
+    /// Type of the resource the role is held within.
+    #[validate(custom = "validation::required", custom = "validation::scope")]
+    #[schema(example = "course", min_length = 1, max_length = 32)]
+    pub scope_type: String,
+    /// Id of the resource the role is held within.
+    #[schema()]
+    pub scope_id: Id,
+}
+
+impl Rules for RoleScopeStoreRequest {
+    fn rules(&self, _: &()) -> Vec<Rule> {
+        vec![
+            Rule::exists("role_id", roles::Column::Id, vec![self.role_id])
+                .message("validation.selected"),
+        ]
+    }
+}
@@ -0,0 +1,37 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::models::tenants;
+use crate::validation::{self, Rule, Rules};
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+pub struct TenantStoreRequest {
+    /// Subdomain the tenant is reached at, also accepted in the `X-Tenant`
+    /// header. Must not belong to another tenant.
+    #[validate(custom = "validation::required", custom = "validation::subdomain")]
+    #[schema(example = "north-high", min_length = 1, max_length = 63)]
+    pub code: String,
+    #[validate(custom = "validation::required")]
+    #[schema(example = "North High School", min_length = 1)]
+    pub name: String,
+}
+
+impl Rules for TenantStoreRequest {
+    fn rules(&self, _: &()) -> Vec<Rule> {
+        vec![Rule::unique(
+            "code",
+            tenants::Column::Code,
+            self.code.clone(),
+        )]
+    }
+}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+pub struct TenantUpdateRequest {
+    #[validate(custom = "validation::required")]
+    #[schema(example = "North High School", min_length = 1)]
+    pub name: String,
+}
+
+impl Rules for TenantUpdateRequest {}
@@ -1,75 +1,351 @@
-use serde::Deserialize;
-use utoipa::openapi::schema::{Schema, SchemaType};
-use utoipa::openapi::{ObjectBuilder, RefOr};
-use utoipa::ToSchema;
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
 
-use crate::models::{users, Id};
+use crate::common::time;
+use crate::models::{files, permissions, roles, users, Id};
+use crate::validation::{self, Rule, Rules};
 
-impl ToSchema<'_> for users::Column {
-    fn schema() -> (&'static str, RefOr<Schema>) {
-        let schema = ObjectBuilder::new().schema_type(SchemaType::String).build();
+sortable!(users::Column as "User" {
+    "name" => Name,
+    "email" => Email,
+    "username" => Username,
+    "createdAt" => CreatedAt,
+});
 
-        ("PermissionColumn", schema.into())
-    }
-}
-
-impl<'de> Deserialize<'de> for users::Column {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let s = String::deserialize(deserializer)?;
-
-        match s.as_str() {
-            "name" => Ok(users::Column::Name),
-            "email" => Ok(users::Column::Email),
-            "username" => Ok(users::Column::Username),
-            "createdAt" => Ok(users::Column::CreatedAt),
-            _ => Err(serde::de::Error::custom("invalid permission column")),
-        }
-    }
-}
-
-#[derive(Clone, Deserialize, ToSchema)]
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
 pub struct UserStoreRequest {
-    #[schema(example = "John Doe")]
+    #[validate(custom = "validation::required")]
+    #[schema(example = "John Doe", min_length = 1)]
     pub name: String,
-    #[schema(example = "john@local.id")]
+    /// Must not belong to another user.
+    #[validate(custom = "validation::required", custom = "validation::email")]
+    #[schema(example = "john@local.id", format = "email")]
     pub email: String,
-    #[schema(example = "john")]
+    /// Must not belong to another user.
+    #[validate(custom = "validation::required")]
+    #[schema(example = "john", min_length = 1)]
     pub username: String,
-    #[schema(example = "password")]
+    /// At least 6 characters with an uppercase letter, a digit and a special character.
+    #[validate(
+        custom = "validation::required",
+        custom = "validation::password_length",
+        custom = "validation::password_uppercase",
+        custom = "validation::password_digit",
+        custom = "validation::password_special"
+    )]
+    #[schema(example = "Password123!", format = Password, min_length = 6)]
     pub password: String,
+    /// Ids of existing permissions.
     #[schema()]
     pub permissions: Vec<Id>,
+    /// Ids of existing roles.
     #[schema()]
     pub roles: Vec<Id>,
 }
 
-#[derive(Clone, Deserialize, ToSchema)]
+impl Rules for UserStoreRequest {
+    fn rules(&self, _: &()) -> Vec<Rule> {
+        vec![
+            Rule::unique(
+                "email",
+                users::Column::Email,
+                self.email.trim().to_lowercase(),
+            ),
+            Rule::unique(
+                "username",
+                users::Column::Username,
+                self.username.trim().to_lowercase(),
+            ),
+            Rule::exists(
+                "permissions",
+                permissions::Column::Id,
+                self.permissions.clone(),
+            ),
+            Rule::exists("roles", roles::Column::Id, self.roles.clone()),
+        ]
+    }
+}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct UserUpdateGeneralInformationRequest {
-    #[schema(example = "John Doe")]
+    #[validate(custom = "validation::required")]
+    #[schema(example = "John Doe", min_length = 1)]
     pub name: String,
-    #[schema(example = "john@local.id")]
+    /// Must not belong to another user.
+    #[validate(custom = "validation::required", custom = "validation::email")]
+    #[schema(example = "john@local.id", format = "email")]
     pub email: String,
-    #[schema(example = "john")]
+    /// Must not belong to another user.
+    #[validate(custom = "validation::required")]
+    #[schema(example = "john", min_length = 1)]
     pub username: String,
+    /// Id of a file the user making the request uploaded, see `POST /api/v1/file`.
     #[schema()]
-    pub profile_photo_id: Option<String>,
+    pub profile_photo_id: Option<Id>,
+    /// Language of messages and mails sent to the user, `Accept-Language` is used when empty.
+    #[validate(custom = "validation::locale")]
+    #[schema(example = "id")]
+    pub locale: Option<String>,
+    /// Ids of existing permissions.
     #[schema()]
     pub permissions: Vec<Id>,
+    /// Ids of existing roles.
     #[schema()]
     pub roles: Vec<Id>,
 }
 
-#[derive(Clone, Deserialize, ToSchema)]
+/// Uniqueness is checked against every user but the one being updated, of
+/// `(id, uploader)`, and the profile photo must be a file `uploader` sent.
+impl Rules<(Id, Option<Id>)> for UserUpdateGeneralInformationRequest {
+    fn rules(&self, (id, uploader): &(Id, Option<Id>)) -> Vec<Rule> {
+        vec![
+            Rule::unique(
+                "email",
+                users::Column::Email,
+                self.email.trim().to_lowercase(),
+            )
+            .except(users::Column::Id, *id),
+            Rule::unique(
+                "username",
+                users::Column::Username,
+                self.username.trim().to_lowercase(),
+            )
+            .except(users::Column::Id, *id),
+            Rule::exists(
+                "profile_photo_id",
+                files::Column::Id,
+                self.profile_photo_id.iter().cloned().collect(),
+            )
+            .within(files::Column::UploadedBy, *uploader)
+            .message("validation.selected"),
+            Rule::exists(
+                "permissions",
+                permissions::Column::Id,
+                self.permissions.clone(),
+            ),
+            Rule::exists("roles", roles::Column::Id, self.roles.clone()),
+        ]
+    }
+}
+
+/// The part of a user one may change of their own account.
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UserUpdateProfileRequest {
+    #[validate(custom = "validation::required")]
+    #[schema(example = "John Doe", min_length = 1)]
+    pub name: String,
+    /// Must not belong to another user.
+    #[validate(custom = "validation::required", custom = "validation::email")]
+    #[schema(example = "john@local.id", format = "email")]
+    pub email: String,
+    /// Must not belong to another user.
+    #[validate(custom = "validation::required")]
+    #[schema(example = "john", min_length = 1)]
+    pub username: String,
+    /// Id of a file the user making the request uploaded, see `POST /api/v1/file`.
+    #[schema()]
+    pub profile_photo_id: Option<Id>,
+    /// Language of messages and mails sent to the user, `Accept-Language` is used when empty.
+    #[validate(custom = "validation::locale")]
+    #[schema(example = "id")]
+    pub locale: Option<String>,
+}
+
+impl Rules<Id> for UserUpdateProfileRequest {
+    fn rules(&self, id: &Id) -> Vec<Rule> {
+        vec![
+            Rule::unique(
+                "email",
+                users::Column::Email,
+                self.email.trim().to_lowercase(),
+            )
+            .except(users::Column::Id, *id),
+            Rule::unique(
+                "username",
+                users::Column::Username,
+                self.username.trim().to_lowercase(),
+            )
+            .except(users::Column::Id, *id),
+            Rule::exists(
+                "profile_photo_id",
+                files::Column::Id,
+                self.profile_photo_id.iter().cloned().collect(),
+            )
+            .within(files::Column::UploadedBy, *id)
+            .message("validation.selected"),
+        ]
+    }
+}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct UserUpdatePasswordRequest {
-    #[schema(example = "Password123")]
+    #[validate(custom = "validation::required")]
+    #[schema(example = "Password123!", format = Password)]
     pub current_password: String,
-    #[schema(example = "Password123")]
+    /// At least 6 characters with an uppercase letter, a digit and a special character.
+    #[validate(
+        custom = "validation::required",
+        custom = "validation::password_length",
+        custom = "validation::password_uppercase",
+        custom = "validation::password_digit",
+        custom = "validation::password_special"
+    )]
+    #[schema(example = "Password123!", format = Password, min_length = 6)]
     pub new_password: String,
-    #[schema(example = "Password123")]
+    #[validate(
+        custom = "validation::required",
+        must_match(other = "new_password", message = "validation.password_confirmation")
+    )]
+    #[schema(example = "Password123!", format = Password)]
     pub password_confirmation: String,
 }
+
+impl Rules for UserUpdatePasswordRequest {}
+
+/// Set by an administrator, so the current password is not asked for.
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSetPasswordRequest {
+    /// At least 6 characters with an uppercase letter, a digit and a special character.
+    #[validate(
+        custom = "validation::required",
+        custom = "validation::password_length",
+        custom = "validation::password_uppercase",
+        custom = "validation::password_digit",
+        custom = "validation::password_special"
+    )]
+    #[schema(example = "Password123!", format = Password, min_length = 6)]
+    pub password: String,
+    #[validate(
+        custom = "validation::required",
+        must_match(other = "password", message = "validation.confirmed")
+    )]
+    #[schema(example = "Password123!", format = Password)]
+    pub password_confirmation: String,
+}
+
+impl Rules for UserSetPasswordRequest {}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+pub struct UserDeleteAccountRequest {
+    /// The current password of the account.
+    #[validate(custom = "validation::required")]
+    #[schema(example = "Password123!", format = Password)]
+    pub password: String,
+}
+
+impl Rules for UserDeleteAccountRequest {}
+
+/// A csv or xlsx file with the columns `name`, `email`, `username`,
+/// `roles` holding role codes separated by commas and an optional
+/// `password`, which the user is mailed a link to choose when empty.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct UserImportRequest {
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Import nothing unless every row is valid.
+    #[default]
+    Atomic,
+    /// Import the valid rows and report the others.
+    Partial,
+}
+
+#[derive(Clone, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct UserImportOptionsRequest {
+    /// Only validate the rows, importing none of them.
+    #[param(example = true)]
+    pub dry_run: Option<bool>,
+    #[param(inline)]
+    pub mode: Option<ImportMode>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountStatus {
+    Active,
+    /// Barred from signing in, until a date or until reactivated.
+    Suspended,
+    Deactivated,
+}
+
+impl AccountStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Suspended => "suspended",
+            Self::Deactivated => "deactivated",
+        }
+    }
+
+    /// A suspension that ran out counts as active, without anyone having
+    /// to reactivate the user.
+    pub fn of(user: &users::Model) -> Self {
+        match user.status.as_str() {
+            "deactivated" => Self::Deactivated,
+            "suspended" if user.suspended_until.is_none_or(|until| until > time::now()) => {
+                Self::Suspended
+            }
+            _ => Self::Active,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, ToSchema, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct UserFilterRequest {
+    #[param(inline)]
+    pub status: Option<AccountStatus>,
+}
+
+#[derive(Clone, Deserialize, ToSchema, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct UserPermissionCheckRequest {
+    /// Code of the permission to check the user against.
+    #[validate(custom = "validation::required")]
+    #[param(example = "UPDATE_USER")]
+    #[schema(example = "UPDATE_USER", min_length = 1)]
+    pub permission: String,
+}
+
+impl Rules for UserPermissionCheckRequest {}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSuspendRequest {
+    /// Shown to the user when they try to sign in.
+    #[validate(custom = "validation::required")]
+    #[schema(example = "Repeated spam in the forum", min_length = 1)]
+    pub reason: String,
+    /// Suspended until reactivated when empty.
+    #[schema(value_type = Option<String>, format = DateTime, example = "2024-03-01T00:00:00+07:00")]
+    pub until: Option<DateTime<FixedOffset>>,
+}
+
+impl Rules for UserSuspendRequest {}
+
+#[derive(Clone, Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UserDeactivateRequest {
+    /// Shown to the user when they try to sign in.
+    #[validate(custom = "validation::required")]
+    #[schema(example = "Left the school", min_length = 1)]
+    pub reason: String,
+}
+
+impl Rules for UserDeactivateRequest {}
@@ -0,0 +1,76 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+use utoipa::{IntoResponses, ToSchema};
+
+use crate::common::time;
+use crate::models::audit_logs::Model;
+use crate::models::{Id, Timestamp};
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct AuditLogOAS {
+    #[schema()]
+    pub id: Id,
+    /// The user who made the change, empty for guests like someone signing up.
+    #[schema()]
+    pub actor_id: Option<Id>,
+    /// Whoever impersonated the actor at the time.
+    #[schema()]
+    pub impersonator_id: Option<Id>,
+    #[schema(example = "update")]
+    pub action: String,
+    #[schema(example = "user")]
+    pub entity_type: String,
+    #[schema()]
+    pub entity_id: Id,
+    /// The changed fields as they were, empty on create.
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<serde_json::Value>,
+    /// The changed fields as they became, empty on delete.
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<serde_json::Value>,
+    #[schema(example = "127.0.0.1")]
+    pub ip: Option<String>,
+    #[schema()]
+    pub user_agent: Option<String>,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub created_at: Timestamp,
+}
+
+impl From<&Model> for AuditLogOAS {
+    fn from(log: &Model) -> Self {
+        Self {
+            id: log.id,
+            actor_id: log.actor_id,
+            impersonator_id: log.impersonator_id,
+            action: log.action.clone(),
+            entity_type: log.entity_type.clone(),
+            entity_id: log.entity_id,
+            before: log.before.clone(),
+            after: log.after.clone(),
+            ip: log.ip.clone(),
+            user_agent: log.user_agent.clone(),
+            created_at: log.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct AuditLogPaginationResponse {
+    #[schema(example = "10")]
+    pub total: u64,
+    #[schema(example = "1")]
+    pub page: u64,
+    #[schema()]
+    pub data: Vec<AuditLogOAS>,
+}
+
+impl From<AuditLogPaginationResponse> for HttpResponse {
+    fn from(response: AuditLogPaginationResponse) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
@@ -18,3 +18,13 @@ pub struct Authenticated {
     #[schema()]
     pub user: UserOAS,
 }
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[response(status = 201, description = "Created")]
+pub struct Registered {
+    /// Only issued when the email does not need to be verified first.
+    #[schema()]
+    pub token: Option<String>,
+    #[schema()]
+    pub user: UserOAS,
+}
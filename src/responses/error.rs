@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result};
+
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use sea_orm::DbErr;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::common::{env, log};
+use crate::i18n::t;
+
+/// Body of every error response, see RFC 7807.
+#[derive(Serialize, ToSchema)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    #[schema(example = "/errors/not_found")]
+    pub r#type: String,
+    #[schema(example = "Not Found")]
+    pub title: String,
+    #[schema(example = 404)]
+    pub status: u16,
+    #[schema(example = "User not found")]
+    pub detail: String,
+    #[schema(example = "not_found")]
+    pub code: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema()]
+    pub errors: Option<HashMap<String, Vec<String>>>,
+}
+
+#[derive(Debug)]
+pub enum AppError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    Validation(HashMap<String, Vec<String>>),
+    Database(DbErr),
+    Internal(String),
+}
+
+impl AppError {
+    pub fn validation<K: ToString, V: ToString>(errors: HashMap<K, Vec<V>>) -> Self {
+        Self::Validation(
+            errors
+                .into_iter()
+                .map(|(field, messages)| {
+                    let messages = messages.iter().map(ToString::to_string).collect();
+
+                    (field.to_string(), messages)
+                })
+                .collect(),
+        )
+    }
+
+    /// Stable, machine readable identifier of the error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::BadRequest(_) => "bad_request",
+            Self::Unauthorized(_) => "unauthorized",
+            Self::Forbidden(_) => "forbidden",
+            Self::NotFound(_) => "not_found",
+            Self::Conflict(_) => "conflict",
+            Self::Validation(_) => "validation_failed",
+            Self::Database(_) => "database_error",
+            Self::Internal(_) => "internal_error",
+        }
+    }
+
+    pub fn problem(&self) -> Problem {
+        let status = self.status_code();
+        let detail = match self {
+            Self::BadRequest(message)
+            | Self::Unauthorized(message)
+            | Self::Forbidden(message)
+            | Self::NotFound(message)
+            | Self::Conflict(message) => message.clone(),
+            Self::Validation(_) => t!("errors.invalid"),
+            Self::Database(_) | Self::Internal(_) if env::production() => t!("errors.unexpected"),
+            Self::Database(e) => e.to_string(),
+            Self::Internal(message) => message.clone(),
+        };
+
+        Problem {
+            r#type: format!("/errors/{}", self.code()),
+            title: t!(&format!("errors.{}", self.code())),
+            status: status.as_u16(),
+            detail,
+            code: self.code(),
+            errors: match self {
+                Self::Validation(errors) => Some(errors.clone()),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::Database(e) => write!(f, "{}", e),
+            Self::Validation(errors) => write!(f, "{:?}", errors),
+            Self::BadRequest(message)
+            | Self::Unauthorized(message)
+            | Self::Forbidden(message)
+            | Self::NotFound(message)
+            | Self::Conflict(message)
+            | Self::Internal(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<DbErr> for AppError {
+    fn from(e: DbErr) -> Self {
+        Self::Database(e)
+    }
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Database(_) | Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if let Self::Database(_) | Self::Internal(_) = self {
+            log::error!(AppError, "{}", self);
+        }
+
+        HttpResponse::build(self.status_code())
+            .content_type("application/problem+json")
+            .json(self.problem())
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use std::collections::HashMap;
+
+    use actix_web::ResponseError;
+
+    use super::AppError;
+
+    #[test]
+    pub async fn validation_must_keep_field_errors() {
+        let error = AppError::validation(HashMap::from([("email", vec!["Email is invalid"])]));
+        let problem = error.problem();
+
+        assert_eq!(problem.status, 422);
+        assert_eq!(problem.code, "validation_failed");
+        assert_eq!(
+            problem.errors.unwrap().get("email"),
+            Some(&vec!["Email is invalid".to_string()])
+        );
+    }
+
+    #[test]
+    pub async fn error_response_must_be_problem_json() {
+        let response = AppError::NotFound("User not found".to_string()).error_response();
+
+        assert_eq!(response.status(), 404);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "application/problem+json"
+        );
+    }
+}
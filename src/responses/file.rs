@@ -0,0 +1,69 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+use utoipa::{IntoResponses, ToSchema};
+
+use crate::common::time;
+use crate::models::files::Model;
+use crate::models::{Id, Timestamp};
+use crate::storage::{self, Variant};
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct FileOAS {
+    #[schema()]
+    pub id: Id,
+    #[schema(example = "avatar.png")]
+    pub name: String,
+    #[schema(example = "image/png")]
+    pub mime: String,
+    #[schema(example = 20480)]
+    pub size: i64,
+    /// Hex encoded sha256 of the content.
+    #[schema()]
+    pub checksum: String,
+    #[schema(example = 512)]
+    pub width: Option<i32>,
+    #[schema(example = 512)]
+    pub height: Option<i32>,
+    /// Signed, expiring download url.
+    #[schema()]
+    pub url: String,
+    #[schema()]
+    pub thumbnail_url: Option<String>,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub created_at: Timestamp,
+}
+
+impl From<FileOAS> for HttpResponse {
+    fn from(response: FileOAS) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
+
+impl From<Model> for FileOAS {
+    fn from(file: Model) -> Self {
+        Self::from(&file)
+    }
+}
+
+impl From<&Model> for FileOAS {
+    fn from(file: &Model) -> Self {
+        Self {
+            id: file.id,
+            name: file.name.clone(),
+            mime: file.mime.clone(),
+            size: file.size,
+            checksum: file.checksum.clone(),
+            width: file.width,
+            height: file.height,
+            url: storage::url(file.id, Variant::Original),
+            thumbnail_url: file
+                .thumbnail_path
+                .as_ref()
+                .map(|_| storage::url(file.id, Variant::Thumbnail)),
+            created_at: file.created_at,
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+use utoipa::{IntoResponses, ToSchema};
+
+use crate::common::time;
+use crate::models::invitations::Model;
+use crate::models::{Id, Timestamp};
+use crate::requests::invitation::InvitationStatus;
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct InvitationOAS {
+    #[schema()]
+    pub id: Id,
+    #[schema(example = "john@local.id")]
+    pub email: String,
+    #[schema(example = "John Doe")]
+    pub name: String,
+    /// Ids of the roles granted on acceptance.
+    #[schema()]
+    pub roles: Vec<Id>,
+    /// Ids of the permissions granted on acceptance.
+    #[schema()]
+    pub permissions: Vec<Id>,
+    #[schema(example = "pending")]
+    pub status: InvitationStatus,
+    #[schema()]
+    pub invited_by: Option<Id>,
+    /// The user created by accepting the invitation.
+    #[schema()]
+    pub user_id: Option<Id>,
+    #[schema(example = 1)]
+    pub sent_count: i32,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub sent_at: Timestamp,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub expired_at: Timestamp,
+    #[serde(with = "time::rfc3339::option")]
+    #[schema()]
+    pub accepted_at: Option<Timestamp>,
+    #[serde(with = "time::rfc3339::option")]
+    #[schema()]
+    pub revoked_at: Option<Timestamp>,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub created_at: Timestamp,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub updated_at: Timestamp,
+}
+
+impl From<InvitationOAS> for HttpResponse {
+    fn from(response: InvitationOAS) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
+
+impl From<Model> for InvitationOAS {
+    fn from(invitation: Model) -> Self {
+        Self::from(&invitation)
+    }
+}
+
+impl From<&Model> for InvitationOAS {
+    fn from(invitation: &Model) -> Self {
+        Self {
+            id: invitation.id,
+            email: invitation.email.clone(),
+            name: invitation.name.clone(),
+            roles: serde_json::from_value(invitation.roles.clone()).unwrap_or_default(),
+            permissions: serde_json::from_value(invitation.permissions.clone()).unwrap_or_default(),
+            status: InvitationStatus::of(invitation),
+            invited_by: invitation.invited_by,
+            user_id: invitation.user_id,
+            sent_count: invitation.sent_count,
+            sent_at: invitation.sent_at,
+            expired_at: invitation.expired_at,
+            accepted_at: invitation.accepted_at,
+            revoked_at: invitation.revoked_at,
+            created_at: invitation.created_at,
+            updated_at: invitation.updated_at,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct InvitationPaginationResponse {
+    #[schema(example = "10")]
+    pub total: u64,
+    #[schema(example = "1")]
+    pub page: u64,
+    #[schema()]
+    pub data: Vec<InvitationOAS>,
+}
+
+impl From<InvitationPaginationResponse> for HttpResponse {
+    fn from(response: InvitationPaginationResponse) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
+
+/// What the invitee sees of a valid invite link before accepting it.
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct InvitationLinkOAS {
+    #[schema(example = "john@local.id")]
+    pub email: String,
+    #[schema(example = "John Doe")]
+    pub name: String,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub expired_at: Timestamp,
+}
+
+impl From<InvitationLinkOAS> for HttpResponse {
+    fn from(response: InvitationLinkOAS) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
+
+impl From<&Model> for InvitationLinkOAS {
+    fn from(invitation: &Model) -> Self {
+        Self {
+            email: invitation.email.clone(),
+            name: invitation.name.clone(),
+            expired_at: invitation.expired_at,
+        }
+    }
+}
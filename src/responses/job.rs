@@ -0,0 +1,94 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+use utoipa::{IntoResponses, ToSchema};
+
+use crate::common::time;
+use crate::models::jobs::Model;
+use crate::models::{Id, Timestamp};
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct JobOAS {
+    #[schema()]
+    pub id: Id,
+    #[schema(example = "default")]
+    pub queue: String,
+    #[schema(example = "send_mail")]
+    pub name: String,
+    #[schema(value_type = Object)]
+    pub payload: serde_json::Value,
+    #[schema(example = "pending")]
+    pub status: String,
+    #[schema(example = "1")]
+    pub attempts: i32,
+    #[schema(example = "5")]
+    pub max_attempts: i32,
+    #[schema()]
+    pub last_error: Option<String>,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub available_at: Timestamp,
+    #[serde(with = "time::rfc3339::option")]
+    #[schema()]
+    pub locked_until: Option<Timestamp>,
+    #[serde(with = "time::rfc3339::option")]
+    #[schema()]
+    pub completed_at: Option<Timestamp>,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub created_at: Timestamp,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub updated_at: Timestamp,
+}
+
+impl From<JobOAS> for HttpResponse {
+    fn from(response: JobOAS) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
+
+impl From<Model> for JobOAS {
+    fn from(job: Model) -> Self {
+        Self::from(&job)
+    }
+}
+
+impl From<&Model> for JobOAS {
+    fn from(job: &Model) -> Self {
+        Self {
+            id: job.id,
+            queue: job.queue.clone(),
+            name: job.name.clone(),
+            payload: job.payload.clone(),
+            status: job.status.clone(),
+            attempts: job.attempts,
+            max_attempts: job.max_attempts,
+            last_error: job.last_error.clone(),
+            available_at: job.available_at,
+            locked_until: job.locked_until,
+            completed_at: job.completed_at,
+            created_at: job.created_at,
+            updated_at: job.updated_at,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct JobPaginationResponse {
+    #[schema(example = "10")]
+    pub total: u64,
+    #[schema(example = "1")]
+    pub page: u64,
+    #[schema()]
+    pub data: Vec<JobOAS>,
+}
+
+impl From<JobPaginationResponse> for HttpResponse {
+    fn from(response: JobPaginationResponse) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
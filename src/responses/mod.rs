@@ -1,7 +1,17 @@
+pub mod audit;
 pub mod auth;
+mod error;
+pub mod file;
+pub mod invitation;
+pub mod job;
 pub mod permission;
+pub mod preference;
 mod rest;
 pub mod role;
+pub mod role_scope;
+pub mod schedule;
+pub mod tenant;
 pub mod user;
 
+pub use error::*;
 pub use rest::*;
@@ -2,11 +2,18 @@ use actix_web::HttpResponse;
 use serde::Serialize;
 use utoipa::{IntoResponses, ToSchema};
 
+use crate::catalogue;
+use crate::common::time;
+use crate::dao::permission::{Effective, Source};
 use crate::models::permissions::Model;
-use crate::models::Id;
+use crate::models::{Id, Timestamp};
+use crate::sheet::Record;
+
+use super::role::RoleOAS;
 
 #[derive(Serialize, ToSchema, IntoResponses)]
 #[response(status = 200, description = "Ok")]
+#[serde(rename_all = "camelCase")]
 pub struct PermissionOAS {
     #[schema()]
     pub id: Id,
@@ -14,11 +21,24 @@ pub struct PermissionOAS {
     pub code: String,
     #[schema(example = "create user")]
     pub name: String,
+    #[schema(example = "user")]
+    pub group: Option<String>,
+    #[schema(example = "Create users and import them from spreadsheets.")]
+    pub description: Option<String>,
+    /// Whether the application defines the permission, which then cannot be
+    /// changed or deleted.
+    #[schema(example = true)]
+    pub system: bool,
+    /// When the application stopped defining the permission, or `None` if
+    /// it still does.
+    #[serde(with = "time::rfc3339::option")]
+    #[schema()]
+    pub orphaned_at: Option<Timestamp>,
 }
 
-impl Into<HttpResponse> for PermissionOAS {
-    fn into(self) -> HttpResponse {
-        HttpResponse::Ok().json(self)
+impl From<PermissionOAS> for HttpResponse {
+    fn from(response: PermissionOAS) -> Self {
+        HttpResponse::Ok().json(response)
     }
 }
 
@@ -31,13 +51,52 @@ impl From<Model> for PermissionOAS {
 impl From<&Model> for PermissionOAS {
     fn from(permission: &Model) -> Self {
         Self {
-            id: permission.id.clone(),
+            id: permission.id,
             code: permission.code.clone(),
             name: permission.name.clone(),
+            group: permission.group.clone(),
+            description: permission.description.clone(),
+            system: catalogue::find(&permission.code).is_some(),
+            orphaned_at: permission.orphaned_at,
         }
     }
 }
 
+impl Record for PermissionOAS {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "code",
+        "name",
+        "group",
+        "description",
+        "system",
+        "orphaned_at",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.code.clone(),
+            self.name.clone(),
+            self.group.clone().unwrap_or_default(),
+            self.description.clone().unwrap_or_default(),
+            self.system.to_string(),
+            self.orphaned_at
+                .map(|at| time::localize(&at).to_rfc3339())
+                .unwrap_or_default(),
+        ]
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PermissionSummaryOAS {
+    #[serde(flatten)]
+    pub permission: PermissionOAS,
+    /// How many users of the tenant are granted the permission directly.
+    #[schema(example = 3)]
+    pub users: u64,
+}
+
 #[derive(Serialize, ToSchema, IntoResponses)]
 #[serde(rename_all = "camelCase")]
 #[response(status = 200, description = "Ok")]
@@ -47,11 +106,107 @@ pub struct PermissionPaginationResponse {
     #[schema(example = "1")]
     pub page: u64,
     #[schema()]
-    pub data: Vec<PermissionOAS>,
+    pub data: Vec<PermissionSummaryOAS>,
+}
+
+impl From<PermissionPaginationResponse> for HttpResponse {
+    fn from(response: PermissionPaginationResponse) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionSourceType {
+    Direct,
+    Role,
+    Inherited,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PermissionSourceOAS {
+    #[serde(rename = "type")]
+    #[schema(example = "inherited")]
+    pub kind: PermissionSourceType,
+    /// The role the permission was granted to, empty for direct grants.
+    #[schema()]
+    pub role: Option<RoleOAS>,
+    /// The role assigned to the user that `role` sits below, for inherited
+    /// permissions only.
+    #[schema()]
+    pub through: Option<RoleOAS>,
+}
+
+impl From<&Source> for PermissionSourceOAS {
+    fn from(source: &Source) -> Self {
+        match source {
+            Source::Direct => Self {
+                kind: PermissionSourceType::Direct,
+                role: None,
+                through: None,
+            },
+            Source::Role(role) => Self {
+                kind: PermissionSourceType::Role,
+                role: Some(role.into()),
+                through: None,
+            },
+            Source::Inherited { role, through } => Self {
+                kind: PermissionSourceType::Inherited,
+                role: Some(role.into()),
+                through: Some(through.into()),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct EffectivePermissionOAS {
+    #[schema()]
+    pub permission: PermissionOAS,
+    /// Everywhere the user holds the permission from.
+    #[schema()]
+    pub sources: Vec<PermissionSourceOAS>,
+}
+
+impl From<&Effective> for EffectivePermissionOAS {
+    fn from(effective: &Effective) -> Self {
+        Self {
+            permission: PermissionOAS::from(&effective.permission),
+            sources: effective.sources.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct EffectivePermissionListResponse {
+    #[schema()]
+    pub data: Vec<EffectivePermissionOAS>,
+}
+
+impl From<EffectivePermissionListResponse> for HttpResponse {
+    fn from(response: EffectivePermissionListResponse) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct PermissionCheckOAS {
+    #[schema(example = "UPDATE_USER")]
+    pub permission: String,
+    #[schema(example = false)]
+    pub allowed: bool,
+    /// Why the permission is allowed or denied.
+    #[schema(example = "john is not granted UPDATE_USER, directly or through any of their roles")]
+    pub reason: String,
+    /// Everywhere the user holds the permission from, empty when they do not.
+    #[schema()]
+    pub sources: Vec<PermissionSourceOAS>,
 }
 
-impl Into<HttpResponse> for PermissionPaginationResponse {
-    fn into(self) -> HttpResponse {
-        HttpResponse::Ok().json(self)
+impl From<PermissionCheckOAS> for HttpResponse {
+    fn from(response: PermissionCheckOAS) -> Self {
+        HttpResponse::Ok().json(response)
     }
 }
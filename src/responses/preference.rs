@@ -0,0 +1,44 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+use utoipa::{IntoResponses, ToSchema};
+
+use crate::common::time;
+use crate::models::{preferences, users, Timestamp};
+use crate::requests::preference::{DateFormat, Notifications};
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct PreferenceOAS {
+    #[schema(example = "Asia/Jakarta")]
+    pub timezone: String,
+    /// Language of messages and mails, `Accept-Language` is used when empty.
+    #[schema(example = "id")]
+    pub locale: Option<String>,
+    #[schema(example = "DD/MM/YYYY")]
+    pub date_format: DateFormat,
+    #[schema()]
+    pub notifications: Notifications,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub updated_at: Timestamp,
+}
+
+impl From<PreferenceOAS> for HttpResponse {
+    fn from(response: PreferenceOAS) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
+
+impl From<(&users::Model, &preferences::Model)> for PreferenceOAS {
+    fn from((user, preferences): (&users::Model, &preferences::Model)) -> Self {
+        Self {
+            timezone: preferences.timezone.clone(),
+            locale: user.locale.clone(),
+            date_format: DateFormat::of(&preferences.date_format),
+            notifications: serde_json::from_value(preferences.notifications.clone())
+                .unwrap_or_default(),
+            updated_at: preferences.updated_at,
+        }
+    }
+}
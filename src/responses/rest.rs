@@ -1,14 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Debug, Display, Formatter, Result};
 
 use actix_web::http::StatusCode;
 use actix_web::{body::BoxBody, HttpRequest};
 use actix_web::{HttpResponse, Responder, ResponseError};
 use serde::Serialize;
+use utoipa::openapi::{ContentBuilder, Ref, RefOr, Response, ResponseBuilder};
 use utoipa::{IntoResponses, ToSchema};
 
 use crate::models::Id;
 
+use super::AppError;
+
 macro_rules! response {
     ($name:tt, $code:tt, $status:tt, $body:tt) => {
         #[derive(Serialize, ToSchema, IntoResponses)]
@@ -33,9 +36,9 @@ macro_rules! response {
             }
         }
 
-        impl Into<HttpResponse> for $name {
-            fn into(self) -> HttpResponse {
-                self.response()
+        impl From<$name> for HttpResponse {
+            fn from(response: $name) -> Self {
+                response.response()
             }
         }
 
@@ -76,37 +79,99 @@ response!(CreatedWithId, 201, Created, {
     pub message: String,
 });
 
-response!(BadRequest, 400, BadRequest, {
-    #[schema()]
+macro_rules! problem {
+    ($name:tt, $code:tt, $description:tt, $body:tt, $error:expr) => {
+        #[derive(Clone)]
+        pub struct $name $body
+
+        impl $name {
+            pub fn response(&self) -> HttpResponse {
+                AppError::from(self.clone()).error_response()
+            }
+        }
+
+        impl From<$name> for AppError {
+            fn from(value: $name) -> Self {
+                ($error)(value)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+                write!(f, "{}", AppError::from(self.clone()))
+            }
+        }
+
+        impl Debug for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+                write!(f, "{:?}", AppError::from(self.clone()))
+            }
+        }
+
+        impl From<$name> for HttpResponse {
+            fn from(response: $name) -> Self {
+                response.response()
+            }
+        }
+
+        impl ResponseError for $name {
+            fn status_code(&self) -> StatusCode {
+                AppError::from(self.clone()).status_code()
+            }
+
+            fn error_response(&self) -> HttpResponse {
+                self.response()
+            }
+        }
+
+        impl Responder for $name {
+            type Body = BoxBody;
+
+            fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+                self.response()
+            }
+        }
+
+        impl IntoResponses for $name {
+            fn responses() -> BTreeMap<String, RefOr<Response>> {
+                let content = ContentBuilder::new()
+                    .schema(Ref::from_schema_name("Problem"))
+                    .build();
+                let response = ResponseBuilder::new()
+                    .description($description)
+                    .content("application/problem+json", content)
+                    .build();
+
+                BTreeMap::from([($code.to_string(), response.into())])
+            }
+        }
+    };
+}
+
+problem!(BadRequest, 400, "Bad Request", {
     pub message: String,
-});
+}, |e: BadRequest| AppError::BadRequest(e.message));
 
-response!(Unauthorized, 401, Unauthorized, {
-    #[schema()]
+problem!(Unauthorized, 401, "Unauthorized", {
     pub message: String,
-});
+}, |e: Unauthorized| AppError::Unauthorized(e.message));
 
-response!(Forbidden, 403, Forbidden, {
-    #[schema()]
+problem!(Forbidden, 403, "Forbidden", {
     pub message: String,
-});
+}, |e: Forbidden| AppError::Forbidden(e.message));
 
-response!(NotFound, 404, NotFound, {
-    #[schema()]
+problem!(NotFound, 404, "Not Found", {
     pub message: String,
-});
+}, |e: NotFound| AppError::NotFound(e.message));
 
-response!(Conflict, 409, Conflict, {
-    #[schema()]
+problem!(Conflict, 409, "Conflict", {
     pub message: String,
-});
+}, |e: Conflict| AppError::Conflict(e.message));
 
-response!(UnprocessableEntity, 422, UnprocessableEntity, {
-    #[schema()]
-    pub errors: HashMap<&'static str, Vec<&'static str>>,
-});
+problem!(UnprocessableEntity, 422, "Unprocessable Entity", {
+    pub errors: HashMap<String, Vec<String>>,
+}, |e: UnprocessableEntity| AppError::Validation(e.errors));
 
-response!(InternalServerError, 500, InternalServerError, {
-    #[schema()]
+problem!(InternalServerError, 500, "Internal Server Error", {
     pub message: String,
-});
+}, |e: InternalServerError| AppError::Internal(e.message));
@@ -4,6 +4,7 @@ use utoipa::{IntoResponses, ToSchema};
 
 use crate::models::roles::Model;
 use crate::models::Id;
+use crate::sheet::Record;
 
 #[derive(Serialize, ToSchema, IntoResponses)]
 #[response(status = 200, description = "Ok")]
@@ -14,11 +15,15 @@ pub struct RoleOAS {
     pub code: String,
     #[schema(example = "superuser")]
     pub name: String,
+    /// Whether the application relies on the role, which then cannot be
+    /// renamed or deleted.
+    #[schema(example = true)]
+    pub system: bool,
 }
 
-impl Into<HttpResponse> for RoleOAS {
-    fn into(self) -> HttpResponse {
-        HttpResponse::Ok().json(self)
+impl From<RoleOAS> for HttpResponse {
+    fn from(response: RoleOAS) -> Self {
+        HttpResponse::Ok().json(response)
     }
 }
 
@@ -31,13 +36,23 @@ impl From<Model> for RoleOAS {
 impl From<&Model> for RoleOAS {
     fn from(role: &Model) -> Self {
         Self {
-            id: role.id.clone(),
+            id: role.id,
             code: role.code.clone(),
             name: role.name.clone(),
+            system: role.system,
         }
     }
 }
 
+#[derive(Serialize, ToSchema)]
+pub struct RoleSummaryOAS {
+    #[serde(flatten)]
+    pub role: RoleOAS,
+    /// How many users are assigned the role directly.
+    #[schema(example = 12)]
+    pub users: u64,
+}
+
 #[derive(Serialize, ToSchema, IntoResponses)]
 #[serde(rename_all = "camelCase")]
 #[response(status = 200, description = "Ok")]
@@ -46,12 +61,50 @@ pub struct RolePaginationResponse {
     pub total: u64,
     #[schema(example = "1")]
     pub page: u64,
+    #[schema()]
+    pub data: Vec<RoleSummaryOAS>,
+}
+
+impl From<RolePaginationResponse> for HttpResponse {
+    fn from(response: RolePaginationResponse) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct RoleListResponse {
     #[schema()]
     pub data: Vec<RoleOAS>,
 }
 
-impl Into<HttpResponse> for RolePaginationResponse {
-    fn into(self) -> HttpResponse {
-        HttpResponse::Ok().json(self)
+impl From<RoleListResponse> for HttpResponse {
+    fn from(response: RoleListResponse) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RoleExportRecord {
+    #[schema()]
+    pub id: Id,
+    #[schema(example = "ADMIN")]
+    pub code: String,
+    #[schema(example = "admin")]
+    pub name: String,
+    #[schema(example = json!(["READ_USER"]))]
+    pub permissions: Vec<String>,
+}
+
+impl Record for RoleExportRecord {
+    const COLUMNS: &'static [&'static str] = &["id", "code", "name", "permissions"];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.code.clone(),
+            self.name.clone(),
+            self.permissions.join(", "),
+        ]
     }
 }
@@ -0,0 +1,48 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+use utoipa::{IntoResponses, ToSchema};
+
+use crate::common::time;
+use crate::models::{role_scopes, roles, Id, Timestamp};
+use crate::responses::role::RoleOAS;
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleScopeOAS {
+    #[schema()]
+    pub id: Id,
+    #[schema()]
+    pub role: RoleOAS,
+    #[schema(example = "course")]
+    pub scope_type: String,
+    #[schema()]
+    pub scope_id: Id,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub created_at: Timestamp,
+}
+
+impl From<&(role_scopes::Model, roles::Model)> for RoleScopeOAS {
+    fn from((scope, role): &(role_scopes::Model, roles::Model)) -> Self {
+        Self {
+            id: scope.id,
+            role: RoleOAS::from(role),
+            scope_type: scope.scope_type.clone(),
+            scope_id: scope.scope_id,
+            created_at: scope.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct RoleScopeListResponse {
+    #[schema()]
+    pub data: Vec<RoleScopeOAS>,
+}
+
+impl From<RoleScopeListResponse> for HttpResponse {
+    fn from(response: RoleScopeListResponse) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
@@ -0,0 +1,56 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+use utoipa::{IntoResponses, ToSchema};
+
+use crate::common::time;
+use crate::models::scheduled_tasks::Model;
+use crate::models::Timestamp;
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct ScheduledTaskOAS {
+    #[schema(example = "purge_expired_tokens")]
+    pub name: String,
+    #[schema(example = "0 * * * *")]
+    pub expression: String,
+    #[serde(with = "time::rfc3339::option")]
+    #[schema()]
+    pub next_run_at: Option<Timestamp>,
+    #[serde(with = "time::rfc3339::option")]
+    #[schema()]
+    pub last_run_at: Option<Timestamp>,
+    #[schema(example = "succeeded")]
+    pub last_status: Option<String>,
+    #[schema()]
+    pub last_error: Option<String>,
+    #[schema(example = "12")]
+    pub last_duration: Option<i64>,
+}
+
+impl From<&Model> for ScheduledTaskOAS {
+    fn from(task: &Model) -> Self {
+        Self {
+            name: task.name.clone(),
+            expression: task.expression.clone(),
+            next_run_at: task.next_run_at,
+            last_run_at: task.last_run_at,
+            last_status: task.last_status.clone(),
+            last_error: task.last_error.clone(),
+            last_duration: task.last_duration,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct ScheduledTaskListResponse {
+    #[schema()]
+    pub data: Vec<ScheduledTaskOAS>,
+}
+
+impl From<ScheduledTaskListResponse> for HttpResponse {
+    fn from(response: ScheduledTaskListResponse) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
@@ -0,0 +1,61 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+use utoipa::{IntoResponses, ToSchema};
+
+use crate::common::time;
+use crate::models::{tenants, Id, Timestamp};
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct TenantOAS {
+    #[schema()]
+    pub id: Id,
+    #[schema(example = "north-high")]
+    pub code: String,
+    #[schema(example = "North High School")]
+    pub name: String,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub created_at: Timestamp,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub updated_at: Timestamp,
+}
+
+impl From<TenantOAS> for HttpResponse {
+    fn from(response: TenantOAS) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
+
+impl From<tenants::Model> for TenantOAS {
+    fn from(tenant: tenants::Model) -> Self {
+        Self::from(&tenant)
+    }
+}
+
+impl From<&tenants::Model> for TenantOAS {
+    fn from(tenant: &tenants::Model) -> Self {
+        Self {
+            id: tenant.id,
+            code: tenant.code.clone(),
+            name: tenant.name.clone(),
+            created_at: tenant.created_at,
+            updated_at: tenant.updated_at,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct TenantListResponse {
+    #[schema()]
+    pub data: Vec<TenantOAS>,
+}
+
+impl From<TenantListResponse> for HttpResponse {
+    fn from(response: TenantListResponse) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
@@ -1,8 +1,15 @@
+use std::collections::HashMap;
+
 use actix_web::HttpResponse;
 use serde::Serialize;
 use utoipa::{IntoResponses, ToSchema};
 
+use crate::common::time;
 use crate::models::{permissions, roles, users, Id, Timestamp};
+use crate::requests::user::{AccountStatus, ImportMode};
+
+use crate::sheet::Record;
+use crate::storage::{self, Variant};
 
 use super::permission::PermissionOAS;
 use super::role::RoleOAS;
@@ -19,31 +26,59 @@ pub struct UserOAS {
     pub email: String,
     #[schema()]
     pub username: String,
+    #[serde(with = "time::rfc3339::option")]
     #[schema()]
     pub email_verified_at: Option<Timestamp>,
     #[schema()]
     pub profile_photo_id: Option<String>,
+    /// Signed, expiring download url of the profile photo.
+    #[schema()]
+    pub profile_photo_url: Option<String>,
+    #[schema()]
+    pub profile_photo_thumbnail_url: Option<String>,
+    #[schema(example = "en")]
+    pub locale: Option<String>,
+    #[schema(example = "active")]
+    pub status: AccountStatus,
+    /// Why the user was suspended or deactivated.
+    #[schema()]
+    pub status_reason: Option<String>,
+    #[serde(with = "time::rfc3339::option")]
+    #[schema()]
+    pub suspended_until: Option<Timestamp>,
     #[schema()]
     pub permissions: Vec<PermissionOAS>,
     #[schema()]
     pub roles: Vec<RoleOAS>,
 }
 
-impl Into<HttpResponse> for UserOAS {
-    fn into(self) -> HttpResponse {
-        HttpResponse::Ok().json(self)
+fn photo(user: &users::Model, variant: Variant) -> Option<String> {
+    user.profile_photo_id
+        .as_ref()
+        .map(|id| storage::url(id, variant))
+}
+
+impl From<UserOAS> for HttpResponse {
+    fn from(response: UserOAS) -> Self {
+        HttpResponse::Ok().json(response)
     }
 }
 
 impl From<&users::Model> for UserOAS {
     fn from(user: &users::Model) -> Self {
         Self {
-            id: user.id.clone(),
+            id: user.id,
             name: user.name.clone(),
             email: user.email.clone(),
             username: user.username.clone(),
             email_verified_at: user.email_verified_at,
             profile_photo_id: user.profile_photo_id.clone(),
+            profile_photo_url: photo(user, Variant::Original),
+            profile_photo_thumbnail_url: photo(user, Variant::Thumbnail),
+            locale: user.locale.clone(),
+            status: AccountStatus::of(user),
+            status_reason: user.status_reason.clone(),
+            suspended_until: user.suspended_until,
             permissions: vec![],
             roles: vec![],
         }
@@ -55,12 +90,18 @@ impl From<(&users::Model, Vec<permissions::Model>, Vec<roles::Model>)> for UserO
         (user, permissions, roles): (&users::Model, Vec<permissions::Model>, Vec<roles::Model>),
     ) -> Self {
         Self {
-            id: user.id.clone(),
+            id: user.id,
             name: user.name.clone(),
             email: user.email.clone(),
             username: user.username.clone(),
             email_verified_at: user.email_verified_at,
             profile_photo_id: user.profile_photo_id.clone(),
+            profile_photo_url: photo(user, Variant::Original),
+            profile_photo_thumbnail_url: photo(user, Variant::Thumbnail),
+            locale: user.locale.clone(),
+            status: AccountStatus::of(user),
+            status_reason: user.status_reason.clone(),
+            suspended_until: user.suspended_until,
             permissions: permissions.iter().map(PermissionOAS::from).collect(),
             roles: roles.iter().map(RoleOAS::from).collect(),
         }
@@ -87,8 +128,121 @@ pub struct UserPaginationResponse {
     pub data: Vec<UserOAS>,
 }
 
-impl Into<HttpResponse> for UserPaginationResponse {
-    fn into(self) -> HttpResponse {
-        HttpResponse::Ok().json(self)
+impl From<UserPaginationResponse> for HttpResponse {
+    fn from(response: UserPaginationResponse) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStatus {
+    /// Passed validation on a dry run.
+    Valid,
+    Imported,
+    Failed,
+    /// Valid, but not imported because another row failed.
+    Skipped,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserImportRowOAS {
+    /// Line of the row in the file, the header being line 1.
+    #[schema(example = "2")]
+    pub line: usize,
+    #[schema(example = "john@local.id")]
+    pub email: String,
+    #[schema(example = "john")]
+    pub username: String,
+    #[schema(example = "imported")]
+    pub status: ImportStatus,
+    /// Whether the password was left empty, so the user is mailed a link to choose one.
+    #[schema()]
+    pub password_generated: bool,
+    #[schema()]
+    pub id: Option<Id>,
+    #[schema()]
+    pub errors: HashMap<String, Vec<String>>,
+}
+
+#[derive(Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct UserImportResponse {
+    #[schema()]
+    pub dry_run: bool,
+    #[schema(example = "atomic")]
+    pub mode: ImportMode,
+    #[schema(example = "10")]
+    pub total: usize,
+    #[schema(example = "9")]
+    pub imported: usize,
+    #[schema(example = "1")]
+    pub failed: usize,
+    #[schema()]
+    pub rows: Vec<UserImportRowOAS>,
+}
+
+impl From<UserImportResponse> for HttpResponse {
+    fn from(response: UserImportResponse) -> Self {
+        HttpResponse::Ok().json(response)
+    }
+}
+
+/// A line of a user export. The columns are those of an import, so an
+/// export can be imported elsewhere.
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserExportRecord {
+    #[schema()]
+    pub id: Id,
+    #[schema(example = "john doe")]
+    pub name: String,
+    #[schema(example = "john@local.id")]
+    pub email: String,
+    #[schema(example = "john")]
+    pub username: String,
+    #[schema(example = json!(["ADMIN"]))]
+    pub roles: Vec<String>,
+    #[schema(example = json!(["READ_USER"]))]
+    pub permissions: Vec<String>,
+    #[schema(example = "en")]
+    pub locale: Option<String>,
+    #[serde(with = "time::rfc3339::option")]
+    #[schema()]
+    pub email_verified_at: Option<Timestamp>,
+    #[serde(with = "time::rfc3339")]
+    #[schema()]
+    pub created_at: Timestamp,
+}
+
+impl Record for UserExportRecord {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "name",
+        "email",
+        "username",
+        "roles",
+        "permissions",
+        "locale",
+        "email_verified_at",
+        "created_at",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.name.clone(),
+            self.email.clone(),
+            self.username.clone(),
+            self.roles.join(", "),
+            self.permissions.join(", "),
+            self.locale.clone().unwrap_or_default(),
+            self.email_verified_at
+                .map(|at| time::localize(&at).to_rfc3339())
+                .unwrap_or_default(),
+            time::localize(&self.created_at).to_rfc3339(),
+        ]
     }
 }
@@ -6,23 +6,84 @@ pub fn route(app: &mut ServiceConfig) -> &mut ServiceConfig {
     app.service(controllers::auth::login)
         .service(controllers::auth::authenticate)
         .service(controllers::auth::logout)
+        .service(controllers::auth::register)
+        .service(controllers::auth::verify_email)
+        .service(controllers::auth::set_password)
+        .service(controllers::auth::resend_verification)
+        // me
+        .service(controllers::me::show)
+        .service(controllers::me::update)
+        .service(controllers::me::preferences)
+        .service(controllers::me::update_preferences)
+        .service(controllers::me::update_password)
+        .service(controllers::me::delete)
         // user
         .service(controllers::user::paginate)
         .service(controllers::user::store)
+        .service(controllers::user::import)
+        .service(controllers::user::export)
         .service(controllers::user::show)
+        .service(controllers::user::effective_permissions)
+        .service(controllers::user::check_permission)
         .service(controllers::user::update_general_information)
-        .service(controllers::user::update_password)
+        .service(controllers::user::set_password)
         .service(controllers::user::delete)
+        .service(controllers::user::suspend)
+        .service(controllers::user::deactivate)
+        .service(controllers::user::reactivate)
+        .service(controllers::role_scope::all)
+        .service(controllers::role_scope::store)
+        .service(controllers::role_scope::delete)
+        // impersonation
+        .service(controllers::impersonation::start)
+        .service(controllers::impersonation::stop)
+        // invitation
+        .service(controllers::invitation::paginate)
+        .service(controllers::invitation::store)
+        .service(controllers::invitation::show)
+        .service(controllers::invitation::resend)
+        .service(controllers::invitation::revoke)
+        .service(controllers::invitation::preview)
+        .service(controllers::invitation::accept)
         // permission
         .service(controllers::permission::paginate)
         .service(controllers::permission::store)
+        .service(controllers::permission::export)
+        .service(controllers::permission::assign)
+        .service(controllers::permission::revoke)
         .service(controllers::permission::show)
         .service(controllers::permission::update)
         .service(controllers::permission::delete)
+        .service(controllers::permission::users)
         // role
         .service(controllers::role::paginate)
         .service(controllers::role::store)
+        .service(controllers::role::export)
+        .service(controllers::role::assign)
+        .service(controllers::role::revoke)
         .service(controllers::role::show)
         .service(controllers::role::update)
         .service(controllers::role::delete)
+        .service(controllers::role::parents)
+        .service(controllers::role::set_parents)
+        .service(controllers::role::users)
+        // job
+        .service(controllers::job::paginate)
+        .service(controllers::job::show)
+        .service(controllers::job::retry)
+        .service(controllers::job::delete)
+        // schedule
+        .service(controllers::schedule::all)
+        // file
+        .service(controllers::file::upload)
+        .service(controllers::file::show)
+        .service(controllers::file::download)
+        // audit
+        .service(controllers::audit::paginate)
+        .service(controllers::audit::history)
+        // tenant
+        .service(controllers::tenant::all)
+        .service(controllers::tenant::store)
+        .service(controllers::tenant::show)
+        .service(controllers::tenant::update)
 }
@@ -0,0 +1,205 @@
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// A classic five field cron expression: minute, hour, day of month,
+/// month and day of week, plus the `@hourly` style shorthands.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days: Vec<bool>,
+    months: Vec<bool>,
+    weekdays: Vec<bool>,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            expression => expression,
+        };
+
+        let fields = expression.split_whitespace().collect::<Vec<_>>();
+
+        if fields.len() != 5 {
+            return Err(format!("expected 5 fields in {}", expression));
+        }
+
+        let mut weekdays = field(fields[4], 0, 7)?;
+
+        // both 0 and 7 are sunday
+        weekdays[0] |= weekdays[7];
+        weekdays.truncate(7);
+
+        Ok(Self {
+            minutes: field(fields[0], 0, 59)?,
+            hours: field(fields[1], 0, 23)?,
+            days: field(fields[2], 1, 31)?,
+            months: field(fields[3], 1, 12)?,
+            weekdays,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+        })
+    }
+}
+
+impl Schedule {
+    /// The first matching minute strictly after `after`.
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut at = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = after + Duration::days(366 * 5);
+
+        while at <= limit {
+            if !self.months[at.month() as usize] {
+                let (year, month) = match at.month() {
+                    12 => (at.year() + 1, 1),
+                    month => (at.year(), month + 1),
+                };
+
+                at = NaiveDate::from_ymd_opt(year, month, 1)?.and_time(NaiveTime::MIN);
+                continue;
+            }
+
+            if !self.day_matches(at.date()) {
+                at = at.date().succ_opt()?.and_time(NaiveTime::MIN);
+                continue;
+            }
+
+            if !self.hours[at.hour() as usize] {
+                at = at.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+
+            if !self.minutes[at.minute() as usize] {
+                at += Duration::minutes(1);
+                continue;
+            }
+
+            return Some(at);
+        }
+
+        None
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = self.days[date.day() as usize];
+        let weekday = self.weekdays[date.weekday().num_days_from_sunday() as usize];
+
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+}
+
+fn field(field: &str, min: u32, max: u32) -> Result<Vec<bool>, String> {
+    let mut values = vec![false; max as usize + 1];
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            None => (part, 1),
+            Some((range, step)) => (range, number(step)?),
+        };
+
+        if step == 0 {
+            return Err(format!("invalid step in {}", part));
+        }
+
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                None if step > 1 => (number(range)?, max),
+                None => (number(range)?, number(range)?),
+                Some((start, end)) => (number(start)?, number(end)?),
+            },
+        };
+
+        if start < min || end > max || start > end {
+            return Err(format!("{} is out of range {}-{}", part, min, max));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            values[value as usize] = true;
+        }
+    }
+
+    Ok(values)
+}
+
+fn number(value: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} is not a valid number", value))
+}
+
+#[cfg(test)]
+pub mod test {
+    use std::str::FromStr;
+
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    use super::Schedule;
+
+    fn at(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    pub async fn schedule_must_find_next_minute() {
+        let schedule = Schedule::from_str("*/15 * * * *").unwrap();
+
+        assert_eq!(
+            schedule.next_after(at("2024-01-01 10:07")),
+            Some(at("2024-01-01 10:15"))
+        );
+        assert_eq!(
+            schedule.next_after(at("2024-01-01 10:45")),
+            Some(at("2024-01-01 11:00"))
+        );
+    }
+
+    #[test]
+    pub async fn schedule_must_roll_over_days_and_months() {
+        let schedule = Schedule::from_str("@monthly").unwrap();
+
+        assert_eq!(
+            schedule.next_after(at("2024-12-15 00:00")),
+            Some(at("2025-01-01 00:00"))
+        );
+    }
+
+    #[test]
+    pub async fn schedule_must_match_day_of_month_or_weekday() {
+        // 2024-01-05 is a friday, 2024-01-10 the next 10th
+        let schedule = Schedule::from_str("30 2 10 * 5").unwrap();
+        let friday = NaiveDate::from_ymd_opt(2024, 1, 5).unwrap();
+
+        assert_eq!(
+            schedule.next_after(at("2024-01-04 12:00")),
+            Some(friday.and_hms_opt(2, 30, 0).unwrap())
+        );
+        assert_eq!(
+            schedule.next_after(at("2024-01-05 03:00")),
+            Some(at("2024-01-10 02:30"))
+        );
+    }
+
+    #[test]
+    pub async fn schedule_must_reject_invalid_expression() {
+        assert!(Schedule::from_str("* * * *").is_err());
+        assert!(Schedule::from_str("60 * * * *").is_err());
+        assert!(Schedule::from_str("*/0 * * * *").is_err());
+        assert!(Schedule::from_str("5-1 * * * *").is_err());
+    }
+}
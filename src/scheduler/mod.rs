@@ -0,0 +1,131 @@
+pub mod cron;
+pub mod tasks;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::time::Instant;
+
+use sea_orm::prelude::*;
+
+use crate::common::{hash, log, time};
+use crate::dao;
+
+pub use cron::Schedule;
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+type Handler = Box<
+    dyn Fn(DatabaseConnection) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>
+        + Send
+        + Sync,
+>;
+
+const INTERVAL: u64 = 1000 * 30;
+
+/// A periodic maintenance task, at most one replica runs it per tick.
+pub trait Task: 'static {
+    const NAME: &'static str;
+    const SCHEDULE: &'static str;
+
+    fn run(db: DatabaseConnection) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+struct Entry {
+    name: &'static str,
+    expression: &'static str,
+    schedule: Schedule,
+    handler: Handler,
+}
+
+pub struct Scheduler {
+    db: DatabaseConnection,
+    entries: Vec<Entry>,
+}
+
+impl Scheduler {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            entries: vec![],
+        }
+    }
+
+    /// Register `T`, panics on an invalid cron expression since tasks
+    /// are declared in code.
+    pub fn register<T: Task>(mut self) -> Self {
+        let schedule = Schedule::from_str(T::SCHEDULE)
+            .unwrap_or_else(|e| panic!("invalid schedule for {}: {}", T::NAME, e));
+
+        self.entries.push(Entry {
+            name: T::NAME,
+            expression: T::SCHEDULE,
+            schedule,
+            handler: Box::new(|db| Box::pin(T::run(db))),
+        });
+
+        self
+    }
+
+    pub async fn run(self) {
+        for entry in &self.entries {
+            let next = entry.schedule.next_after(time::now());
+
+            if let Err(e) = dao::schedule::sync(&self.db, entry.name, entry.expression, next).await
+            {
+                log::error!(Scheduler, "{}", e);
+            }
+        }
+
+        loop {
+            for entry in &self.entries {
+                if let Err(e) = self.tick(entry).await {
+                    log::error!(Scheduler, "{}: {}", entry.name, e);
+                }
+            }
+
+            actix_web::rt::time::sleep(std::time::Duration::from_millis(INTERVAL)).await;
+        }
+    }
+
+    async fn tick(&self, entry: &Entry) -> Result<(), DbErr> {
+        let now = time::now();
+        let next = entry.schedule.next_after(now);
+        let claim = match dao::schedule::claim(&self.db, entry.name, lock(entry.name), next).await?
+        {
+            None => return Ok(()),
+            Some(claim) => claim,
+        };
+
+        let started = Instant::now();
+        let result = (entry.handler)(self.db.clone()).await;
+        let duration = started.elapsed().as_millis() as i64;
+
+        if let Err(e) = &result {
+            log::error!(Scheduler, "{} failed: {}", entry.name, e);
+        }
+
+        dao::schedule::finish(&self.db, claim, now, duration, result.err()).await?;
+
+        Ok(())
+    }
+}
+
+/// The built in maintenance tasks.
+pub fn scheduler(db: DatabaseConnection) -> Scheduler {
+    Scheduler::new(db)
+        .register::<tasks::PurgeExpiredTokens>()
+        .register::<tasks::PurgeTrashedUsers>()
+        .register::<tasks::PurgeCompletedJobs>()
+        .register::<tasks::PurgeDeadJobs>()
+        .register::<tasks::LapseGrants>()
+}
+
+/// Advisory lock key of a task, stable across replicas.
+fn lock(name: &str) -> i64 {
+    let mut key = [0u8; 8];
+
+    key.copy_from_slice(&hash::make("scheduler", name).as_bytes()[..8]);
+
+    i64::from_be_bytes(key)
+}
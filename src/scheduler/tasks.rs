@@ -0,0 +1,115 @@
+use chrono::Duration;
+use sea_orm::prelude::*;
+
+use crate::common::{log, time};
+use crate::dao;
+use crate::models::{jobs, tokens, users};
+use crate::queue;
+
+use super::{Error, Task};
+
+/// Trashed users are kept this long before being removed for good.
+const TRASHED_USERS_RETENTION: i64 = 30;
+/// Completed jobs are kept this long for inspection.
+const COMPLETED_JOBS_RETENTION: i64 = 7;
+/// Dead jobs are kept this long to be looked into or retried.
+const DEAD_JOBS_RETENTION: i64 = 30;
+
+pub struct PurgeExpiredTokens;
+
+impl Task for PurgeExpiredTokens {
+    const NAME: &'static str = "purge_expired_tokens";
+    const SCHEDULE: &'static str = "0 * * * *";
+
+    async fn run(db: DatabaseConnection) -> Result<(), Error> {
+        let deleted = tokens::Entity::delete_many()
+            .filter(tokens::Column::ExpiredAt.lt(time::now()))
+            .exec(&db)
+            .await?;
+
+        log::info!(
+            PurgeExpiredTokens,
+            "{} tokens purged",
+            deleted.rows_affected
+        );
+
+        Ok(())
+    }
+}
+
+pub struct PurgeTrashedUsers;
+
+impl Task for PurgeTrashedUsers {
+    const NAME: &'static str = "purge_trashed_users";
+    const SCHEDULE: &'static str = "30 2 * * *";
+
+    async fn run(db: DatabaseConnection) -> Result<(), Error> {
+        let before = time::now() - Duration::days(TRASHED_USERS_RETENTION);
+        let deleted = users::Entity::delete_many()
+            .filter(users::Column::DeletedAt.lt(before))
+            .exec(&db)
+            .await?;
+
+        log::info!(PurgeTrashedUsers, "{} users purged", deleted.rows_affected);
+
+        Ok(())
+    }
+}
+
+pub struct PurgeCompletedJobs;
+
+impl Task for PurgeCompletedJobs {
+    const NAME: &'static str = "purge_completed_jobs";
+    const SCHEDULE: &'static str = "0 3 * * *";
+
+    async fn run(db: DatabaseConnection) -> Result<(), Error> {
+        let before = time::now() - Duration::days(COMPLETED_JOBS_RETENTION);
+        let deleted = jobs::Entity::delete_many()
+            .filter(jobs::Column::Status.eq(queue::Status::Completed.as_str()))
+            .filter(jobs::Column::CompletedAt.lt(before))
+            .exec(&db)
+            .await?;
+
+        log::info!(PurgeCompletedJobs, "{} jobs purged", deleted.rows_affected);
+
+        Ok(())
+    }
+}
+
+pub struct PurgeDeadJobs;
+
+impl Task for PurgeDeadJobs {
+    const NAME: &'static str = "purge_dead_jobs";
+    const SCHEDULE: &'static str = "15 3 * * *";
+
+    async fn run(db: DatabaseConnection) -> Result<(), Error> {
+        let before = time::now() - Duration::days(DEAD_JOBS_RETENTION);
+        let deleted = jobs::Entity::delete_many()
+            .filter(jobs::Column::Status.eq(queue::Status::Dead.as_str()))
+            .filter(jobs::Column::UpdatedAt.lt(before))
+            .exec(&db)
+            .await?;
+
+        log::info!(PurgeDeadJobs, "{} jobs purged", deleted.rows_affected);
+
+        Ok(())
+    }
+}
+
+/// Removes role and permission grants past their expiry, recording in the
+/// audit log what each user lost. They stop counting as soon as they expire
+/// regardless, this only tidies up after them.
+pub struct LapseGrants;
+
+impl Task for LapseGrants {
+    const NAME: &'static str = "lapse_grants";
+    const SCHEDULE: &'static str = "*/15 * * * *";
+
+    async fn run(db: DatabaseConnection) -> Result<(), Error> {
+        let lapsed = dao::grant::lapse(&db, time::now()).await?;
+
+        log::info!(LapseGrants, "grants of {} users lapsed", lapsed);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use actix_web::HttpResponse;
+use sea_orm::{prelude::*, QueryOrder, QuerySelect, Select};
+use sea_query::Condition;
+
+use crate::models::{audit_logs, Id};
+use crate::requests::audit::{AuditEntity, AuditFilterRequest};
+use crate::requests::PaginationRequest;
+use crate::responses::audit::{AuditLogOAS, AuditLogPaginationResponse};
+use crate::responses::AppError;
+use crate::tenant::Tenanted;
+
+async fn paginated(
+    db: &DatabaseConnection,
+    mut query: Select<audit_logs::Entity>,
+    request: PaginationRequest<audit_logs::Column>,
+) -> Result<HttpResponse, AppError> {
+    if let Some(search) = request.search() {
+        query = query.filter(
+            Condition::any()
+                .add(audit_logs::Column::Ip.like(search.clone()))
+                .add(audit_logs::Column::UserAgent.like(search)),
+        );
+    }
+
+    let total = query.clone().count(db).await?;
+    let logs = query
+        .limit(request.limit())
+        .offset(request.offset())
+        // newest first unless asked otherwise
+        .order_by(
+            request.order(audit_logs::Column::CreatedAt),
+            request
+                .sort
+                .map(Into::into)
+                .unwrap_or(sea_query::Order::Desc),
+        )
+        .all(db)
+        .await?;
+
+    Ok(AuditLogPaginationResponse {
+        total,
+        page: total / request.limit(),
+        data: logs.iter().map(AuditLogOAS::from).collect(),
+    }
+    .into())
+}
+
+pub async fn paginate(
+    db: &DatabaseConnection,
+    request: PaginationRequest<audit_logs::Column>,
+    filter: AuditFilterRequest,
+) -> Result<HttpResponse, AppError> {
+    let mut query = audit_logs::Entity::find().tenanted();
+
+    if let Some(actor_id) = filter.actor_id {
+        query = query.filter(
+            Condition::any()
+                .add(audit_logs::Column::ActorId.eq(actor_id))
+                .add(audit_logs::Column::ImpersonatorId.eq(actor_id)),
+        );
+    }
+
+    if let Some(action) = filter.action {
+        query = query.filter(audit_logs::Column::Action.eq(action.as_str()));
+    }
+
+    if let Some(entity) = filter.entity_type {
+        query = query.filter(audit_logs::Column::EntityType.eq(entity.as_str()));
+    }
+
+    if let Some(entity_id) = filter.entity_id {
+        query = query.filter(audit_logs::Column::EntityId.eq(entity_id));
+    }
+
+    if let Some(from) = filter.from {
+        query = query.filter(audit_logs::Column::CreatedAt.gte(from));
+    }
+
+    if let Some(to) = filter.to {
+        query = query.filter(audit_logs::Column::CreatedAt.lt(to));
+    }
+
+    paginated(db, query, request).await
+}
+
+/// Every recorded change of a single entity.
+pub async fn history<I: Into<Id>>(
+    db: &DatabaseConnection,
+    entity: AuditEntity,
+    id: I,
+    request: PaginationRequest<audit_logs::Column>,
+) -> Result<HttpResponse, AppError> {
+    let id: Id = id.into();
+    let query = audit_logs::Entity::find()
+        .tenanted()
+        .filter(audit_logs::Column::EntityType.eq(entity.as_str()))
+        .filter(audit_logs::Column::EntityId.eq(id));
+
+    paginated(db, query, request).await
+}
@@ -1,101 +1,401 @@
 use std::collections::HashMap;
 
 use actix_web::HttpResponse;
-use sea_orm::DatabaseConnection;
-use serde_json::json;
+use sea_orm::{prelude::*, DatabaseConnection};
 
-use crate::common::hash::Hash;
-use crate::common::{base58, hash, log};
+use crate::captcha::Verifier;
+use crate::common::base58;
+use crate::common::hash::{self, Hash};
+use crate::common::{env, log, sign, time};
 use crate::dao::{self, user};
-use crate::middlewares::auth::Auth;
-use crate::requests::auth::Login;
+use crate::i18n::t;
+use crate::mail::SendMail;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::{blocked, Auth, Authenticated};
+use crate::models::{roles, users, Id};
+use crate::requests::auth::{
+    Login, PasswordLinkRequest, Register, ResendVerification, VerifyEmailRequest,
+};
+use crate::requests::user::UserSetPasswordRequest;
 use crate::responses::user::UserOAS;
+use crate::responses::{self, AppError, Ok};
+use crate::tenant::Tenanted;
+use crate::validation::{self, invalid};
 
-pub async fn login(db: &DatabaseConnection, request: Login) -> HttpResponse {
-    let mut validation = HashMap::new();
-    let email_or_username = request.email_or_username.trim().to_lowercase();
-    let password = request.password;
-    let mut user = None;
-
-    if email_or_username.is_empty() {
-        validation.insert(
-            "email_or_username",
-            vec!["field email or username is required"],
-        );
-    } else {
-        let mut errors = vec![];
-
-        user = user::find_by_email_or_username(db, email_or_username.clone()).await;
+/// Who may sign up through `POST /register`, from `REGISTRATION`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Registration {
+    Disabled,
+    Open,
+    /// Only emails of the comma separated `REGISTRATION_DOMAINS`.
+    Domains(Vec<String>),
+}
 
-        if user.is_none() {
-            errors.push("email or username doesn't exist");
+impl Registration {
+    pub fn configured() -> Self {
+        match env::var("REGISTRATION", "disabled".to_string()).as_str() {
+            "open" => Self::Open,
+            "domains" => Self::Domains(
+                env::var("REGISTRATION_DOMAINS", String::new())
+                    .split(',')
+                    .map(|domain| domain.trim().trim_start_matches('@').to_lowercase())
+                    .filter(|domain| !domain.is_empty())
+                    .collect(),
+            ),
+            _ => Self::Disabled,
         }
+    }
 
-        if !errors.is_empty() {
-            validation.insert("email_or_username", errors);
+    pub fn allows(&self, email: &str) -> bool {
+        match self {
+            Self::Disabled => false,
+            Self::Open => true,
+            Self::Domains(domains) => match email.trim().rsplit_once('@') {
+                Some((_, domain)) => domains.iter().any(|d| d.eq_ignore_ascii_case(domain)),
+                None => false,
+            },
         }
     }
+}
 
-    if password.is_empty() {
-        validation.insert("password", vec!["password field is required"]);
-    } else {
-        let mut errors = vec![];
+/// Whether self registered users must verify their email before signing in.
+fn verification_required() -> bool {
+    env::var("REGISTRATION_VERIFY_EMAIL", true)
+}
 
-        if let Some((user, _, _)) = user.clone() {
-            let hashed = Hash::from(user.password);
+fn verification_signature<I: std::fmt::Display>(id: I, email: &str, expires: u64) -> String {
+    format!("verification:{}:{}:{}", id, email, expires)
+}
 
-            if !hash::verify(hashed, user.id.to_string(), password) {
-                errors.push("wrong password");
-            }
-        }
+/// The signed link confirming the current email of `user`, so changing
+/// the email voids it.
+pub fn verification_url(user: &users::Model) -> String {
+    let expires = time::unix() / 1000 + env::var("VERIFICATION_TTL", 24 * 3600u64).max(1);
 
-        if !errors.is_empty() {
-            validation.insert("password", errors);
-        }
+    format!(
+        "{}/register/verify/{}?expires={}&signature={}",
+        env::var("APP_URL", "http://localhost:8000".to_string()).trim_end_matches('/'),
+        user.id,
+        expires,
+        sign::sign(verification_signature(user.id, &user.email, expires)),
+    )
+}
+
+fn password_signature<I: std::fmt::Display>(id: I, password: &str, expires: u64) -> String {
+    format!("password:{}:{}:{}", id, password, expires)
+}
+
+/// The signed link letting `user` choose their password, which is void
+/// once used as it signs the hash of the current one.
+pub fn password_url(user: &users::Model) -> String {
+    let expires = time::unix() / 1000 + env::var("PASSWORD_LINK_TTL", 7 * 24 * 3600u64).max(1);
+
+    format!(
+        "{}/password/{}?expires={}&signature={}",
+        env::var("APP_URL", "http://localhost:8000".to_string()).trim_end_matches('/'),
+        user.id,
+        expires,
+        sign::sign(password_signature(user.id, &user.password, expires)),
+    )
+}
+
+/// Whether `link` was signed for the current password of `user` and has not
+/// expired yet.
+fn password_link_verifies(user: &users::Model, link: &PasswordLinkRequest) -> bool {
+    link.expires >= time::unix() / 1000
+        && sign::verify(
+            password_signature(user.id, &user.password, link.expires),
+            &link.signature,
+        )
+}
+
+async fn send_verification(db: &DatabaseConnection, user: &users::Model) {
+    let mail = SendMail::new(user, "verify_email")
+        .param("url", verification_url(user))
+        .dispatch(db)
+        .await;
+
+    if let Err(e) = mail {
+        log::error!(send_verification, "{}", e);
     }
+}
 
-    if !validation.is_empty() {
-        return HttpResponse::UnprocessableEntity().json(json!({
-            "errors": validation,
-        }));
+/// Fail unless the CAPTCHA `token` passes `verifier`, when it asks for one.
+pub async fn challenge<V: Verifier>(
+    verifier: &V,
+    token: Option<&str>,
+    ip: Option<&str>,
+) -> Result<(), AppError> {
+    if !verifier.enabled() {
+        return Ok(());
     }
 
-    let (user, permissions, roles) = user.unwrap();
+    let token = match token.map(str::trim) {
+        Some(token) if !token.is_empty() => token,
+        _ => return Err(invalid("captcha", "validation.required", vec![])),
+    };
 
-    match dao::auth::generate(db, &user, None).await {
+    match verifier.verify(token, ip).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(invalid("captcha", "validation.captcha", vec![])),
         Err(e) => {
-            log::error!(services::auth::login, "{}", e);
+            log::error!(challenge, "{}", e);
 
-            HttpResponse::InternalServerError().json(json!({
-                "message": e.to_string(),
-            }))
+            Err(AppError::Internal(t!("errors.unexpected")))
         }
-        Ok(token) => {
-            let response = json!({
-                "token": base58::to_string(token.id.as_bytes()),
-                "user": UserOAS::from((user, permissions, roles)),
-            });
+    }
+}
 
-            HttpResponse::Ok().json(response)
-        }
+pub async fn login(db: &DatabaseConnection, request: Login) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
+
+    let (user, permissions, roles) = user::find_by_email_or_username(db, request.email_or_username)
+        .await
+        .ok_or_else(|| {
+            AppError::validation(HashMap::from([(
+                "email_or_username",
+                vec![t!("auth.unknown_user")],
+            )]))
+        })?;
+
+    if !hash::verify(
+        Hash::from(user.password.clone()),
+        user.id.to_string(),
+        request.password,
+    ) {
+        return Err(AppError::validation(HashMap::from([(
+            "password",
+            vec![t!("auth.wrong_password")],
+        )])));
+    }
+
+    if user.registered_at.is_some() && user.email_verified_at.is_none() && verification_required() {
+        return Err(AppError::Forbidden(t!("auth.unverified")));
+    }
+
+    if let Some(message) = blocked(&user) {
+        return Err(AppError::Forbidden(message));
     }
+
+    let token = dao::auth::generate(db, &user, None).await?;
+
+    Ok(HttpResponse::Ok().json(responses::auth::Login {
+        token: base58::to_string(token.id.as_bytes()),
+        user: UserOAS::from((user, permissions, roles)),
+    }))
 }
 
-pub async fn authenticate(auth: Auth) -> HttpResponse {
-    HttpResponse::Ok().json(json!({
-        "user": UserOAS::from((auth.user, auth.permissions, auth.roles)),
+pub async fn authenticate(auth: Auth) -> Result<HttpResponse, AppError> {
+    Ok(HttpResponse::Ok().json(responses::auth::Authenticated {
+        user: UserOAS::from((auth.user, auth.permissions, auth.roles)),
     }))
 }
 
-pub async fn logout(db: &DatabaseConnection, auth: Auth) -> HttpResponse {
-    match dao::auth::delete(db, auth.user.id).await {
-        Err(e) => {
-            log::error!(services::auth::logout, "{}", e);
+pub async fn logout(db: &DatabaseConnection, auth: Auth) -> Result<HttpResponse, AppError> {
+    dao::auth::delete(db, auth.user.id).await?;
+
+    Ok(Ok {
+        message: t!("auth.logged_out", username = auth.user.username),
+    }
+    .into())
+}
+
+pub async fn register<V: Verifier>(
+    db: &DatabaseConnection,
+    captcha: &V,
+    actor: &Actor,
+    request: Register,
+) -> Result<HttpResponse, AppError> {
+    let registration = Registration::configured();
+
+    if registration == Registration::Disabled {
+        return Err(AppError::Forbidden(t!("auth.registration_closed")));
+    }
+
+    validation::validate(db, &request).await?;
+
+    if let Registration::Domains(domains) = &registration {
+        if !registration.allows(&request.email) {
+            return Err(invalid(
+                "email",
+                "validation.email_domain",
+                vec![("domains".to_string(), domains.join(", "))],
+            ));
+        }
+    }
+
+    challenge(captcha, request.captcha.as_deref(), actor.ip.as_deref()).await?;
+
+    let code = env::var("REGISTRATION_ROLE", "STUDENT".to_string());
+    let roles: Vec<Id> = roles::Entity::find()
+        .tenanted()
+        .filter(roles::Column::Code.eq(code.to_uppercase()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|role| role.id)
+        .collect();
+
+    if roles.is_empty() && !code.is_empty() {
+        log::info!(register, "role {} does not exist, none is assigned", code);
+    }
+
+    let (user, permissions, roles) = dao::user::register(db, actor, request, roles).await?;
+
+    let token = if verification_required() {
+        send_verification(db, &user).await;
+
+        None
+    } else {
+        let token = dao::auth::generate(db, &user, None).await?;
+
+        Some(base58::to_string(token.id.as_bytes()))
+    };
+
+    Ok(HttpResponse::Created().json(responses::auth::Registered {
+        token,
+        user: UserOAS::from((user, permissions, roles)),
+    }))
+}
+
+pub async fn verify_email<I: Into<Id>>(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    id: I,
+    request: VerifyEmailRequest,
+) -> Result<HttpResponse, AppError> {
+    let (user, _, _) = dao::user::find(db, id)
+        .await
+        .ok_or_else(|| AppError::Forbidden(t!("auth.invalid_verification")))?;
+
+    let signature = verification_signature(user.id, &user.email, request.expires);
+
+    if request.expires < time::unix() / 1000 || !sign::verify(signature, &request.signature) {
+        return Err(AppError::Forbidden(t!("auth.invalid_verification")));
+    }
+
+    if user.email_verified_at.is_none() {
+        dao::user::verify_email(db, actor, user).await?;
+    }
+
+    Ok(Ok {
+        message: t!("auth.email_verified"),
+    }
+    .into())
+}
+
+/// Set the password of a user through the mailed link, signing out all of
+/// their sessions.
+pub async fn set_password<I: Into<Id>>(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    actor: &Actor,
+    id: I,
+    link: PasswordLinkRequest,
+    request: UserSetPasswordRequest,
+) -> Result<HttpResponse, AppError> {
+    let (user, _, _) = dao::user::find(db, id)
+        .await
+        .ok_or_else(|| AppError::Forbidden(t!("auth.invalid_password_link")))?;
+
+    if !password_link_verifies(&user, &link) {
+        return Err(AppError::Forbidden(t!("auth.invalid_password_link")));
+    }
+
+    validation::validate(db, &request).await?;
+
+    let user = dao::user::update_password(db, actor, user, request.password).await?;
+
+    dao::auth::revoke(db, user.id, None).await?;
+    cache.forget(&user.id, None);
 
-            HttpResponse::InternalServerError().json(json!({
-                "message": e.to_string(),
-            }))
+    Ok(Ok {
+        message: t!("user.password_updated", username = user.username),
+    }
+    .into())
+}
+
+/// Mail a new verification link, answering the same whether or not the
+/// email belongs to someone so it cannot be used to probe for accounts.
+pub async fn resend_verification(
+    db: &DatabaseConnection,
+    request: ResendVerification,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
+
+    if let Some((user, _, _)) = user::find_by_email_or_username(db, request.email).await {
+        if user.registered_at.is_some() && user.email_verified_at.is_none() {
+            send_verification(db, &user).await;
         }
-        Ok(_) => HttpResponse::Ok().finish(),
+    }
+
+    Ok(Ok {
+        message: t!("auth.verification_sent"),
+    }
+    .into())
+}
+
+#[cfg(test)]
+pub mod test {
+    use crate::captcha::{Captcha, Fake};
+
+    use super::{challenge, Registration};
+
+    #[test]
+    pub async fn registration_must_only_allow_listed_domains() {
+        let registration = Registration::Domains(vec!["school.id".to_string()]);
+
+        assert!(registration.allows("john@school.id"));
+        assert!(registration.allows("john@SCHOOL.id"));
+        assert!(!registration.allows("john@mail.school.id"));
+        assert!(!registration.allows("john@local.id"));
+        assert!(!registration.allows("john"));
+        assert!(Registration::Open.allows("john@local.id"));
+        assert!(!Registration::Disabled.allows("john@local.id"));
+    }
+
+    #[test]
+    pub async fn challenge_must_ask_the_verifier() {
+        assert!(challenge(&Fake(true), Some("token"), None).await.is_ok());
+        assert!(challenge(&Fake(false), Some("token"), None).await.is_err());
+        assert!(challenge(&Fake(true), None, None).await.is_err());
+        assert!(challenge(&Fake(true), Some(" "), None).await.is_err());
+        assert!(challenge(&Captcha::Disabled, None, None).await.is_ok());
+    }
+
+    #[test]
+    pub async fn password_link_must_be_void_once_used() {
+        use uuid::Uuid;
+
+        use crate::common::hash;
+        use crate::middlewares::auth::test::auth;
+        use crate::models::users;
+        use crate::requests::auth::PasswordLinkRequest;
+
+        use super::{password_link_verifies, password_url};
+
+        std::env::set_var("APP_KEY", "secret");
+
+        let id = Uuid::new_v4();
+        let user = auth(id, id, &[]).user;
+        let url = password_url(&user);
+        let (expires, signature) = url
+            .split_once("?expires=")
+            .and_then(|(_, query)| query.split_once("&signature="))
+            .unwrap();
+        let link = PasswordLinkRequest {
+            expires: expires.parse().unwrap(),
+            signature: signature.to_string(),
+        };
+
+        assert!(url.contains(&format!("/password/{}", id)));
+        assert!(password_link_verifies(&user, &link));
+
+        let used = users::Model {
+            password: hash::make(id.to_string(), "Password123!").to_string(),
+            ..user
+        };
+
+        assert!(!password_link_verifies(&used, &link));
     }
 }
@@ -0,0 +1,177 @@
+use std::future::Future;
+
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::web::{self, Bytes};
+use actix_web::HttpResponse;
+use chrono_tz::Tz;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use sea_orm::{prelude::*, QuerySelect, Select};
+
+use crate::common::{env, log, time};
+use crate::i18n::{self, Locale};
+use crate::models::Id;
+use crate::responses::AppError;
+use crate::sheet::{ExportFormat, Record, Writer};
+use crate::tenant;
+
+/// An entity whose rows can be exported, turned into records a chunk at a
+/// time so related rows can be loaded for the whole chunk at once.
+pub trait Export: EntityTrait {
+    type Record: Record;
+
+    fn records(
+        db: &DatabaseConnection,
+        models: Vec<Self::Model>,
+    ) -> impl Future<Output = Result<Vec<Self::Record>, DbErr>>;
+}
+
+/// The tenant, locale and timezone of the request an export answers, as
+/// its chunks are loaded once the handler has returned and left them.
+#[derive(Clone, Debug, PartialEq)]
+struct Context {
+    tenant: Option<Id>,
+    locale: Locale,
+    timezone: Tz,
+}
+
+impl Context {
+    fn current() -> Self {
+        Self {
+            tenant: tenant::current(),
+            locale: i18n::current(),
+            timezone: time::timezone(),
+        }
+    }
+
+    async fn scope<F: Future>(self, f: F) -> F::Output {
+        let f = i18n::scope(self.locale, time::scope(self.timezone, f));
+
+        match self.tenant {
+            Some(tenant) => tenant::scope(tenant, f).await,
+            None => f.await,
+        }
+    }
+}
+
+struct State<E: EntityTrait> {
+    db: DatabaseConnection,
+    context: Context,
+    query: Select<E>,
+    chunk: u64,
+    writer: Option<Writer>,
+    offset: u64,
+    done: bool,
+}
+
+/// The bytes of the next chunk of rows, along with what is left to stream.
+async fn next<E: Export>(mut state: State<E>) -> Result<Option<(Bytes, State<E>)>, AppError> {
+    if state.done {
+        return Ok(None);
+    }
+
+    let models = state
+        .query
+        .clone()
+        .offset(state.offset)
+        .limit(state.chunk)
+        .all(&state.db)
+        .await?;
+
+    state.offset += models.len() as u64;
+    state.done = (models.len() as u64) < state.chunk;
+
+    let records = E::records(&state.db, models).await?;
+    let mut bytes = match state.writer.as_mut() {
+        Some(writer) => writer.write(&records).map_err(AppError::Internal)?,
+        None => vec![],
+    };
+
+    if state.done {
+        if let Some(writer) = state.writer.take() {
+            let finished = web::block(move || writer.finish())
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+
+            bytes.extend(finished.map_err(AppError::Internal)?);
+        }
+    }
+
+    Ok(Some((Bytes::from(bytes), state)))
+}
+
+/// Stream every row of `query` as a `{name}-{date}` attachment, loading
+/// `EXPORT_CHUNK_SIZE` rows at a time. `query` must be ordered uniquely
+/// for chunks not to overlap.
+pub fn stream<E: Export>(
+    db: &DatabaseConnection,
+    query: Select<E>,
+    format: ExportFormat,
+    name: &str,
+) -> Result<HttpResponse, AppError> {
+    let chunk = env::var("EXPORT_CHUNK_SIZE", 500u64);
+    let (writer, header) =
+        Writer::new::<E::Record>(format).map_err(|e| AppError::Internal(e.to_string()))?;
+    let state = State {
+        db: db.clone(),
+        context: Context::current(),
+        query,
+        chunk,
+        writer: Some(writer),
+        offset: 0,
+        done: false,
+    };
+
+    let body = stream::try_unfold(state, |state| state.context.clone().scope(next(state)));
+
+    // the status is sent by now, a failure can only cut the body short
+    let body = stream::iter([Ok(Bytes::from(header))])
+        .chain(body.inspect_err(|e| log::error!(stream, "{}", e)))
+        .map_err(actix_web::Error::from);
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!(
+                "{}-{}.{}",
+                name,
+                time::now().format("%Y%m%d%H%M%S"),
+                format.extension()
+            ))],
+        })
+        .streaming(body))
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono_tz::Tz;
+    use uuid::Uuid;
+
+    use crate::common::time;
+    use crate::i18n::{self, Locale};
+    use crate::tenant;
+
+    use super::Context;
+
+    #[test]
+    pub async fn context_must_be_entered_again_outside_of_the_request() {
+        let id = Uuid::new_v4();
+        let context = tenant::scope(
+            id,
+            i18n::scope(
+                Locale::Id,
+                time::scope(Tz::Asia__Jakarta, async { Context::current() }),
+            ),
+        )
+        .await;
+
+        assert_eq!(Context::current().tenant, None);
+        assert_eq!(
+            context.clone().scope(async { Context::current() }).await,
+            context
+        );
+        assert_eq!(context.tenant, Some(id));
+        assert_eq!(context.locale, Locale::Id);
+        assert_eq!(context.timezone, Tz::Asia__Jakarta);
+    }
+}
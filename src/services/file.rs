@@ -0,0 +1,176 @@
+use actix_multipart::Multipart;
+use actix_web::http::header::{self, ContentDisposition, DispositionParam, DispositionType};
+use actix_web::web;
+use actix_web::HttpResponse;
+use futures_util::TryStreamExt;
+use sea_orm::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::catalogue;
+use crate::common::{env, time};
+use crate::dao;
+use crate::i18n::t;
+use crate::middlewares::auth::Auth;
+use crate::models::{files, Id};
+use crate::requests::file::FileDownloadRequest;
+use crate::responses::file::FileOAS;
+use crate::responses::{AppError, BadRequest};
+use crate::storage::{self, image, Disk, Storage, Variant};
+use crate::tenant;
+use crate::validation::invalid;
+
+/// Code of the permission allowing to see files others uploaded.
+pub const PERMISSION: &str = catalogue::READ_FILE;
+
+/// Read the name and the content of the multipart field `file`, failing
+/// when it is missing, empty or larger than `max` bytes.
+pub async fn receive(mut payload: Multipart, max: usize) -> Result<(String, Vec<u8>), AppError> {
+    let mut upload = None;
+
+    while let Some(mut field) = payload.try_next().await.map_err(|e| BadRequest {
+        message: e.to_string(),
+    })? {
+        if field.name() != "file" {
+            continue;
+        }
+
+        let name = field
+            .content_disposition()
+            .get_filename()
+            .unwrap_or("file")
+            .to_string();
+        let mut bytes = vec![];
+
+        while let Some(chunk) = field.try_next().await.map_err(|e| BadRequest {
+            message: e.to_string(),
+        })? {
+            if bytes.len() + chunk.len() > max {
+                return Err(invalid(
+                    "file",
+                    "validation.file_size",
+                    vec![("max".to_string(), (max / 1024).to_string())],
+                ));
+            }
+
+            bytes.extend_from_slice(&chunk);
+        }
+
+        upload = Some((name, bytes));
+    }
+
+    let (name, bytes) = upload.ok_or_else(|| invalid("file", "validation.required", vec![]))?;
+
+    if bytes.is_empty() {
+        return Err(invalid("file", "validation.required", vec![]));
+    }
+
+    Ok((name, bytes))
+}
+
+pub async fn upload(
+    db: &DatabaseConnection,
+    auth: Auth,
+    payload: Multipart,
+) -> Result<HttpResponse, AppError> {
+    let max = env::var("UPLOAD_MAX_SIZE", 5 * 1024 * 1024usize);
+    let (name, bytes) = receive(payload, max).await?;
+
+    let (image, bytes) = web::block(move || (image::process(&bytes), bytes))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let image = image.map_err(|_| invalid("file", "validation.image", vec![]))?;
+
+    let disk = Disk::configured().map_err(|e| AppError::Internal(e.to_string()))?;
+    let id: Id = Uuid::new_v4();
+    let directory = format!("files/{}", time::now().format("%Y/%m"));
+    let path = format!("{}/{}.{}", directory, id, image.extension);
+    let thumbnail = format!("{}/{}_thumbnail.png", directory, id);
+    let file = files::Model {
+        id,
+        tenant_id: tenant::required()?,
+        disk: disk.name().to_string(),
+        path: path.clone(),
+        thumbnail_path: Some(thumbnail.clone()),
+        name,
+        mime: image.mime.to_string(),
+        size: bytes.len() as i64,
+        checksum: hex::encode(Sha256::digest(&bytes)),
+        width: Some(image.width as i32),
+        height: Some(image.height as i32),
+        uploaded_by: Some(auth.user.id),
+        created_at: time::now(),
+        updated_at: time::now(),
+    };
+
+    disk.put(&path, bytes, image.mime)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let stored = match disk.put(&thumbnail, image.thumbnail, "image/png").await {
+        Ok(_) => dao::file::store(db, file).await.map_err(AppError::from),
+        Err(e) => Err(AppError::Internal(e.to_string())),
+    };
+
+    match stored {
+        Ok(file) => Ok(HttpResponse::Created().json(FileOAS::from(file))),
+        Err(e) => {
+            // best effort, the upload failed either way
+            let _ = disk.delete(&path).await;
+            let _ = disk.delete(&thumbnail).await;
+
+            Err(e)
+        }
+    }
+}
+
+pub async fn show<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: Auth,
+    id: I,
+) -> Result<HttpResponse, AppError> {
+    let file = dao::file::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("file.not_found")))?;
+
+    // the urls it answers with give the content away
+    auth.authorize(PERMISSION, &file)?;
+
+    Ok(FileOAS::from(file).into())
+}
+
+pub async fn download<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+    request: FileDownloadRequest,
+) -> Result<HttpResponse, AppError> {
+    let id: Id = id.into();
+    let variant = request.variant.unwrap_or_default();
+
+    if !storage::verify(id, variant, request.expires, &request.signature) {
+        return Err(AppError::Forbidden(t!("file.invalid_signature")));
+    }
+
+    let file = dao::file::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("file.not_found")))?;
+    let (path, mime) = match (variant, &file.thumbnail_path) {
+        (Variant::Original, _) => (&file.path, file.mime.as_str()),
+        (Variant::Thumbnail, Some(path)) => (path, "image/png"),
+        (Variant::Thumbnail, None) => return Err(AppError::NotFound(t!("file.not_found"))),
+    };
+    let disk = Disk::named(&file.disk).map_err(|e| AppError::Internal(e.to_string()))?;
+    let bytes = disk
+        .get(path)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(mime)
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Inline,
+            parameters: vec![DispositionParam::Filename(file.name.clone())],
+        })
+        .insert_header((header::CACHE_CONTROL, "private, max-age=3600"))
+        .insert_header((header::ETAG, format!("\"{}\"", file.checksum)))
+        .body(bytes))
+}
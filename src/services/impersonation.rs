@@ -0,0 +1,98 @@
+use actix_web::HttpResponse;
+use chrono::Duration;
+use sea_orm::DatabaseConnection;
+
+use crate::catalogue;
+use crate::common::{base58, env, log, time};
+use crate::dao;
+use crate::i18n::t;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::{superusers, Auth, Authenticated, ADMINISTRATORS};
+use crate::models::Id;
+use crate::responses::user::UserOAS;
+use crate::responses::{self, AppError, Ok};
+
+/// Code of the permission allowing to act as another user.
+pub const PERMISSION: &str = catalogue::IMPERSONATE_USER;
+
+pub async fn start<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: Auth,
+    actor: Actor,
+    id: I,
+) -> Result<HttpResponse, AppError> {
+    if auth.is_impersonating() {
+        return Err(AppError::Forbidden(t!("impersonation.nested")));
+    }
+
+    let (user, permissions, roles) = dao::user::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("user.not_found")))?;
+
+    if user.id == auth.user.id {
+        return Err(AppError::Conflict(t!("impersonation.self")));
+    }
+
+    // granted globally, or within the scope of this very user
+    auth.authorize(PERMISSION, &user)?;
+
+    // acting as an administrator would hand out more than the permission grants
+    let superusers = superusers();
+    let effective = dao::role::effective(db, roles.iter().map(|role| role.id).collect()).await?;
+
+    if effective
+        .iter()
+        .any(|role| ADMINISTRATORS.contains(&role.code.as_str()) || superusers.contains(&role.code))
+    {
+        return Err(AppError::Forbidden(t!("impersonation.administrator")));
+    }
+
+    let ttl = env::var("IMPERSONATION_TTL", 3600i64).max(1);
+    let expired_at = time::now() + Duration::seconds(ttl);
+    let (token, _) = dao::impersonation::start(db, &auth.user, &user, expired_at, &actor).await?;
+
+    log::info!(
+        start,
+        "{} started impersonating {}",
+        auth.user.username,
+        user.username
+    );
+
+    Ok(HttpResponse::Ok().json(responses::auth::Login {
+        token: base58::to_string(token.id.as_bytes()),
+        user: UserOAS::from((user, permissions, roles)),
+    }))
+}
+
+pub async fn stop(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    auth: Auth,
+    actor: Actor,
+) -> Result<HttpResponse, AppError> {
+    let impersonator = auth
+        .impersonator
+        .as_ref()
+        .ok_or_else(|| AppError::Conflict(t!("impersonation.inactive")))?;
+    // recorded against whoever is really behind the request
+    let actor = Actor {
+        user_id: Some(impersonator.id),
+        impersonator_id: None,
+        ..actor
+    };
+
+    dao::impersonation::stop(db, &actor, &auth.user, &auth.token).await?;
+    cache.remove(&auth.token);
+
+    log::info!(
+        stop,
+        "{} stopped impersonating {}",
+        impersonator.username,
+        auth.user.username
+    );
+
+    Ok(Ok {
+        message: t!("impersonation.stopped", username = auth.user.username),
+    }
+    .into())
+}
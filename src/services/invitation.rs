@@ -0,0 +1,288 @@
+use actix_web::HttpResponse;
+use chrono::Duration;
+use sea_orm::{prelude::*, QueryOrder, QuerySelect};
+use sea_query::Condition;
+
+use crate::common::{env, log, sign, time};
+use crate::dao;
+use crate::i18n::t;
+use crate::mail::SendMail;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::Auth;
+use crate::models::{invitations, Id, Timestamp};
+use crate::requests::invitation::{
+    InvitationAcceptRequest, InvitationFilterRequest, InvitationLinkRequest, InvitationStatus,
+    InvitationStoreRequest,
+};
+use crate::requests::PaginationRequest;
+use crate::responses::invitation::{
+    InvitationLinkOAS, InvitationOAS, InvitationPaginationResponse,
+};
+use crate::responses::user::UserOAS;
+use crate::responses::AppError;
+use crate::tenant::Tenanted;
+use crate::validation::{self, invalid};
+
+fn expiry() -> Timestamp {
+    time::now() + Duration::seconds(env::var("INVITATION_TTL", 7 * 24 * 3600i64).max(1))
+}
+
+fn expires(invitation: &invitations::Model) -> u64 {
+    invitation.expired_at.timestamp() as u64
+}
+
+fn signature<I: std::fmt::Display>(id: I, expires: u64) -> String {
+    format!("invitation:{}:{}", id, expires)
+}
+
+/// The signed link mailed to the invitee, valid until the invitation expires.
+pub fn url(invitation: &invitations::Model) -> String {
+    let expires = expires(invitation);
+
+    format!(
+        "{}/api/v1/invitation/{}/accept?expires={}&signature={}",
+        env::var("APP_URL", "http://localhost:8000".to_string()).trim_end_matches('/'),
+        invitation.id,
+        expires,
+        sign::sign(signature(invitation.id, expires)),
+    )
+}
+
+/// A link is only good for the latest expiry of the invitation, so
+/// resending voids the links mailed before.
+pub fn verify(invitation: &invitations::Model, expires: u64, signature: &str) -> bool {
+    expires == self::expires(invitation)
+        && sign::verify(self::signature(invitation.id, expires), signature)
+}
+
+async fn send(db: &DatabaseConnection, invitation: &invitations::Model) {
+    let mail = SendMail::guest(&invitation.email, &invitation.name, "invitation")
+        .param("url", url(invitation))
+        .param("expired_at", invitation.expired_at.format("%Y-%m-%d %H:%M"))
+        .dispatch(db)
+        .await;
+
+    if let Err(e) = mail {
+        log::error!(send, "{}", e);
+    }
+}
+
+/// The invitation behind a link, as long as it can still be accepted.
+async fn pending<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+    link: &InvitationLinkRequest,
+) -> Result<invitations::Model, AppError> {
+    let invitation = dao::invitation::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("invitation.not_found")))?;
+
+    if !verify(&invitation, link.expires, &link.signature) {
+        return Err(AppError::Forbidden(t!("invitation.invalid_signature")));
+    }
+
+    match InvitationStatus::of(&invitation) {
+        InvitationStatus::Pending => Ok(invitation),
+        status => Err(AppError::Conflict(t!(
+            "invitation.not_pending",
+            status = status.as_str()
+        ))),
+    }
+}
+
+pub async fn paginate(
+    db: &DatabaseConnection,
+    request: PaginationRequest<invitations::Column>,
+    filter: InvitationFilterRequest,
+) -> Result<HttpResponse, AppError> {
+    let mut query = invitations::Entity::find().tenanted();
+
+    if let Some(status) = filter.status {
+        let pending = invitations::Column::Status.eq(InvitationStatus::Pending.as_str());
+
+        query = match status {
+            InvitationStatus::Pending => query
+                .filter(pending)
+                .filter(invitations::Column::ExpiredAt.gt(time::now())),
+            InvitationStatus::Expired => query
+                .filter(pending)
+                .filter(invitations::Column::ExpiredAt.lte(time::now())),
+            status => query.filter(invitations::Column::Status.eq(status.as_str())),
+        };
+    }
+
+    if let Some(search) = request.search() {
+        query = query.filter(
+            Condition::any()
+                .add(invitations::Column::Email.like(search.clone()))
+                .add(invitations::Column::Name.like(search)),
+        );
+    }
+
+    let total = query.clone().count(db).await?;
+    let invitations = query
+        .limit(request.limit())
+        .offset(request.offset())
+        .order_by(
+            request.order(invitations::Column::CreatedAt),
+            request.sort(),
+        )
+        .all(db)
+        .await?;
+
+    Ok(InvitationPaginationResponse {
+        total,
+        page: total / request.limit(),
+        data: invitations.iter().map(InvitationOAS::from).collect(),
+    }
+    .into())
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    auth: Auth,
+    request: InvitationStoreRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
+
+    if dao::invitation::find_pending(db, &request.email)
+        .await?
+        .is_some()
+    {
+        return Err(invalid("email", "validation.invited", vec![]));
+    }
+
+    let invitation = dao::invitation::store(db, request, &auth.user, expiry()).await?;
+
+    send(db, &invitation).await;
+
+    Ok(HttpResponse::Created().json(InvitationOAS::from(invitation)))
+}
+
+pub async fn show<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Result<HttpResponse, AppError> {
+    let invitation = dao::invitation::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("invitation.not_found")))?;
+
+    Ok(InvitationOAS::from(invitation).into())
+}
+
+/// Mail a fresh link, renewing expired invitations too.
+pub async fn resend<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Result<HttpResponse, AppError> {
+    let invitation = dao::invitation::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("invitation.not_found")))?;
+
+    match InvitationStatus::of(&invitation) {
+        InvitationStatus::Pending | InvitationStatus::Expired => {}
+        status => {
+            return Err(AppError::Conflict(t!(
+                "invitation.not_pending",
+                status = status.as_str()
+            )))
+        }
+    }
+
+    let invitation = dao::invitation::resend(db, invitation, expiry()).await?;
+
+    send(db, &invitation).await;
+
+    Ok(InvitationOAS::from(invitation).into())
+}
+
+pub async fn revoke<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Result<HttpResponse, AppError> {
+    let invitation = dao::invitation::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("invitation.not_found")))?;
+
+    if InvitationStatus::of(&invitation) == InvitationStatus::Accepted {
+        return Err(AppError::Conflict(t!(
+            "invitation.not_pending",
+            status = InvitationStatus::Accepted.as_str()
+        )));
+    }
+
+    let invitation = dao::invitation::revoke(db, invitation).await?;
+
+    Ok(InvitationOAS::from(invitation).into())
+}
+
+pub async fn preview<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+    link: InvitationLinkRequest,
+) -> Result<HttpResponse, AppError> {
+    let invitation = pending(db, id, &link).await?;
+
+    Ok(InvitationLinkOAS::from(&invitation).into())
+}
+
+pub async fn accept<I: Into<Id>>(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    id: I,
+    link: InvitationLinkRequest,
+    request: InvitationAcceptRequest,
+) -> Result<HttpResponse, AppError> {
+    let invitation = pending(db, id, &link).await?;
+
+    validation::validate_with(db, &request, &invitation).await?;
+
+    let (user, permissions, roles) = dao::invitation::accept(db, actor, invitation, request)
+        .await?
+        .ok_or_else(|| AppError::Conflict(t!("invitation.used")))?;
+
+    Ok(HttpResponse::Created().json(UserOAS::from((user, permissions, roles))))
+}
+
+#[cfg(test)]
+pub mod test {
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    use crate::common::time;
+    use crate::models::invitations;
+
+    fn invitation() -> invitations::Model {
+        invitations::Model {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            email: "john@local.id".to_string(),
+            name: "John Doe".to_string(),
+            roles: serde_json::json!([]),
+            permissions: serde_json::json!([]),
+            status: "pending".to_string(),
+            invited_by: None,
+            user_id: None,
+            sent_count: 1,
+            sent_at: time::now(),
+            expired_at: time::now() + Duration::days(1),
+            accepted_at: None,
+            revoked_at: None,
+            created_at: time::now(),
+            updated_at: time::now(),
+        }
+    }
+
+    #[test]
+    pub async fn link_must_be_voided_by_a_new_expiry() {
+        use super::{expires, url, verify};
+
+        std::env::set_var("APP_KEY", "secret");
+
+        let invitation = invitation();
+        let link = url(&invitation);
+        let signature = link.split("signature=").last().unwrap();
+
+        assert!(link.contains(&format!("/api/v1/invitation/{}/accept", invitation.id)));
+        assert!(verify(&invitation, expires(&invitation), signature));
+
+        let resent = invitations::Model {
+            expired_at: invitation.expired_at + Duration::days(1),
+            ..invitation.clone()
+        };
+
+        assert!(!verify(&resent, expires(&invitation), signature));
+        assert!(!verify(&resent, expires(&resent), signature));
+    }
+}
@@ -0,0 +1,90 @@
+use actix_web::HttpResponse;
+use sea_orm::{prelude::*, QueryOrder, QuerySelect};
+use sea_query::Condition;
+
+use crate::dao;
+use crate::i18n::t;
+use crate::models::{jobs, Id};
+use crate::queue::Status;
+use crate::requests::job::JobFilterRequest;
+use crate::requests::PaginationRequest;
+use crate::responses::job::{JobOAS, JobPaginationResponse};
+use crate::responses::{AppError, Ok};
+
+pub async fn paginate(
+    db: &DatabaseConnection,
+    request: PaginationRequest<jobs::Column>,
+    filter: JobFilterRequest,
+) -> Result<HttpResponse, AppError> {
+    let mut query = jobs::Entity::find();
+
+    if let Some(queue) = filter.queue {
+        query = query.filter(jobs::Column::Queue.eq(queue));
+    }
+
+    if let Some(status) = filter.status {
+        query = query.filter(jobs::Column::Status.eq(status.as_str()));
+    }
+
+    if let Some(search) = request.search() {
+        query = query.filter(
+            Condition::any()
+                .add(jobs::Column::Name.like(search.clone()))
+                .add(jobs::Column::LastError.like(search)),
+        );
+    }
+
+    let total = query.clone().count(db).await?;
+    let jobs = query
+        .limit(request.limit())
+        .offset(request.offset())
+        .order_by(request.order(jobs::Column::CreatedAt), request.sort())
+        .all(db)
+        .await?;
+
+    Ok(JobPaginationResponse {
+        total,
+        page: total / request.limit(),
+        data: jobs.iter().map(JobOAS::from).collect(),
+    }
+    .into())
+}
+
+pub async fn show<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Result<HttpResponse, AppError> {
+    let job = dao::job::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("job.not_found")))?;
+
+    Ok(JobOAS::from(job).into())
+}
+
+pub async fn retry<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Result<HttpResponse, AppError> {
+    let job = dao::job::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("job.not_found")))?;
+
+    if job.status == Status::Running.as_str() {
+        return Err(AppError::Conflict(t!("job.running", id = job.id)));
+    }
+
+    let job = dao::job::retry(db, job).await?;
+
+    Ok(JobOAS::from(job).into())
+}
+
+pub async fn delete<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Result<HttpResponse, AppError> {
+    let job = dao::job::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("job.not_found")))?;
+
+    if job.status == Status::Running.as_str() {
+        return Err(AppError::Conflict(t!("job.running", id = job.id)));
+    }
+
+    let job = dao::job::delete(db, job).await?;
+
+    Ok(Ok {
+        message: t!("job.deleted", id = job.id),
+    }
+    .into())
+}
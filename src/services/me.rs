@@ -0,0 +1,129 @@
+use actix_web::HttpResponse;
+use sea_orm::DatabaseConnection;
+
+use crate::common::hash::{self, Hash};
+use crate::common::time;
+use crate::dao;
+use crate::i18n::t;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::{Auth, Authenticated};
+use crate::models::users;
+use crate::requests::preference::PreferenceUpdateRequest;
+use crate::requests::user::{
+    UserDeleteAccountRequest, UserUpdatePasswordRequest, UserUpdateProfileRequest,
+};
+use crate::responses::preference::PreferenceOAS;
+use crate::responses::user::UserOAS;
+use crate::responses::{AppError, Ok};
+use crate::services;
+use crate::validation;
+
+/// Fail on `field` unless `password` is the current password of `user`.
+fn confirm(user: &users::Model, field: &str, password: &str) -> Result<(), AppError> {
+    if hash::verify(
+        Hash::from(user.password.clone()),
+        user.id.to_string(),
+        password,
+    ) {
+        return Ok(());
+    }
+
+    Err(validation::invalid(
+        field,
+        "validation.current_password",
+        vec![],
+    ))
+}
+
+pub async fn show(auth: Auth) -> Result<HttpResponse, AppError> {
+    Ok(UserOAS::from((auth.user, auth.permissions, auth.roles)).into())
+}
+
+pub async fn update(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    auth: Auth,
+    actor: &Actor,
+    request: UserUpdateProfileRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate_with(db, &request, &auth.user.id).await?;
+
+    let user = dao::user::update_profile(db, actor, auth.user, request).await?;
+
+    // cached sessions still hold the old profile
+    cache.forget(&user.id, None);
+
+    Ok(UserOAS::from((user, auth.permissions, auth.roles)).into())
+}
+
+pub async fn preferences(auth: Auth) -> Result<HttpResponse, AppError> {
+    Ok(PreferenceOAS::from((&auth.user, &auth.preferences)).into())
+}
+
+pub async fn update_preferences(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    auth: Auth,
+    actor: &Actor,
+    request: PreferenceUpdateRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
+
+    let (user, preferences) = dao::preference::save(db, actor, auth.user, request).await?;
+
+    // cached sessions still hold the old preferences
+    cache.forget(&user.id, None);
+
+    // answer in the timezone just chosen
+    if let Ok(timezone) = preferences.timezone.parse() {
+        time::set_timezone(timezone);
+    }
+
+    Ok(PreferenceOAS::from((&user, &preferences)).into())
+}
+
+pub async fn update_password(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    auth: Auth,
+    actor: &Actor,
+    request: UserUpdatePasswordRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
+
+    confirm(&auth.user, "current_password", &request.current_password)?;
+
+    let user = dao::user::update_password(db, actor, auth.user, request.new_password).await?;
+
+    dao::auth::revoke(db, user.id, Some(auth.token)).await?;
+    cache.forget(&user.id, None);
+    services::user::password_changed(db, &user).await;
+
+    Ok(Ok {
+        message: t!("user.own_password_updated"),
+    }
+    .into())
+}
+
+pub async fn delete(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    auth: Auth,
+    actor: &Actor,
+    request: UserDeleteAccountRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
+
+    confirm(&auth.user, "password", &request.password)?;
+    services::user::keep_superuser(db, &auth.user, None).await?;
+
+    let user = dao::user::delete(db, actor, auth.user).await?;
+
+    dao::auth::revoke(db, user.id, None).await?;
+    cache.forget(&user.id, None);
+
+    Ok(Ok {
+        message: t!("user.own_deleted"),
+    }
+    .into())
+}
@@ -1,4 +1,14 @@
+pub mod audit;
 pub mod auth;
+pub mod export;
+pub mod file;
+pub mod impersonation;
+pub mod invitation;
+pub mod job;
+pub mod me;
 pub mod permission;
 pub mod role;
+pub mod role_scope;
+pub mod schedule;
+pub mod tenant;
 pub mod user;
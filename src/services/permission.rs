@@ -1,23 +1,44 @@
-use std::collections::HashMap;
-
 use actix_web::HttpResponse;
-use sea_orm::{prelude::*, QueryOrder, QuerySelect};
-use sea_query::Condition;
+use sea_orm::{prelude::*, QueryOrder, QuerySelect, Select};
+use sea_query::{Condition, Query};
 
-use crate::common::log;
+use crate::catalogue;
 use crate::dao;
-use crate::models::{permissions, Id};
-use crate::requests::permission::{PermissionStoreRequest, PermissionUpdateRequest};
+use crate::i18n::t;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::Authenticated;
+use crate::models::{permission_user, permissions, users, Id};
+use crate::requests::permission::{
+    PermissionBulkRequest, PermissionFilterRequest, PermissionStoreRequest, PermissionUpdateRequest,
+};
+use crate::requests::user::UserFilterRequest;
 use crate::requests::PaginationRequest;
-use crate::responses::permission::{PermissionOAS, PermissionPaginationResponse};
-use crate::responses::{CreatedWithId, InternalServerError, Ok, UnprocessableEntity};
-
-pub async fn paginate(
-    db: &DatabaseConnection,
-    request: PaginationRequest<permissions::Column>,
-) -> HttpResponse {
+use crate::responses::permission::{
+    PermissionOAS, PermissionPaginationResponse, PermissionSummaryOAS,
+};
+use crate::responses::{AppError, CreatedWithId, Ok};
+use crate::services;
+use crate::services::export::Export;
+use crate::sheet::ExportFormat;
+use crate::tenant::Tenanted;
+use crate::validation;
+
+fn search(
+    request: &PaginationRequest<permissions::Column>,
+    filter: &PermissionFilterRequest,
+) -> Select<permissions::Entity> {
     let mut query = permissions::Entity::find();
 
+    if let Some(group) = &filter.group {
+        query = query.filter(permissions::Column::Group.eq(group));
+    }
+
+    query = match filter.orphaned {
+        Some(true) => query.filter(permissions::Column::OrphanedAt.is_not_null()),
+        Some(false) => query.filter(permissions::Column::OrphanedAt.is_null()),
+        None => query,
+    };
+
     if let Some(search) = request.search() {
         let search = format!("%{}%", search.to_lowercase());
 
@@ -28,161 +49,229 @@ pub async fn paginate(
         );
     }
 
-    let total = query.clone().count(db).await;
-
-    if let Err(e) = total {
-        log::error!(paginate, "{}", e);
+    query
+}
 
-        return InternalServerError {
-            message: e.to_string(),
-        }
-        .into();
-    }
+pub async fn paginate(
+    db: &DatabaseConnection,
+    request: PaginationRequest<permissions::Column>,
+    filter: PermissionFilterRequest,
+) -> Result<HttpResponse, AppError> {
+    let query = search(&request, &filter);
 
-    let total = total.unwrap();
+    let total = query.clone().count(db).await?;
     let permissions = query
         .limit(request.limit())
         .offset(request.offset())
         .order_by(request.order(permissions::Column::Code), request.sort())
-        .all(db);
+        .all(db)
+        .await?;
+    let members = dao::permission::members(
+        db,
+        permissions.iter().map(|permission| permission.id).collect(),
+    )
+    .await?;
+
+    Ok(PermissionPaginationResponse {
+        total,
+        page: total / request.limit(),
+        data: permissions
+            .iter()
+            .map(|permission| PermissionSummaryOAS {
+                permission: PermissionOAS::from(permission),
+                users: members.get(&permission.id).copied().unwrap_or_default(),
+            })
+            .collect(),
+    }
+    .into())
+}
 
-    match permissions.await {
-        Err(e) => {
-            log::error!(paginate, "{}", e);
+pub async fn export(
+    db: &DatabaseConnection,
+    request: PaginationRequest<permissions::Column>,
+    filter: PermissionFilterRequest,
+    format: ExportFormat,
+) -> Result<HttpResponse, AppError> {
+    let query = search(&request, &filter)
+        .order_by(request.order(permissions::Column::Code), request.sort())
+        .order_by_asc(permissions::Column::Id);
 
-            InternalServerError {
-                message: e.to_string(),
-            }
-            .into()
-        }
-        Ok(permissions) => PermissionPaginationResponse {
-            total,
-            page: total / request.limit(),
-            data: permissions.iter().map(PermissionOAS::from).collect(),
-        }
-        .into(),
-    }
+    services::export::stream(db, query, format, "permissions")
 }
 
-pub async fn store(db: &DatabaseConnection, request: PermissionStoreRequest) -> HttpResponse {
-    let mut validation = HashMap::new();
-    let code = request.code.trim().to_uppercase();
-    let name = request.name.trim().to_lowercase();
-    let mut errors = vec![];
+impl Export for permissions::Entity {
+    type Record = PermissionOAS;
 
-    if code.is_empty() {
-        errors.push("Code field is required");
+    async fn records(
+        _: &DatabaseConnection,
+        permissions: Vec<permissions::Model>,
+    ) -> Result<Vec<PermissionOAS>, DbErr> {
+        Ok(permissions.iter().map(PermissionOAS::from).collect())
     }
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    request: PermissionStoreRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
+
+    let permission = dao::permission::store(db, actor, request).await?;
 
-    if name.is_empty() {
-        validation.insert("name", vec!["Name field is required"]);
+    Ok(CreatedWithId {
+        id: permission.id,
+        message: t!("permission.created", code = permission.code),
     }
+    .into())
+}
 
-    let exist = permissions::Entity::find()
-        .filter(permissions::Column::Code.eq(code.clone()))
-        .count(db)
-        .await;
+pub async fn show<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Result<HttpResponse, AppError> {
+    let permission = dao::permission::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("permission.not_found")))?;
 
-    if let Err(e) = exist {
-        log::error!(store, "{}", e);
+    Ok(PermissionOAS::from(permission).into())
+}
 
-        return InternalServerError {
-            message: e.to_string(),
+/// The users of the current tenant the permission behind `id` is granted to
+/// directly.
+pub async fn users<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+    request: PaginationRequest<users::Column>,
+    filter: UserFilterRequest,
+) -> Result<HttpResponse, AppError> {
+    let permission = dao::permission::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("permission.not_found")))?;
+    let granted = Query::select()
+        .column(permission_user::Column::UserId)
+        .from(permission_user::Entity)
+        .and_where(permission_user::Column::PermissionId.eq(permission.id))
+        .to_owned();
+
+    services::user::holding(db, users::Column::Id.in_subquery(granted), request, filter).await
+}
+
+/// Grant every permission of the request to each of its users, or revoke
+/// them when `revoke`.
+async fn regrant(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    actor: &Actor,
+    request: PermissionBulkRequest,
+    revoke: bool,
+) -> Result<usize, AppError> {
+    validation::validate(db, &request).await?;
+
+    let period = request.period()?;
+    let permissions = permissions::Entity::find()
+        .filter(permissions::Column::Id.is_in(request.permissions))
+        .all(db)
+        .await?;
+    let users = users::Entity::find()
+        .tenanted()
+        .filter(users::Column::Id.is_in(request.users))
+        .filter(users::Column::DeletedAt.is_null())
+        .all(db)
+        .await?;
+    let ids = users.iter().map(|user| user.id).collect::<Vec<_>>();
+    let changed = dao::user::regrant(db, actor, users, |held, _| {
+        for permission in &permissions {
+            let index = held.iter().position(|(held, _)| held.id == permission.id);
+
+            match (index, revoke) {
+                (None, false) => held.push((permission.clone(), period.clone())),
+                (Some(index), false) => held[index].1 = period.clone(),
+                (Some(index), true) => {
+                    held.remove(index);
+                }
+                (None, true) => {}
+            }
         }
-        .into();
-    }
+    })
+    .await?;
 
-    if exist.unwrap() > 0 {
-        errors.push("Code already exist");
-    }
+    ids.iter().for_each(|id| cache.forget(id, None));
 
-    if !errors.is_empty() {
-        validation.insert("code", errors);
-    }
+    Ok(changed)
+}
 
-    if !validation.is_empty() {
-        return UnprocessableEntity { errors: validation }.into();
+pub async fn assign(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    actor: &Actor,
+    request: PermissionBulkRequest,
+) -> Result<HttpResponse, AppError> {
+    let changed = regrant(db, cache, actor, request, false).await?;
+
+    Ok(Ok {
+        message: t!("permission.assigned", count = changed),
     }
+    .into())
+}
 
-    match dao::permission::store(db, request).await {
-        Err(e) => {
-            log::error!(store, "{}", e);
-
-            InternalServerError {
-                message: e.to_string(),
-            }
-            .into()
-        }
-        Ok(permission) => CreatedWithId {
-            id: permission.id,
-            message: format!("Permission {} has been created", permission.code),
-        }
-        .into(),
+pub async fn revoke(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    actor: &Actor,
+    request: PermissionBulkRequest,
+) -> Result<HttpResponse, AppError> {
+    let changed = regrant(db, cache, actor, request, true).await?;
+
+    Ok(Ok {
+        message: t!("permission.revoked", count = changed),
     }
+    .into())
 }
 
-pub async fn show<I: Into<Id>>(db: &DatabaseConnection, id: I) -> HttpResponse {
-    match dao::permission::find(db, id).await {
-        None => HttpResponse::NotFound().finish(),
-        Some(permission) => PermissionOAS::from(permission).into(),
+/// Fail as a conflict for permissions of the catalogue, which the
+/// application keeps as it defines them.
+fn defined(permission: &permissions::Model) -> Result<(), AppError> {
+    match catalogue::find(&permission.code) {
+        Some(_) => Err(AppError::Conflict(t!(
+            "permission.system",
+            code = permission.code
+        ))),
+        None => Ok(()),
     }
 }
 
 pub async fn update<I: Into<Id>>(
     db: &DatabaseConnection,
+    actor: &Actor,
     id: I,
     request: PermissionUpdateRequest,
-) -> HttpResponse {
-    let id: Id = id.into();
-    let mut validation = HashMap::new();
-    let name = request.name.trim().to_lowercase();
-
-    if name.is_empty() {
-        validation.insert("name", vec!["Name field is required"]);
-    }
-
-    if !validation.is_empty() {
-        return UnprocessableEntity { errors: validation }.into();
-    }
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
 
-    let permission = dao::permission::find(db, id).await;
+    let permission = dao::permission::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("permission.not_found")))?;
 
-    if permission.is_none() {
-        return HttpResponse::NotFound().finish();
-    }
+    defined(&permission)?;
 
-    match dao::permission::update(db, permission.unwrap(), request).await {
-        Err(e) => {
-            log::error!(update, "{}", e);
+    let permission = dao::permission::update(db, actor, permission, request).await?;
 
-            InternalServerError {
-                message: e.to_string(),
-            }
-            .into()
-        }
-        Ok(permission) => PermissionOAS::from(permission).into(),
-    }
+    Ok(PermissionOAS::from(permission).into())
 }
 
-pub async fn delete<I: Into<Id>>(db: &DatabaseConnection, id: I) -> HttpResponse {
-    let permission = dao::permission::find(db, id).await;
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    id: I,
+) -> Result<HttpResponse, AppError> {
+    let permission = dao::permission::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("permission.not_found")))?;
 
-    if permission.is_none() {
-        return HttpResponse::NotFound().finish();
-    }
+    defined(&permission)?;
 
-    match dao::permission::delete(db, permission.unwrap()).await {
-        Err(e) => {
-            log::error!(delete, "{}", e);
+    let permission = dao::permission::delete(db, actor, permission).await?;
 
-            InternalServerError {
-                message: e.to_string(),
-            }
-            .into()
-        }
-        Ok(permission) => Ok {
-            message: format!("Permission {} has been deleted", permission.code),
-        }
-        .into(),
+    Ok(Ok {
+        message: t!("permission.deleted", code = permission.code),
     }
+    .into())
 }
@@ -1,22 +1,29 @@
-use std::collections::HashMap;
-
 use actix_web::HttpResponse;
-use sea_orm::{prelude::*, QueryOrder, QuerySelect};
-use sea_query::Condition;
+use sea_orm::{prelude::*, QueryOrder, QuerySelect, Select};
+use sea_query::{Condition, Query};
 
-use crate::common::log;
 use crate::dao;
-use crate::models::{roles, Id};
-use crate::requests::role::{RoleStoreRequest, RoleUpdateRequest};
+use crate::i18n::t;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::{Auth, Authenticated};
+use crate::models::{permission_role, permissions, role_user, roles, users, Id};
+use crate::requests::role::{
+    RoleBulkRequest, RoleParentRequest, RoleStoreRequest, RoleUpdateRequest,
+};
+use crate::requests::user::UserFilterRequest;
 use crate::requests::PaginationRequest;
-use crate::responses::role::{RoleOAS, RolePaginationResponse};
-use crate::responses::{CreatedWithId, InternalServerError, Ok, UnprocessableEntity};
-
-pub async fn paginate(
-    db: &DatabaseConnection,
-    request: PaginationRequest<roles::Column>,
-) -> HttpResponse {
-    let mut query = roles::Entity::find();
+use crate::responses::role::{
+    RoleExportRecord, RoleListResponse, RoleOAS, RolePaginationResponse, RoleSummaryOAS,
+};
+use crate::responses::{AppError, CreatedWithId, Ok};
+use crate::services;
+use crate::services::export::Export;
+use crate::sheet::ExportFormat;
+use crate::tenant::{self, Tenanted};
+use crate::validation::{self, invalid};
+
+fn search(request: &PaginationRequest<roles::Column>) -> Select<roles::Entity> {
+    let mut query = roles::Entity::find().tenanted();
 
     if let Some(search) = request.search() {
         let search = format!("%{}%", search.to_lowercase());
@@ -28,161 +35,478 @@ pub async fn paginate(
         );
     }
 
-    let total = query.clone().count(db).await;
-
-    if let Err(e) = total {
-        log::error!(paginate, "{}", e);
+    query
+}
 
-        return InternalServerError {
-            message: e.to_string(),
-        }
-        .into();
-    }
+pub async fn paginate(
+    db: &DatabaseConnection,
+    request: PaginationRequest<roles::Column>,
+) -> Result<HttpResponse, AppError> {
+    let query = search(&request);
 
-    let total = total.unwrap();
+    let total = query.clone().count(db).await?;
     let roles = query
         .limit(request.limit())
         .offset(request.offset())
         .order_by(request.order(roles::Column::Code), request.sort())
-        .all(db);
+        .all(db)
+        .await?;
+    let members = dao::role::members(db, roles.iter().map(|role| role.id).collect()).await?;
+
+    Ok(RolePaginationResponse {
+        total,
+        page: total / request.limit(),
+        data: roles
+            .iter()
+            .map(|role| RoleSummaryOAS {
+                role: RoleOAS::from(role),
+                users: members.get(&role.id).copied().unwrap_or_default(),
+            })
+            .collect(),
+    }
+    .into())
+}
 
-    match roles.await {
-        Err(e) => {
-            log::error!(paginate, "{}", e);
+pub async fn export(
+    db: &DatabaseConnection,
+    request: PaginationRequest<roles::Column>,
+    format: ExportFormat,
+) -> Result<HttpResponse, AppError> {
+    let query = search(&request)
+        .order_by(request.order(roles::Column::Code), request.sort())
+        .order_by_asc(roles::Column::Id);
 
-            InternalServerError {
-                message: e.to_string(),
-            }
-            .into()
-        }
-        Ok(roles) => RolePaginationResponse {
-            total,
-            page: total / request.limit(),
-            data: roles.iter().map(RoleOAS::from).collect(),
-        }
-        .into(),
+    services::export::stream(db, query, format, "roles")
+}
+
+impl Export for roles::Entity {
+    type Record = RoleExportRecord;
+
+    async fn records(
+        db: &DatabaseConnection,
+        roles: Vec<roles::Model>,
+    ) -> Result<Vec<RoleExportRecord>, DbErr> {
+        let permissions = permission_role::Entity::find()
+            .find_also_related(permissions::Entity)
+            .filter(
+                permission_role::Column::RoleId
+                    .is_in(roles.iter().map(|role| role.id).collect::<Vec<Id>>()),
+            )
+            .all(db)
+            .await?;
+
+        Ok(roles
+            .into_iter()
+            .map(|role| RoleExportRecord {
+                permissions: permissions
+                    .iter()
+                    .filter(|(permission_role, _)| permission_role.role_id == role.id)
+                    .filter_map(|(_, permission)| {
+                        permission
+                            .as_ref()
+                            .map(|permission| permission.code.clone())
+                    })
+                    .collect(),
+                id: role.id,
+                code: role.code,
+                name: role.name,
+            })
+            .collect())
     }
 }
 
-pub async fn store(db: &DatabaseConnection, request: RoleStoreRequest) -> HttpResponse {
-    let mut validation = HashMap::new();
-    let code = request.code.trim().to_uppercase();
-    let name = request.name.trim().to_lowercase();
-    let mut errors = vec![];
+pub async fn store(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    request: RoleStoreRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
 
-    if code.is_empty() {
-        errors.push("Code field is required");
-    }
+    let role = dao::role::store(db, actor, request).await?;
 
-    if name.is_empty() {
-        validation.insert("name", vec!["Name field is required"]);
+    Ok(CreatedWithId {
+        id: role.id,
+        message: t!("role.created", code = role.code),
     }
+    .into())
+}
 
-    let exist = roles::Entity::find()
-        .filter(roles::Column::Code.eq(code.clone()))
-        .count(db)
-        .await;
+pub async fn show<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Result<HttpResponse, AppError> {
+    let role = dao::role::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("role.not_found")))?;
 
-    if let Err(e) = exist {
-        log::error!(store, "{}", e);
+    Ok(RoleOAS::from(role).into())
+}
 
-        return InternalServerError {
-            message: e.to_string(),
-        }
-        .into();
+/// Fail as a conflict for system roles, which the application relies on
+/// being there as they are.
+fn managed(role: &roles::Model) -> Result<(), AppError> {
+    match role.system {
+        true => Err(AppError::Conflict(t!("role.system", code = role.code))),
+        false => Ok(()),
     }
+}
 
-    if exist.unwrap() > 0 {
-        errors.push("Code already exist");
-    }
+pub async fn update<I: Into<Id>>(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    id: I,
+    request: RoleUpdateRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
 
-    if !errors.is_empty() {
-        validation.insert("code", errors);
-    }
+    let role = dao::role::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("role.not_found")))?;
+
+    managed(&role)?;
 
-    if !validation.is_empty() {
-        return UnprocessableEntity { errors: validation }.into();
+    let role = dao::role::update(db, actor, role, request).await?;
+
+    Ok(RoleOAS::from(role).into())
+}
+
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    id: I,
+) -> Result<HttpResponse, AppError> {
+    let role = dao::role::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("role.not_found")))?;
+
+    managed(&role)?;
+
+    let role = dao::role::delete(db, actor, role).await?;
+
+    Ok(Ok {
+        message: t!("role.deleted", code = role.code),
     }
+    .into())
+}
 
-    match dao::role::store(db, request).await {
-        Err(e) => {
-            log::error!(store, "{}", e);
+/// The users the role behind `id` is assigned to directly.
+pub async fn users<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+    request: PaginationRequest<users::Column>,
+    filter: UserFilterRequest,
+) -> Result<HttpResponse, AppError> {
+    let role = dao::role::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("role.not_found")))?;
+    let assigned = Query::select()
+        .column(role_user::Column::UserId)
+        .from(role_user::Entity)
+        .and_where(role_user::Column::RoleId.eq(role.id))
+        .to_owned();
+
+    services::user::holding(db, users::Column::Id.in_subquery(assigned), request, filter).await
+}
 
-            InternalServerError {
-                message: e.to_string(),
+/// The users of `ids` that are not deleted.
+async fn bulk_users(db: &DatabaseConnection, ids: Vec<Id>) -> Result<Vec<users::Model>, DbErr> {
+    users::Entity::find()
+        .tenanted()
+        .filter(users::Column::Id.is_in(ids))
+        .filter(users::Column::DeletedAt.is_null())
+        .all(db)
+        .await
+}
+
+/// Assign every role of the request to each of its users for the period of
+/// the request, on top of the roles they hold.
+pub async fn assign(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    auth: &Auth,
+    actor: &Actor,
+    request: RoleBulkRequest,
+) -> Result<HttpResponse, AppError> {
+    superuser_only(db, auth, &request).await?;
+    validation::validate(db, &request).await?;
+
+    let period = request.period()?;
+
+    // a superuser grant with an end is lost once it lapses
+    if period.is_bounded() && last_superuser(db, &request, true).await? {
+        return Err(AppError::Conflict(t!("role.last_lasting_superuser")));
+    }
+
+    let roles = roles::Entity::find()
+        .tenanted()
+        .filter(roles::Column::Id.is_in(request.roles))
+        .all(db)
+        .await?;
+    let users = bulk_users(db, request.users).await?;
+    let ids = users.iter().map(|user| user.id).collect::<Vec<_>>();
+    let changed = dao::user::regrant(db, actor, users, |_, held| {
+        for role in &roles {
+            match held.iter_mut().find(|(held, _)| held.id == role.id) {
+                Some((_, held)) => *held = period.clone(),
+                None => held.push((role.clone(), period.clone())),
             }
-            .into()
-        }
-        Ok(role) => CreatedWithId {
-            id: role.id,
-            message: format!("Role {} has been created", role.code),
         }
-        .into(),
+    })
+    .await?;
+
+    ids.iter().for_each(|id| cache.forget(id, None));
+
+    Ok(Ok {
+        message: t!("role.assigned", count = changed),
     }
+    .into())
 }
 
-pub async fn show<I: Into<Id>>(db: &DatabaseConnection, id: I) -> HttpResponse {
-    match dao::role::find(db, id).await {
-        None => HttpResponse::NotFound().finish(),
-        Some(role) => RoleOAS::from(role).into(),
+/// Revoke every role of the request from each of its users, unless that
+/// leaves nobody holding a superuser role.
+pub async fn revoke(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    auth: &Auth,
+    actor: &Actor,
+    request: RoleBulkRequest,
+) -> Result<HttpResponse, AppError> {
+    superuser_only(db, auth, &request).await?;
+    validation::validate(db, &request).await?;
+
+    if last_superuser(db, &request, false).await? {
+        return Err(AppError::Conflict(t!("role.last_superuser")));
+    }
+
+    let users = bulk_users(db, request.users).await?;
+    let ids = users.iter().map(|user| user.id).collect::<Vec<_>>();
+    let changed = dao::user::regrant(db, actor, users, |_, held| {
+        held.retain(|(role, _)| !request.roles.contains(&role.id));
+    })
+    .await?;
+
+    ids.iter().for_each(|id| cache.forget(id, None));
+
+    Ok(Ok {
+        message: t!("role.revoked", count = changed),
     }
+    .into())
 }
 
-pub async fn update<I: Into<Id>>(
+/// See [`services::user::superuser_only`], for the users and roles of the
+/// request.
+async fn superuser_only(
     db: &DatabaseConnection,
-    id: I,
-    request: RoleUpdateRequest,
-) -> HttpResponse {
-    let id: Id = id.into();
-    let mut validation = HashMap::new();
-    let name = request.name.trim().to_lowercase();
+    auth: &Auth,
+    request: &RoleBulkRequest,
+) -> Result<(), AppError> {
+    services::user::superuser_only(
+        db,
+        auth,
+        tenant::required()?,
+        &request.users,
+        &request.roles,
+    )
+    .await
+}
+
+/// Whether the users of the request hold the last superuser roles of the
+/// tenant through the roles of the request, so that taking those away from
+/// them leaves nobody holding one, or nobody holding one without an end
+/// when `lasting`.
+async fn last_superuser(
+    db: &DatabaseConnection,
+    request: &RoleBulkRequest,
+    lasting: bool,
+) -> Result<bool, AppError> {
+    let superuser = dao::role::superuser(db, tenant::required()?).await?;
 
-    if name.is_empty() {
-        validation.insert("name", vec!["Name field is required"]);
+    if !request.roles.iter().any(|role| superuser.contains(role)) {
+        return Ok(false);
     }
 
-    if !validation.is_empty() {
-        return UnprocessableEntity { errors: validation }.into();
+    let kept = superuser
+        .iter()
+        .filter(|role| !request.roles.contains(role))
+        .cloned()
+        .collect();
+    let (holders, kept) = match lasting {
+        true => (
+            dao::user::lasting_holders(db, superuser).await?,
+            dao::user::lasting_holders(db, kept).await?,
+        ),
+        false => (
+            dao::user::holders(db, superuser).await?,
+            dao::user::holders(db, kept).await?,
+        ),
+    };
+    let left = holders.iter().any(|holder| !request.users.contains(holder)) || !kept.is_empty();
+
+    Ok(!holders.is_empty() && !left)
+}
+
+pub async fn parents<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Result<HttpResponse, AppError> {
+    let role = dao::role::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("role.not_found")))?;
+    let parents = dao::role::parents(db, &role).await?;
+
+    Ok(RoleListResponse {
+        data: parents.iter().map(RoleOAS::from).collect(),
     }
+    .into())
+}
 
-    let role = dao::role::find(db, id).await;
+pub async fn set_parents<I: Into<Id>>(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    auth: &Auth,
+    actor: &Actor,
+    id: I,
+    request: RoleParentRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
+
+    let role = dao::role::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("role.not_found")))?;
+
+    managed(&role)?;
+
+    // the roles above a superuser role, or one above it, hold superuser
+    let superuser = dao::role::superuser(db, role.tenant_id).await?;
+
+    if !auth.is_superuser() && superuser.contains(&role.id) {
+        return Err(AppError::Forbidden(t!("user.superuser_only")));
+    }
 
-    if role.is_none() {
-        return HttpResponse::NotFound().finish();
+    if dao::role::cyclic(&dao::role::edges(db).await?, &role.id, &request.parents) {
+        return Err(invalid("parents", "validation.cycle", vec![]));
     }
 
-    match dao::role::update(db, role.unwrap(), request).await {
-        Err(e) => {
-            log::error!(update, "{}", e);
+    let parents = roles::Entity::find()
+        .tenanted()
+        .filter(roles::Column::Id.is_in(request.parents))
+        .all(db)
+        .await?;
+    let parents = dao::role::set_parents(db, actor, role, parents).await?;
 
-            InternalServerError {
-                message: e.to_string(),
-            }
-            .into()
-        }
-        Ok(role) => RoleOAS::from(role).into(),
+    // whoever holds a role above it now holds more, or less
+    cache.flush();
+
+    Ok(RoleListResponse {
+        data: parents.iter().map(RoleOAS::from).collect(),
     }
+    .into())
 }
 
-pub async fn delete<I: Into<Id>>(db: &DatabaseConnection, id: I) -> HttpResponse {
-    let role = dao::role::find(db, id).await;
+#[cfg(test)]
+pub mod test {
+    use sea_orm::{ActiveModelTrait, Set};
+    use uuid::Uuid;
+
+    use crate::dao::{self, grant::test::assign, grant::test::role, grant::Period};
+    use crate::middlewares::audit::Actor;
+    use crate::middlewares::auth::test::auth;
+    use crate::middlewares::auth::Authenticated;
+    use crate::models::roles;
+    use crate::requests::role::{RoleBulkRequest, RoleParentRequest};
+    use crate::responses::AppError;
+    use crate::tenant;
+
+    #[test]
+    pub async fn admin_must_not_assign_or_revoke_superuser_roles() {
+        let db = dao::test::database().await;
+        let user = dao::user::test::user(&db).await;
+        let superuser = role(&db, &user, "SUPERUSER").await;
+        let teacher = role(&db, &user, "TEACHER").await;
+        let admin = auth(Uuid::new_v4(), user.id, &["ADMIN"]);
+        let cache = Authenticated::new();
+        let request = |role: Uuid| RoleBulkRequest {
+            roles: vec![role],
+            users: vec![user.id],
+            starts_at: None,
+            expires_at: None,
+        };
+        let assigned = tenant::scope(
+            user.tenant_id,
+            super::assign(&db, &cache, &admin, &Actor::system(), request(superuser.id)),
+        )
+        .await;
+
+        assert!(matches!(assigned, Err(AppError::Forbidden(_))));
 
-    if role.is_none() {
-        return HttpResponse::NotFound().finish();
+        assign(&db, &user, &superuser, Period::default()).await;
+
+        let revoked = tenant::scope(
+            user.tenant_id,
+            super::revoke(&db, &cache, &admin, &Actor::system(), request(teacher.id)),
+        )
+        .await;
+
+        assert!(matches!(revoked, Err(AppError::Forbidden(_))));
     }
 
-    match dao::role::delete(db, role.unwrap()).await {
-        Err(e) => {
-            log::error!(delete, "{}", e);
+    #[test]
+    pub async fn admin_must_not_place_roles_above_superuser_roles() {
+        let db = dao::test::database().await;
+        let user = dao::user::test::user(&db).await;
+        let superuser = role(&db, &user, "SUPERUSER").await;
+        let admin = role(&db, &user, "ADMIN").await;
+        let cache = Authenticated::new();
+        let request = || RoleParentRequest {
+            parents: vec![admin.id],
+        };
+        let placed = tenant::scope(
+            user.tenant_id,
+            super::set_parents(
+                &db,
+                &cache,
+                &auth(Uuid::new_v4(), user.id, &["ADMIN"]),
+                &Actor::system(),
+                superuser.id,
+                request(),
+            ),
+        )
+        .await;
 
-            InternalServerError {
-                message: e.to_string(),
-            }
-            .into()
-        }
-        Ok(role) => Ok {
-            message: format!("Role {} has been deleted", role.code),
+        assert!(matches!(placed, Err(AppError::Forbidden(_))));
+
+        let placed = tenant::scope(
+            user.tenant_id,
+            super::set_parents(
+                &db,
+                &cache,
+                &auth(Uuid::new_v4(), user.id, &["SUPERUSER"]),
+                &Actor::system(),
+                superuser.id,
+                request(),
+            ),
+        )
+        .await;
+
+        assert!(placed.is_ok());
+
+        let system = roles::ActiveModel {
+            system: Set(true),
+            ..admin.clone().into()
         }
-        .into(),
+        .update(&db)
+        .await
+        .unwrap();
+        let placed = tenant::scope(
+            user.tenant_id,
+            super::set_parents(
+                &db,
+                &cache,
+                &auth(Uuid::new_v4(), user.id, &["SUPERUSER"]),
+                &Actor::system(),
+                system.id,
+                RoleParentRequest { parents: vec![] },
+            ),
+        )
+        .await;
+
+        assert!(matches!(placed, Err(AppError::Conflict(_))));
     }
 }
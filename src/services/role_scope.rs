@@ -0,0 +1,97 @@
+use actix_web::HttpResponse;
+use sea_orm::DatabaseConnection;
+
+use crate::dao;
+use crate::i18n::t;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::Authenticated;
+use crate::models::{users, Id};
+use crate::requests::role_scope::RoleScopeStoreRequest;
+use crate::responses::role_scope::{RoleScopeListResponse, RoleScopeOAS};
+use crate::responses::{AppError, CreatedWithId, Ok};
+use crate::validation;
+
+async fn user<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Result<users::Model, AppError> {
+    let (user, _, _) = dao::user::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("user.not_found")))?;
+
+    Ok(user)
+}
+
+pub async fn all<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Result<HttpResponse, AppError> {
+    let user = user(db, id).await?;
+    let scopes = dao::role_scope::all(db, user.id).await?;
+
+    Ok(RoleScopeListResponse {
+        data: scopes.iter().map(RoleScopeOAS::from).collect(),
+    }
+    .into())
+}
+
+pub async fn store<I: Into<Id>>(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    actor: &Actor,
+    id: I,
+    request: RoleScopeStoreRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
+
+    let user = user(db, id).await?;
+    let role = dao::role::find(db, request.role_id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("role.not_found")))?;
+    let scope = format!("{}:{}", request.scope_type, request.scope_id);
+
+    if dao::role_scope::assigned(db, &user, &request).await? {
+        return Err(AppError::Conflict(t!(
+            "role_scope.exists",
+            username = user.username,
+            code = role.code,
+            scope = scope
+        )));
+    }
+
+    let code = role.code.clone();
+    let assigned = dao::role_scope::store(db, actor, user.clone(), role, request).await?;
+
+    cache.forget(&user.id, None);
+
+    Ok(CreatedWithId {
+        id: assigned.id,
+        message: t!(
+            "role_scope.assigned",
+            code = code,
+            username = user.username,
+            scope = scope
+        ),
+    }
+    .into())
+}
+
+pub async fn delete<U: Into<Id>, I: Into<Id>>(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    actor: &Actor,
+    user_id: U,
+    id: I,
+) -> Result<HttpResponse, AppError> {
+    let user = user(db, user_id).await?;
+    let (scope, role) = dao::role_scope::find(db, user.id, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(t!("role_scope.not_found")))?;
+    let scope = dao::role_scope::delete(db, actor, user.clone(), scope).await?;
+
+    cache.forget(&user.id, None);
+
+    Ok(Ok {
+        message: t!(
+            "role_scope.revoked",
+            code = role.code,
+            username = user.username,
+            scope = format!("{}:{}", scope.scope_type, scope.scope_id)
+        ),
+    }
+    .into())
+}
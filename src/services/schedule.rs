@@ -0,0 +1,15 @@
+use actix_web::HttpResponse;
+use sea_orm::DatabaseConnection;
+
+use crate::dao;
+use crate::responses::schedule::{ScheduledTaskListResponse, ScheduledTaskOAS};
+use crate::responses::AppError;
+
+pub async fn all(db: &DatabaseConnection) -> Result<HttpResponse, AppError> {
+    let tasks = dao::schedule::all(db).await?;
+
+    Ok(ScheduledTaskListResponse {
+        data: tasks.iter().map(ScheduledTaskOAS::from).collect(),
+    }
+    .into())
+}
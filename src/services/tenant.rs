@@ -0,0 +1,60 @@
+use actix_web::HttpResponse;
+use sea_orm::DatabaseConnection;
+
+use crate::dao;
+use crate::i18n::t;
+use crate::middlewares::audit::Actor;
+use crate::models::Id;
+use crate::requests::tenant::{TenantStoreRequest, TenantUpdateRequest};
+use crate::responses::tenant::{TenantListResponse, TenantOAS};
+use crate::responses::{AppError, CreatedWithId};
+use crate::validation;
+
+pub async fn all(db: &DatabaseConnection) -> Result<HttpResponse, AppError> {
+    let tenants = dao::tenant::all(db).await?;
+
+    Ok(TenantListResponse {
+        data: tenants.iter().map(TenantOAS::from).collect(),
+    }
+    .into())
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    request: TenantStoreRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
+
+    let tenant = dao::tenant::store(db, actor, request).await?;
+
+    Ok(CreatedWithId {
+        id: tenant.id,
+        message: t!("tenant.created", code = tenant.code),
+    }
+    .into())
+}
+
+pub async fn show<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Result<HttpResponse, AppError> {
+    let tenant = dao::tenant::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("tenant.not_found")))?;
+
+    Ok(TenantOAS::from(tenant).into())
+}
+
+pub async fn update<I: Into<Id>>(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    id: I,
+    request: TenantUpdateRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
+
+    let tenant = dao::tenant::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("tenant.not_found")))?;
+    let tenant = dao::tenant::update(db, actor, tenant, request).await?;
+
+    Ok(TenantOAS::from(tenant).into())
+}
@@ -1,27 +1,68 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use actix_web::HttpResponse;
-use sea_orm::{prelude::*, QueryOrder, QuerySelect};
-use sea_query::Condition;
+use actix_multipart::Multipart;
+use actix_web::{web, HttpResponse};
+use sea_orm::{prelude::*, QueryOrder, QuerySelect, Select, TransactionTrait};
+use sea_query::{Condition, SimpleExpr};
 
-use crate::common::hash::Hash;
-use crate::common::{hash, log};
+use crate::common::{base58, env, log, time};
 use crate::dao;
+use crate::i18n::t;
+use crate::mail::SendMail;
+use crate::middlewares::audit::Actor;
+use crate::middlewares::auth::{blocked, superusers, Auth, Authenticated};
 use crate::models::{permission_user, permissions, role_user, roles, users, Id};
 use crate::requests::user::{
-    UserStoreRequest, UserUpdateGeneralInformationRequest, UserUpdatePasswordRequest,
+    AccountStatus, ImportMode, UserDeactivateRequest, UserFilterRequest, UserImportOptionsRequest,
+    UserPermissionCheckRequest, UserSetPasswordRequest, UserStoreRequest, UserSuspendRequest,
+    UserUpdateGeneralInformationRequest,
 };
 use crate::requests::PaginationRequest;
-use crate::responses::permission::PermissionOAS;
+use crate::responses::permission::{
+    EffectivePermissionListResponse, EffectivePermissionOAS, PermissionCheckOAS, PermissionOAS,
+    PermissionSourceOAS,
+};
 use crate::responses::role::RoleOAS;
-use crate::responses::user::{UserOAS, UserPaginationResponse};
-use crate::responses::{InternalServerError, Ok, UnprocessableEntity};
-
-pub async fn paginate(
-    db: &DatabaseConnection,
-    request: PaginationRequest<users::Column>,
-) -> HttpResponse {
-    let mut query = users::Entity::find().filter(users::Column::DeletedAt.is_null());
+use crate::responses::user::{
+    ImportStatus, UserExportRecord, UserImportResponse, UserImportRowOAS, UserOAS,
+    UserPaginationResponse,
+};
+use crate::responses::{AppError, Ok};
+use crate::services;
+use crate::services::export::Export;
+use crate::sheet::{self, ExportFormat, Format};
+use crate::tenant::Tenanted;
+use crate::validation::{self, invalid};
+
+fn search(
+    request: &PaginationRequest<users::Column>,
+    filter: &UserFilterRequest,
+) -> Select<users::Entity> {
+    let mut query = users::Entity::find()
+        .tenanted()
+        .filter(users::Column::DeletedAt.is_null());
+
+    if let Some(status) = filter.status {
+        let suspended = users::Column::Status.eq(AccountStatus::Suspended.as_str());
+        let running = Condition::any()
+            .add(users::Column::SuspendedUntil.is_null())
+            .add(users::Column::SuspendedUntil.gt(time::now()));
+
+        query = query.filter(match status {
+            // suspensions that ran out count as active
+            AccountStatus::Active => Condition::any()
+                .add(users::Column::Status.eq(AccountStatus::Active.as_str()))
+                .add(
+                    Condition::all()
+                        .add(suspended)
+                        .add(users::Column::SuspendedUntil.lte(time::now())),
+                ),
+            AccountStatus::Suspended => Condition::all().add(suspended).add(running),
+            AccountStatus::Deactivated => {
+                Condition::all().add(users::Column::Status.eq(status.as_str()))
+            }
+        });
+    }
 
     if let Some(search) = request.search() {
         let search = format!("%{}%", search.to_lowercase());
@@ -34,528 +75,891 @@ pub async fn paginate(
         );
     }
 
-    let total = query.clone().count(db).await;
+    query
+}
 
-    if let Err(e) = total {
-        log::error!(paginate, "{}", e);
+pub async fn paginate(
+    db: &DatabaseConnection,
+    request: PaginationRequest<users::Column>,
+    filter: UserFilterRequest,
+) -> Result<HttpResponse, AppError> {
+    page(db, search(&request, &filter), &request).await
+}
 
-        return InternalServerError {
-            message: e.to_string(),
-        }
-        .into();
-    }
+/// Paginate the users matching `condition` along with the search, like
+/// [`paginate`], such as those holding a role.
+pub async fn holding(
+    db: &DatabaseConnection,
+    condition: SimpleExpr,
+    request: PaginationRequest<users::Column>,
+    filter: UserFilterRequest,
+) -> Result<HttpResponse, AppError> {
+    page(db, search(&request, &filter).filter(condition), &request).await
+}
 
-    let total = total.unwrap();
+async fn page(
+    db: &DatabaseConnection,
+    query: Select<users::Entity>,
+    request: &PaginationRequest<users::Column>,
+) -> Result<HttpResponse, AppError> {
+    let total = query.clone().count(db).await?;
     let users = query
         .limit(request.limit())
         .offset(request.offset())
         .order_by(request.order(users::Column::CreatedAt), request.sort())
-        .all(db);
-
-    match users.await {
-        Err(e) => {
-            log::error!(paginate, "{}", e);
-
-            InternalServerError {
-                message: e.to_string(),
-            }
-            .into()
-        }
-        Ok(users) => {
-            let permission_user = permission_user::Entity::find()
-                .find_with_related(permissions::Entity)
-                .filter(
-                    permission_user::Column::UserId.is_in(
-                        users
-                            .iter()
-                            .map(|user| user.id.clone())
-                            .collect::<Vec<Id>>(),
-                    ),
-                )
-                .all(db)
-                .await;
-
-            if let Err(e) = permission_user {
-                log::error!(paginate, "{}", e);
-
-                return InternalServerError {
-                    message: e.to_string(),
+        .all(db)
+        .await?;
+
+    let permission_user = permission_user::Entity::find()
+        .find_with_related(permissions::Entity)
+        .filter(
+            permission_user::Column::UserId
+                .is_in(users.iter().map(|user| user.id).collect::<Vec<Id>>()),
+        )
+        .all(db)
+        .await?;
+
+    let role_user = role_user::Entity::find()
+        .find_with_related(roles::Entity)
+        .filter(
+            role_user::Column::UserId.is_in(users.iter().map(|user| user.id).collect::<Vec<Id>>()),
+        )
+        .all(db)
+        .await?;
+
+    let users = users
+        .iter()
+        .map(|user| {
+            let mut user = UserOAS::from(user);
+
+            for (permission_user, permissions) in permission_user.clone() {
+                if permission_user.user_id == user.id {
+                    user.permissions
+                        .extend(permissions.iter().map(PermissionOAS::from));
                 }
-                .into();
             }
 
-            let permission_user = permission_user.unwrap();
-
-            let role_user = role_user::Entity::find()
-                .find_with_related(roles::Entity)
-                .filter(
-                    role_user::Column::UserId.is_in(
-                        users
-                            .iter()
-                            .map(|user| user.id.clone())
-                            .collect::<Vec<Id>>(),
-                    ),
-                )
-                .all(db)
-                .await;
-
-            if let Err(e) = role_user {
-                log::error!(paginate, "{}", e);
-
-                return InternalServerError {
-                    message: e.to_string(),
+            for (role_user, roles) in role_user.clone() {
+                if role_user.user_id == user.id {
+                    user.roles.extend(roles.iter().map(RoleOAS::from));
                 }
-                .into();
             }
 
-            let role_user = role_user.unwrap();
+            user
+        })
+        .collect();
 
-            let users = users
-                .iter()
-                .map(|user| {
-                    let mut user = UserOAS::from(user);
-
-                    for (permission_user, permissions) in permission_user.clone() {
-                        if permission_user.user_id == user.id {
-                            user.permissions
-                                .extend(permissions.iter().map(PermissionOAS::from));
-                        }
-                    }
-
-                    for (role_user, roles) in role_user.clone() {
-                        if role_user.user_id == user.id {
-                            user.roles.extend(roles.iter().map(RoleOAS::from));
-                        }
-                    }
-
-                    user
-                })
-                .collect();
-
-            UserPaginationResponse {
-                total,
-                page: total / request.limit(),
-                data: users,
-            }
-            .into()
-        }
+    Ok(UserPaginationResponse {
+        total,
+        page: total / request.limit(),
+        data: users,
     }
+    .into())
 }
 
-pub async fn store(db: &DatabaseConnection, request: UserStoreRequest) -> HttpResponse {
-    let mut validation = HashMap::new();
-    let name = request.name.trim().to_lowercase();
-    let email = request.email.trim().to_lowercase();
-    let username = request.username.trim().to_lowercase();
-    let password = request.password.trim().to_lowercase();
-    let permissions = request.permissions.clone();
-    let roles = request.roles.clone();
+pub async fn export(
+    db: &DatabaseConnection,
+    request: PaginationRequest<users::Column>,
+    filter: UserFilterRequest,
+    format: ExportFormat,
+) -> Result<HttpResponse, AppError> {
+    let query = search(&request, &filter)
+        .order_by(request.order(users::Column::CreatedAt), request.sort())
+        .order_by_asc(users::Column::Id);
+
+    services::export::stream(db, query, format, "users")
+}
 
-    if name.is_empty() {
-        validation.insert("name", vec!["Name field is required"]);
+impl Export for users::Entity {
+    type Record = UserExportRecord;
+
+    async fn records(
+        db: &DatabaseConnection,
+        users: Vec<users::Model>,
+    ) -> Result<Vec<UserExportRecord>, DbErr> {
+        let ids = users.iter().map(|user| user.id).collect::<Vec<Id>>();
+        let roles = role_user::Entity::find()
+            .find_also_related(roles::Entity)
+            .filter(role_user::Column::UserId.is_in(ids.clone()))
+            .all(db)
+            .await?;
+        let permissions = permission_user::Entity::find()
+            .find_also_related(permissions::Entity)
+            .filter(permission_user::Column::UserId.is_in(ids))
+            .all(db)
+            .await?;
+
+        Ok(users
+            .into_iter()
+            .map(|user| UserExportRecord {
+                roles: roles
+                    .iter()
+                    .filter(|(role_user, _)| role_user.user_id == user.id)
+                    .filter_map(|(_, role)| role.as_ref().map(|role| role.code.clone()))
+                    .collect(),
+                permissions: permissions
+                    .iter()
+                    .filter(|(permission_user, _)| permission_user.user_id == user.id)
+                    .filter_map(|(_, permission)| {
+                        permission
+                            .as_ref()
+                            .map(|permission| permission.code.clone())
+                    })
+                    .collect(),
+                id: user.id,
+                name: user.name,
+                email: user.email,
+                username: user.username,
+                locale: user.locale,
+                email_verified_at: user.email_verified_at,
+                created_at: user.created_at,
+            })
+            .collect())
     }
+}
 
-    if email.is_empty() {
-        validation.insert("email", vec!["Email field is required"]);
-    } else {
-        let mut errors = vec![];
-        let exist = dao::user::email_exist(db, email.clone()).await;
-
-        if let Err(e) = exist {
-            log::error!(store, "{}", e);
+pub async fn store(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    request: UserStoreRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
 
-            return InternalServerError {
-                message: e.to_string(),
-            }
-            .into();
-        }
+    let user = dao::user::store(db, actor, request).await?;
 
-        if exist.unwrap() {
-            errors.push("Email already exist");
-        }
+    Ok(UserOAS::from(user).into())
+}
 
-        if !email.contains("@") {
-            errors.push("Email is invalid");
-        }
+const IMPORT_COLUMNS: [&str; 3] = ["name", "email", "username"];
 
-        if !errors.is_empty() {
-            validation.insert("email", errors);
-        }
-    }
+struct Candidate {
+    line: usize,
+    request: UserStoreRequest,
+    generated: bool,
+    errors: HashMap<String, Vec<String>>,
+    id: Option<Id>,
+}
 
-    if username.is_empty() {
-        validation.insert("username", vec!["Username field is required"]);
-    } else {
-        let mut errors = vec![];
-        let exist = dao::user::username_exist(db, username).await;
+/// A random password passing the password rules.
+fn generate_password() -> String {
+    format!("{}!9A", base58::to_string(Uuid::new_v4().as_bytes()))
+}
 
-        if let Err(e) = exist {
-            log::error!(store, "{}", e);
+fn role_codes(cell: &str) -> Vec<String> {
+    cell.split([',', ';'])
+        .map(|code| code.trim().to_uppercase())
+        .filter(|code| !code.is_empty())
+        .collect()
+}
 
-            return InternalServerError {
-                message: e.to_string(),
-            }
-            .into();
-        }
+/// Create a user for every row of a csv or xlsx file, validating each the
+/// same way as [`store`] and reporting the outcome row by row.
+pub async fn import(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    payload: Multipart,
+    options: UserImportOptionsRequest,
+) -> Result<HttpResponse, AppError> {
+    let dry_run = options.dry_run.unwrap_or(false);
+    let mode = options.mode.unwrap_or_default();
+    let max = env::var("IMPORT_MAX_SIZE", 5 * 1024 * 1024usize);
+    let (name, bytes) = services::file::receive(payload, max).await?;
+    let format =
+        Format::detect(&name).ok_or_else(|| invalid("file", "validation.spreadsheet", vec![]))?;
+    let (columns, rows) = web::block(move || sheet::read(format, &bytes))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .map_err(|_| invalid("file", "validation.spreadsheet", vec![]))?;
+
+    let missing = IMPORT_COLUMNS
+        .iter()
+        .filter(|column| !columns.iter().any(|c| c == *column))
+        .copied()
+        .collect::<Vec<_>>();
+
+    if !missing.is_empty() {
+        return Err(invalid(
+            "file",
+            "validation.columns",
+            vec![("columns".to_string(), missing.join(", "))],
+        ));
+    }
 
-        if exist.unwrap() {
-            errors.push("Username already exist");
-        }
+    let limit = env::var("IMPORT_MAX_ROWS", 1000usize);
 
-        if !errors.is_empty() {
-            validation.insert("username", errors);
-        }
+    if rows.len() > limit {
+        return Err(invalid(
+            "file",
+            "validation.rows_max",
+            vec![("max".to_string(), limit.to_string())],
+        ));
     }
 
-    if password.is_empty() {
-        validation.insert("password", vec!["Password field is required"]);
-    } else {
-        let mut errors = vec![];
+    let roles = roles::Entity::find()
+        .tenanted()
+        .filter(
+            roles::Column::Code.is_in(
+                rows.iter()
+                    .flat_map(|row| role_codes(row.get("roles")))
+                    .collect::<HashSet<_>>(),
+            ),
+        )
+        .all(db)
+        .await?;
+
+    let (mut emails, mut usernames) = (HashSet::new(), HashSet::new());
+    let mut candidates = vec![];
+
+    for row in rows {
+        let codes = role_codes(row.get("roles"));
+        let generated = row.get("password").is_empty();
+        let request = UserStoreRequest {
+            name: row.get("name").to_string(),
+            email: row.get("email").to_string(),
+            username: row.get("username").to_string(),
+            password: match generated {
+                true => generate_password(),
+                false => row.get("password").to_string(),
+            },
+            permissions: vec![],
+            roles: roles
+                .iter()
+                .filter(|role| codes.contains(&role.code))
+                .map(|role| role.id)
+                .collect(),
+        };
+
+        let mut errors = match validation::validate(db, &request).await {
+            Ok(_) => HashMap::new(),
+            Err(AppError::Validation(errors)) => errors,
+            Err(e) => return Err(e),
+        };
+
+        if request.roles.len() < codes.len() && !errors.contains_key("roles") {
+            errors.insert(
+                "roles".to_string(),
+                vec![validation::message("roles", "validation.exists", vec![])],
+            );
+        }
+
+        // the database rules cannot see the other rows of the file
+        if !emails.insert(request.email.trim().to_lowercase()) && !errors.contains_key("email") {
+            errors.insert(
+                "email".to_string(),
+                vec![validation::message("email", "validation.distinct", vec![])],
+            );
+        }
+
+        if !usernames.insert(request.username.trim().to_lowercase())
+            && !errors.contains_key("username")
+        {
+            errors.insert(
+                "username".to_string(),
+                vec![validation::message(
+                    "username",
+                    "validation.distinct",
+                    vec![],
+                )],
+            );
+        }
+
+        candidates.push(Candidate {
+            line: row.line,
+            request,
+            generated,
+            errors,
+            id: None,
+        });
+    }
 
-        if password.len() < 6 {
-            errors.push("Password must be at least 6 characters");
-        }
+    let valid = candidates
+        .iter()
+        .all(|candidate| candidate.errors.is_empty());
+    let mut imported = vec![];
 
-        if password.to_lowercase() == password {
-            errors.push("Password must contain at least 1 uppercase character");
-        }
+    if !dry_run && mode == ImportMode::Atomic && valid {
+        let tx = db.begin().await?;
 
-        if !password.chars().any(|c| c.is_digit(10)) {
-            errors.push("Password must contain at least 1 digit");
-        }
+        for candidate in candidates.iter_mut() {
+            let (user, _, _) = dao::user::store(&tx, actor, candidate.request.clone()).await?;
 
-        if !password.chars().any(|c| !c.is_alphanumeric()) {
-            errors.push("Password must contain at least 1 special character");
+            candidate.id = Some(user.id);
+            imported.push((user, candidate.generated));
         }
 
-        if !errors.is_empty() {
-            validation.insert("password", errors);
-        }
+        tx.commit().await?;
     }
 
-    if !permissions.is_empty() {
-        let permissions = permissions::Entity::find()
-            .filter(permissions::Column::Code.is_in(permissions))
-            .all(db)
-            .await;
-
-        if let Err(e) = permissions {
-            log::error!(store, "{}", e);
-
-            return InternalServerError {
-                message: e.to_string(),
+    if !dry_run && mode == ImportMode::Partial {
+        for candidate in candidates.iter_mut() {
+            if !candidate.errors.is_empty() {
+                continue;
             }
-            .into();
-        }
 
-        if request.permissions.len() != permissions.unwrap().len() {
-            validation.insert("permissions", vec!["Some permissions are invalid"]);
-        }
+            match dao::user::store(db, actor, candidate.request.clone()).await {
+                Ok((user, _, _)) => {
+                    candidate.id = Some(user.id);
+                    imported.push((user, candidate.generated));
+                }
+                Err(e) => {
+                    log::error!(import, "{}", e);
 
-        if !validation.is_empty() {
-            return UnprocessableEntity { errors: validation }.into();
+                    candidate
+                        .errors
+                        .insert("row".to_string(), vec![t!("errors.unexpected")]);
+                }
+            }
         }
     }
 
-    if !roles.is_empty() {
-        let roles = roles::Entity::find()
-            .filter(roles::Column::Code.is_in(roles))
-            .all(db)
-            .await;
-
-        if let Err(e) = roles {
-            log::error!(store, "{}", e);
-
-            return InternalServerError {
-                message: e.to_string(),
-            }
-            .into();
+    // jobs are listed along with their payload, so the generated password
+    // stays unknown and the user chooses one through a link instead
+    for (user, generated) in imported.iter() {
+        if !generated {
+            continue;
         }
 
-        if request.roles.len() != roles.unwrap().len() {
-            validation.insert("roles", vec!["Some roles are invalid"]);
-        }
+        let mail = SendMail::new(user, "invite")
+            .param("url", services::auth::password_url(user))
+            .dispatch(db)
+            .await;
 
-        if !validation.is_empty() {
-            return UnprocessableEntity { errors: validation }.into();
+        if let Err(e) = mail {
+            log::error!(import, "{}", e);
         }
     }
 
-    if !validation.is_empty() {
-        return UnprocessableEntity { errors: validation }.into();
-    }
-
-    match dao::user::store(db, request).await {
-        Err(e) => {
-            log::error!(store, "{}", e);
-
-            InternalServerError {
-                message: e.to_string(),
-            }
-            .into()
-        }
-        Ok(user) => UserOAS::from(user).into(),
+    let rows = candidates
+        .into_iter()
+        .map(|candidate| UserImportRowOAS {
+            line: candidate.line,
+            email: candidate.request.email.trim().to_lowercase(),
+            username: candidate.request.username.trim().to_lowercase(),
+            status: match (&candidate.id, candidate.errors.is_empty()) {
+                (Some(_), _) => ImportStatus::Imported,
+                (None, false) => ImportStatus::Failed,
+                (None, true) if dry_run => ImportStatus::Valid,
+                (None, true) => ImportStatus::Skipped,
+            },
+            password_generated: candidate.generated,
+            id: candidate.id,
+            errors: candidate.errors,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(UserImportResponse {
+        dry_run,
+        mode,
+        total: rows.len(),
+        imported: imported.len(),
+        failed: rows
+            .iter()
+            .filter(|row| row.status == ImportStatus::Failed)
+            .count(),
+        rows,
     }
+    .into())
 }
 
-pub async fn show<I: Into<Id>>(db: &DatabaseConnection, id: I) -> HttpResponse {
-    match dao::user::find(db, id).await {
-        None => HttpResponse::NotFound().finish(),
-        Some(user) => UserOAS::from(user).into(),
-    }
+pub async fn show<I: Into<Id>>(db: &DatabaseConnection, id: I) -> Result<HttpResponse, AppError> {
+    let user = dao::user::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("user.not_found")))?;
+
+    Ok(UserOAS::from(user).into())
 }
 
 pub async fn update_general_information<I: Into<Id>>(
     db: &DatabaseConnection,
+    cache: &Authenticated,
+    auth: &Auth,
+    actor: &Actor,
     id: I,
     request: UserUpdateGeneralInformationRequest,
-) -> HttpResponse {
+) -> Result<HttpResponse, AppError> {
     let user = dao::user::find(db, id).await;
 
-    if user.is_none() {
-        return HttpResponse::NotFound().finish();
-    }
+    let user = user.ok_or_else(|| AppError::NotFound(t!("user.not_found")))?;
 
-    let (user, _, _) = user.unwrap();
-    let mut validation = HashMap::new();
-    let name = request.name.trim().to_lowercase();
-    let email = request.email.trim().to_lowercase();
-    let username = request.username.trim().to_lowercase();
-    let permissions = request.permissions.clone();
-    let roles = request.roles.clone();
+    superuser_only(db, auth, user.0.tenant_id, &[user.0.id], &request.roles).await?;
+    validation::validate_with(db, &request, &(user.0.id, actor.user_id)).await?;
+    keep_superuser(db, &user.0, Some(&request.roles)).await?;
 
-    if name.is_empty() {
-        validation.insert("name", vec!["Name field is required"]);
-    }
+    let user = dao::user::update_general_information(db, actor, user, request).await?;
 
-    if email.is_empty() {
-        validation.insert("email", vec!["Email field is required"]);
-    } else {
-        let mut errors = vec![];
-        let exist = dao::user::email_exist_except(db, email.clone(), user.id.clone()).await;
+    // the roles and permissions it holds may have changed
+    cache.forget(&user.0.id, None);
 
-        if let Err(e) = exist {
-            log::error!(update_general_information, "{}", e);
+    Ok(UserOAS::from(user).into())
+}
 
-            return InternalServerError {
-                message: e.to_string(),
-            }
-            .into();
-        }
+pub async fn set_password<I: Into<Id>>(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    auth: &Auth,
+    actor: &Actor,
+    id: I,
+    request: UserSetPasswordRequest,
+) -> Result<HttpResponse, AppError> {
+    let user = dao::user::find(db, id).await;
 
-        if exist.unwrap() && email != user.email {
-            errors.push("Email already exist");
-        }
+    let (user, _, _) = user.ok_or_else(|| AppError::NotFound(t!("user.not_found")))?;
 
-        if !email.contains("@") {
-            errors.push("Email is invalid");
-        }
+    // signing in as a superuser would be as good as being one
+    superuser_only(db, auth, user.tenant_id, &[user.id], &[]).await?;
+    validation::validate(db, &request).await?;
 
-        if !errors.is_empty() {
-            validation.insert("email", errors);
-        }
+    let user = dao::user::update_password(db, actor, user, request.password).await?;
+
+    dao::auth::revoke(db, user.id, None).await?;
+    cache.forget(&user.id, None);
+    password_changed(db, &user).await;
+
+    Ok(Ok {
+        message: t!("user.password_updated", username = user.username),
     }
+    .into())
+}
 
-    if username.is_empty() {
-        validation.insert("username", vec!["Username field is required"]);
-    } else {
-        let mut errors = vec![];
-        let exist = dao::user::username_exist_except(db, username.clone(), user.id.clone()).await;
+/// Let `user` know their password was changed, failing silently.
+pub async fn password_changed(db: &DatabaseConnection, user: &users::Model) {
+    let mail = SendMail::new(user, "password_changed")
+        .param("time", user.updated_at.format("%Y-%m-%d %H:%M"))
+        .dispatch(db)
+        .await;
 
-        if let Err(e) = exist {
-            log::error!(update_general_information, "{}", e);
+    if let Err(e) = mail {
+        log::error!(password_changed, "{}", e);
+    }
+}
 
-            return InternalServerError {
-                message: e.to_string(),
-            }
-            .into();
-        }
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    actor: &Actor,
+    id: I,
+) -> Result<HttpResponse, AppError> {
+    let user = dao::user::find(db, id).await;
 
-        if exist.unwrap() && username != user.username {
-            errors.push("Username already exist");
-        }
+    let (user, _, _) = user.ok_or_else(|| AppError::NotFound(t!("user.not_found")))?;
 
-        if !errors.is_empty() {
-            validation.insert("username", errors);
-        }
-    }
+    keep_superuser(db, &user, None).await?;
 
-    if !permissions.is_empty() {
-        let mut errors = vec![];
-        let permissions = permissions::Entity::find()
-            .filter(permissions::Column::Code.is_in(permissions))
-            .all(db)
-            .await;
+    let user = dao::user::delete(db, actor, user).await?;
 
-        if let Err(e) = permissions {
-            log::error!(update_general_information, "{}", e);
+    dao::auth::revoke(db, user.id, None).await?;
+    cache.forget(&user.id, None);
 
-            return InternalServerError {
-                message: e.to_string(),
-            }
-            .into();
-        }
+    Ok(Ok {
+        message: t!("user.deleted", username = user.username),
+    }
+    .into())
+}
 
-        if request.permissions.len() != permissions.unwrap().len() {
-            errors.push("Some permissions are invalid");
-        }
+pub async fn effective_permissions<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Result<HttpResponse, AppError> {
+    let (user, _, _) = dao::user::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("user.not_found")))?;
+    let (_, effective) = dao::permission::explain(db, user.id).await?;
+
+    Ok(EffectivePermissionListResponse {
+        data: effective.iter().map(EffectivePermissionOAS::from).collect(),
+    }
+    .into())
+}
 
-        if !errors.is_empty() {
-            validation.insert("permissions", errors);
-        }
+/// Whether the user behind `id` passes the checks of a permission outside
+/// of any single resource, like [`Auth::can`](crate::middlewares::auth::Auth::can),
+/// and why.
+pub async fn check_permission<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+    request: UserPermissionCheckRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
+
+    let (user, _, _) = dao::user::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("user.not_found")))?;
+    let code = request.permission.trim().to_uppercase();
+    let (roles, effective) = dao::permission::explain(db, user.id).await?;
+    let sources: Vec<PermissionSourceOAS> = effective
+        .iter()
+        .find(|effective| effective.permission.code == code)
+        .map(|effective| effective.sources.iter().map(Into::into).collect())
+        .unwrap_or_default();
+    let superusers = superusers();
+    let superuser = roles.iter().find(|role| superusers.contains(&role.code));
+    let username = user.username.as_str();
+
+    let (allowed, reason) = if let Some(message) = blocked(&user) {
+        (
+            false,
+            t!(
+                "permission.check.blocked",
+                username = username,
+                reason = message
+            ),
+        )
+    } else if let Some(role) = superuser {
+        (
+            true,
+            t!(
+                "permission.check.superuser",
+                username = username,
+                role = role.code
+            ),
+        )
+    } else if !sources.is_empty() {
+        (
+            true,
+            t!(
+                "permission.check.granted",
+                username = username,
+                permission = code
+            ),
+        )
+    } else if dao::permission::find_by_code(db, &code).await?.is_none() {
+        (false, t!("permission.check.unknown", permission = code))
+    } else {
+        (
+            false,
+            t!(
+                "permission.check.denied",
+                username = username,
+                permission = code
+            ),
+        )
+    };
+
+    Ok(PermissionCheckOAS {
+        permission: code,
+        allowed,
+        reason,
+        sources,
     }
+    .into())
+}
 
-    if !roles.is_empty() {
-        let mut errors = vec![];
-        let roles = roles::Entity::find()
-            .filter(roles::Column::Code.is_in(roles))
-            .all(db)
-            .await;
+/// Fail as forbidden when `users` are assigned or `roles` include a
+/// superuser role of `tenant_id`, unless `auth` is a superuser, for an
+/// administrator not to make themselves one.
+pub async fn superuser_only(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    tenant_id: Id,
+    users: &[Id],
+    roles: &[Id],
+) -> Result<(), AppError> {
+    if auth.is_superuser() {
+        return Ok(());
+    }
 
-        if let Err(e) = roles {
-            log::error!(update_general_information, "{}", e);
+    let superuser = dao::role::superuser(db, tenant_id).await?;
 
-            return InternalServerError {
-                message: e.to_string(),
-            }
-            .into();
-        }
+    if roles.iter().any(|role| superuser.contains(role))
+        || dao::user::assigned(db, users, superuser).await?
+    {
+        return Err(AppError::Forbidden(t!("user.superuser_only")));
+    }
 
-        if request.roles.len() != roles.unwrap().len() {
-            errors.push("Some roles are invalid");
-        }
+    Ok(())
+}
 
-        if !errors.is_empty() {
-            validation.insert("roles", errors);
-        }
+/// Fail as a conflict when `user` is the last active superuser of their
+/// tenant and would stop being one, keeping `roles` if they keep any, which
+/// would otherwise leave nobody able to manage it.
+pub async fn keep_superuser(
+    db: &DatabaseConnection,
+    user: &users::Model,
+    roles: Option<&[Id]>,
+) -> Result<(), AppError> {
+    let superuser = dao::role::superuser(db, user.tenant_id).await?;
+
+    if roles.is_some_and(|roles| roles.iter().any(|role| superuser.contains(role))) {
+        return Ok(());
     }
 
-    if !validation.is_empty() {
-        return UnprocessableEntity { errors: validation }.into();
+    match dao::user::holders(db, superuser).await?[..] {
+        [ref holder] if holder == &user.id => Err(AppError::Conflict(t!(
+            "user.last_superuser",
+            username = user.username
+        ))),
+        _ => Ok(()),
     }
+}
 
-    match dao::user::update_general_information(db, user, request).await {
-        Err(e) => {
-            log::error!(update, "{}", e);
+/// The user behind `id` unless it is the actor themselves, who could
+/// otherwise lock themselves out.
+async fn other<I: Into<Id>>(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    id: I,
+) -> Result<users::Model, AppError> {
+    let (user, _, _) = dao::user::find(db, id)
+        .await
+        .ok_or_else(|| AppError::NotFound(t!("user.not_found")))?;
 
-            InternalServerError {
-                message: e.to_string(),
-            }
-            .into()
-        }
-        Ok(user) => UserOAS::from(user).into(),
+    if actor.user_id.as_ref() == Some(&user.id) {
+        return Err(AppError::Conflict(t!("user.own_status")));
     }
+
+    Ok(user)
 }
 
-pub async fn update_password<I: Into<Id>>(
+/// Sign the user out everywhere, cached sessions included.
+async fn sign_out(
     db: &DatabaseConnection,
+    cache: &Authenticated,
+    user: &users::Model,
+) -> Result<(), AppError> {
+    dao::auth::revoke(db, user.id, None).await?;
+    cache.forget(&user.id, None);
+
+    Ok(())
+}
+
+pub async fn suspend<I: Into<Id>>(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    actor: &Actor,
     id: I,
-    request: UserUpdatePasswordRequest,
-) -> HttpResponse {
-    let user = dao::user::find(db, id).await;
+    request: UserSuspendRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
+
+    let until = request.until.map(|until| until.naive_utc());
 
-    if user.is_none() {
-        return HttpResponse::NotFound().finish();
+    if until.is_some_and(|until| until <= time::now()) {
+        return Err(invalid("until", "validation.future", vec![]));
     }
 
-    let (user, _, _) = user.unwrap();
-    let mut validation = HashMap::new();
-    let current_password = request.current_password.clone();
-    let password_confirmation = request.password_confirmation.clone();
-    let new_password = request.new_password.clone();
+    let user = other(db, actor, id).await?;
 
-    if current_password.is_empty() {
-        validation.insert(
-            "current_password",
-            vec!["Current password field is required"],
-        );
-    } else if !hash::verify(
-        Hash::from(user.password.clone()),
-        user.id.clone(),
-        current_password,
-    ) {
-        validation.insert("current_password", vec!["Current password is invalid"]);
+    keep_superuser(db, &user, None).await?;
+
+    let user = dao::user::set_status(
+        db,
+        actor,
+        user,
+        AccountStatus::Suspended,
+        Some(request.reason.trim().to_string()),
+        until,
+    )
+    .await?;
+
+    sign_out(db, cache, &user).await?;
+
+    Ok(Ok {
+        message: t!("user.suspended", username = user.username),
     }
+    .into())
+}
 
-    if new_password.is_empty() {
-        validation.insert("new_password", vec!["New password field is required"]);
-    } else {
-        let mut errors = vec![];
+pub async fn deactivate<I: Into<Id>>(
+    db: &DatabaseConnection,
+    cache: &Authenticated,
+    actor: &Actor,
+    id: I,
+    request: UserDeactivateRequest,
+) -> Result<HttpResponse, AppError> {
+    validation::validate(db, &request).await?;
 
-        if new_password.len() < 6 {
-            errors.push("New password must be at least 6 characters");
-        }
+    let user = other(db, actor, id).await?;
 
-        if new_password.to_lowercase() == new_password {
-            errors.push("New password must contain at least 1 uppercase character");
-        }
+    keep_superuser(db, &user, None).await?;
 
-        if !new_password.chars().any(|c| c.is_digit(10)) {
-            errors.push("New password must contain at least 1 digit");
-        }
+    let user = dao::user::set_status(
+        db,
+        actor,
+        user,
+        AccountStatus::Deactivated,
+        Some(request.reason.trim().to_string()),
+        None,
+    )
+    .await?;
 
-        if !new_password.chars().any(|c| !c.is_alphanumeric()) {
-            errors.push("New password must contain at least 1 special character");
-        }
+    sign_out(db, cache, &user).await?;
 
-        if !errors.is_empty() {
-            validation.insert("new_password", errors);
-        }
+    Ok(Ok {
+        message: t!("user.deactivated", username = user.username),
     }
+    .into())
+}
 
-    if password_confirmation.is_empty() {
-        validation.insert(
-            "password_confirmation",
-            vec!["Password confirmation field is required"],
-        );
-    } else if password_confirmation != new_password {
-        validation.insert(
-            "password_confirmation",
-            vec!["Password confirmation must match new password"],
-        );
+pub async fn reactivate<I: Into<Id>>(
+    db: &DatabaseConnection,
+    actor: &Actor,
+    id: I,
+) -> Result<HttpResponse, AppError> {
+    let user = other(db, actor, id).await?;
+
+    if user.status == AccountStatus::Active.as_str() {
+        return Err(AppError::Conflict(t!(
+            "user.already_active",
+            username = user.username
+        )));
     }
 
-    if !validation.is_empty() {
-        return UnprocessableEntity { errors: validation }.into();
+    let user = dao::user::set_status(db, actor, user, AccountStatus::Active, None, None).await?;
+
+    Ok(Ok {
+        message: t!("user.reactivated", username = user.username),
     }
+    .into())
+}
 
-    match dao::user::update_password(db, user, new_password).await {
-        Err(e) => {
-            log::error!(update_password, "{}", e);
+#[cfg(test)]
+pub mod test {
+    use uuid::Uuid;
+
+    use crate::dao::{self, grant::test::assign, grant::test::role, grant::Period};
+    use crate::middlewares::audit::Actor;
+    use crate::middlewares::auth::test::auth;
+    use crate::middlewares::auth::Authenticated;
+    use crate::requests::user::{UserSetPasswordRequest, UserUpdateGeneralInformationRequest};
+    use crate::responses::AppError;
+    use crate::tenant;
+    use crate::validation;
+
+    use super::{generate_password, role_codes};
+
+    #[test]
+    pub async fn generated_password_must_pass_password_rules() {
+        let password = generate_password();
+
+        assert!(validation::password_length(&password).is_ok());
+        assert!(validation::password_uppercase(&password).is_ok());
+        assert!(validation::password_digit(&password).is_ok());
+        assert!(validation::password_special(&password).is_ok());
+        assert_ne!(password, generate_password());
+    }
 
-            InternalServerError {
-                message: e.to_string(),
-            }
-            .into()
-        }
-        Ok(user) => Ok {
-            message: format!("Password for user {} has been updated", user.username),
-        }
-        .into(),
+    #[test]
+    pub async fn role_codes_must_be_normalised() {
+        assert_eq!(role_codes(" admin, teacher;;"), vec!["ADMIN", "TEACHER"]);
+        assert!(role_codes("").is_empty());
     }
-}
 
-pub async fn delete<I: Into<Id>>(db: &DatabaseConnection, id: I) -> HttpResponse {
-    let user = dao::user::find(db, id).await;
+    #[test]
+    pub async fn admin_must_not_grant_superuser_roles() {
+        let db = dao::test::database().await;
+        let user = dao::user::test::user(&db).await;
+        let superuser = role(&db, &user, "SUPERUSER").await;
+        let admin = auth(Uuid::new_v4(), Uuid::new_v4(), &["ADMIN"]);
+        let request = UserUpdateGeneralInformationRequest {
+            name: user.name.clone(),
+            email: user.email.clone(),
+            username: user.username.clone(),
+            profile_photo_id: None,
+            locale: None,
+            permissions: vec![],
+            roles: vec![superuser.id],
+        };
+        let updated = tenant::scope(
+            user.tenant_id,
+            super::update_general_information(
+                &db,
+                &Authenticated::new(),
+                &admin,
+                &Actor::system(),
+                user.id,
+                request,
+            ),
+        )
+        .await;
+
+        assert!(matches!(updated, Err(AppError::Forbidden(_))));
+    }
 
-    if user.is_none() {
-        return HttpResponse::NotFound().finish();
+    #[test]
+    pub async fn update_general_information_must_forget_the_cached_auth() {
+        let db = dao::test::database().await;
+        let user = dao::user::test::user(&db).await;
+        let token = Uuid::new_v4();
+        let cache = Authenticated::new();
+        let request = UserUpdateGeneralInformationRequest {
+            name: "Jane Doe".to_string(),
+            email: user.email.clone(),
+            username: user.username.clone(),
+            profile_photo_id: None,
+            locale: None,
+            permissions: vec![],
+            roles: vec![],
+        };
+
+        cache.set(token, u64::MAX, auth(token, user.id, &["ADMIN"]));
+
+        tenant::scope(
+            user.tenant_id,
+            super::update_general_information(
+                &db,
+                &cache,
+                &auth(Uuid::new_v4(), Uuid::new_v4(), &["SUPERUSER"]),
+                &Actor::system(),
+                user.id,
+                request,
+            ),
+        )
+        .await
+        .unwrap();
+
+        assert!(cache.get(&token).is_none());
     }
 
-    let (user, _, _) = user.unwrap();
+    #[test]
+    pub async fn admin_must_not_set_the_password_of_a_superuser() {
+        let db = dao::test::database().await;
+        let user = dao::user::test::user(&db).await;
+        let superuser = role(&db, &user, "SUPERUSER").await;
+        let admin = auth(Uuid::new_v4(), Uuid::new_v4(), &["ADMIN"]);
+        let request = UserSetPasswordRequest {
+            password: "Secret!123".to_string(),
+            password_confirmation: "Secret!123".to_string(),
+        };
+
+        assign(&db, &user, &superuser, Period::default()).await;
+
+        let updated = tenant::scope(
+            user.tenant_id,
+            super::set_password(
+                &db,
+                &Authenticated::new(),
+                &admin,
+                &Actor::system(),
+                user.id,
+                request,
+            ),
+        )
+        .await;
+
+        assert!(matches!(updated, Err(AppError::Forbidden(_))));
+    }
 
-    match dao::user::delete(db, user).await {
-        Err(e) => {
-            log::error!(delete, "{}", e);
+    #[test]
+    pub async fn superuser_must_manage_superusers() {
+        let db = dao::test::database().await;
+        let user = dao::user::test::user(&db).await;
+        let superuser = role(&db, &user, "SUPERUSER").await;
 
-            InternalServerError {
-                message: e.to_string(),
+        assign(&db, &user, &superuser, Period::default()).await;
+
+        let granted = |roles: &[&str]| {
+            let auth = auth(Uuid::new_v4(), Uuid::new_v4(), roles);
+            let db = db.clone();
+            let (user, superuser) = (user.clone(), superuser.clone());
+
+            async move {
+                super::superuser_only(&db, &auth, user.tenant_id, &[user.id], &[superuser.id])
+                    .await
+                    .is_ok()
             }
-            .into()
-        }
-        Ok(user) => Ok {
-            message: format!("User {} has been deleted", user.username),
-        }
-        .into(),
+        };
+
+        assert!(granted(&["SUPERUSER"]).await);
+        assert!(!granted(&["ADMIN"]).await);
     }
 }
@@ -0,0 +1,261 @@
+use std::borrow::Cow;
+
+use rust_xlsxwriter::Workbook;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// What a cell starts with for spreadsheets to take it for a formula.
+const FORMULA: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// `cell` quoted with a leading `'` when a spreadsheet would otherwise run
+/// it as a formula, as names and emails are up to the users.
+pub fn escape(cell: &str) -> Cow<'_, str> {
+    match cell.starts_with(FORMULA) {
+        true => Cow::Owned(format!("'{cell}")),
+        false => Cow::Borrowed(cell),
+    }
+}
+
+/// `cell` as it was before being [`escape`]d.
+pub fn unescape(cell: &str) -> &str {
+    match cell.strip_prefix('\'') {
+        Some(quoted) if quoted.starts_with(FORMULA) => quoted,
+        _ => cell,
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Xlsx,
+    /// One json object per line.
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Xlsx => "xlsx",
+            Self::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// A row of an export, serialized as is for ndjson and as `cells` under
+/// the `COLUMNS` header otherwise.
+pub trait Record: Serialize {
+    const COLUMNS: &'static [&'static str];
+
+    fn cells(&self) -> Vec<String>;
+}
+
+/// Encodes records chunk by chunk. Csv and ndjson come out as they are
+/// written, an xlsx file only once finished since it is a zip archive;
+/// its rows are kept in temporary files meanwhile, not in memory.
+pub struct Writer {
+    format: ExportFormat,
+    workbook: Option<Workbook>,
+    row: u32,
+}
+
+impl Writer {
+    /// A writer for records of `R` and the bytes of its header.
+    pub fn new<R: Record>(format: ExportFormat) -> Result<(Self, Vec<u8>), String> {
+        let mut writer = Self {
+            format,
+            workbook: None,
+            row: 0,
+        };
+        let columns = R::COLUMNS
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        let header = match format {
+            ExportFormat::Csv => writer.csv(&[columns])?,
+            ExportFormat::Xlsx => {
+                let mut workbook = Workbook::new();
+
+                workbook.add_worksheet_with_constant_memory();
+                writer.workbook = Some(workbook);
+                writer.xlsx(&[columns])?;
+
+                vec![]
+            }
+            ExportFormat::Ndjson => vec![],
+        };
+
+        Ok((writer, header))
+    }
+
+    pub fn write<R: Record>(&mut self, records: &[R]) -> Result<Vec<u8>, String> {
+        match self.format {
+            ExportFormat::Csv => self.csv(&records.iter().map(R::cells).collect::<Vec<_>>()),
+            ExportFormat::Xlsx => {
+                self.xlsx(&records.iter().map(R::cells).collect::<Vec<_>>())?;
+
+                Ok(vec![])
+            }
+            ExportFormat::Ndjson => {
+                let mut bytes = vec![];
+
+                for record in records {
+                    serde_json::to_writer(&mut bytes, record).map_err(|e| e.to_string())?;
+                    bytes.push(b'\n');
+                }
+
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// The bytes left once every record is written, which for xlsx takes
+    /// zipping the whole workbook and is better not run on the executor.
+    pub fn finish(self) -> Result<Vec<u8>, String> {
+        match self.workbook {
+            Some(mut workbook) => workbook.save_to_buffer().map_err(|e| e.to_string()),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn csv(&self, rows: &[Vec<String>]) -> Result<Vec<u8>, String> {
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        for row in rows {
+            writer
+                .write_record(row.iter().map(|cell| escape(cell).into_owned()))
+                .map_err(|e| e.to_string())?;
+        }
+
+        writer.into_inner().map_err(|e| e.to_string())
+    }
+
+    fn xlsx(&mut self, rows: &[Vec<String>]) -> Result<(), String> {
+        let worksheet = self
+            .workbook
+            .as_mut()
+            .and_then(|workbook| workbook.worksheet_from_index(0).ok())
+            .ok_or_else(|| "workbook has no worksheet".to_string())?;
+
+        for row in rows {
+            for (column, cell) in row.iter().enumerate() {
+                worksheet
+                    .write_string(self.row, column as u16, escape(cell))
+                    .map_err(|e| e.to_string())?;
+            }
+
+            self.row += 1;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use serde::Serialize;
+
+    use crate::sheet::{self, Format};
+
+    use super::{escape, unescape, ExportFormat, Record, Writer};
+
+    #[derive(Serialize)]
+    struct Role {
+        code: String,
+        permissions: Vec<String>,
+    }
+
+    impl Record for Role {
+        const COLUMNS: &'static [&'static str] = &["code", "permissions"];
+
+        fn cells(&self) -> Vec<String> {
+            vec![self.code.clone(), self.permissions.join(", ")]
+        }
+    }
+
+    fn export(format: ExportFormat) -> Vec<u8> {
+        let roles = [
+            Role {
+                code: "ADMIN".to_string(),
+                permissions: vec!["READ_USER".to_string(), "CREATE_USER".to_string()],
+            },
+            Role {
+                code: "STUDENT".to_string(),
+                permissions: vec![],
+            },
+        ];
+        let (mut writer, mut bytes) = Writer::new::<Role>(format).unwrap();
+
+        for chunk in roles.chunks(1) {
+            bytes.extend(writer.write(chunk).unwrap());
+        }
+
+        bytes.extend(writer.finish().unwrap());
+        bytes
+    }
+
+    #[test]
+    pub async fn csv_must_be_readable_by_import() {
+        let (columns, rows) = sheet::read(Format::Csv, &export(ExportFormat::Csv)).unwrap();
+
+        assert_eq!(columns, vec!["code", "permissions"]);
+        assert_eq!(rows[0].get("permissions"), "READ_USER, CREATE_USER");
+        assert_eq!(rows[1].get("code"), "STUDENT");
+    }
+
+    #[test]
+    pub async fn xlsx_must_be_readable_by_import() {
+        let (columns, rows) = sheet::read(Format::Xlsx, &export(ExportFormat::Xlsx)).unwrap();
+
+        assert_eq!(columns, vec!["code", "permissions"]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get("permissions"), "READ_USER, CREATE_USER");
+    }
+
+    #[test]
+    pub async fn ndjson_must_hold_an_object_per_line() {
+        let bytes = export(ExportFormat::Ndjson);
+        let lines = String::from_utf8(bytes).unwrap();
+
+        assert_eq!(
+            lines,
+            "{\"code\":\"ADMIN\",\"permissions\":[\"READ_USER\",\"CREATE_USER\"]}\n\
+             {\"code\":\"STUDENT\",\"permissions\":[]}\n"
+        );
+    }
+
+    #[test]
+    pub async fn formula_must_be_escaped_and_read_back_as_is() {
+        for cell in ["=HYPERLINK(\"http://evil\")", "+1", "-1", "@SUM(A1)"] {
+            assert_eq!(escape(cell), format!("'{cell}"));
+            assert_eq!(unescape(&escape(cell)), cell);
+        }
+
+        assert_eq!(escape("john@local.id"), "john@local.id");
+        assert_eq!(unescape("'quoted"), "'quoted");
+
+        let (mut writer, mut bytes) = Writer::new::<Role>(ExportFormat::Csv).unwrap();
+
+        bytes.extend(
+            writer
+                .write(&[Role {
+                    code: "=1+1".to_string(),
+                    permissions: vec![],
+                }])
+                .unwrap(),
+        );
+
+        assert!(String::from_utf8(bytes).unwrap().contains("'=1+1"));
+    }
+}
@@ -0,0 +1,128 @@
+mod export;
+
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use calamine::{open_workbook_from_rs, Reader, Xlsx, XlsxError};
+
+pub use export::*;
+
+/// A tabular file whose first row names the columns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Csv,
+    Xlsx,
+}
+
+impl Format {
+    /// The format of a file going by the extension of its `name`.
+    pub fn detect(name: &str) -> Option<Self> {
+        let extension = name.rsplit_once('.')?.1.to_lowercase();
+
+        match extension.as_str() {
+            "csv" => Some(Self::Csv),
+            "xlsx" => Some(Self::Xlsx),
+            _ => None,
+        }
+    }
+}
+
+pub struct Row {
+    /// Line of the row in the file, the header being line 1.
+    pub line: usize,
+    cells: HashMap<String, String>,
+}
+
+impl Row {
+    /// The trimmed cell of `column`, empty when the file lacks it.
+    pub fn get(&self, column: &str) -> &str {
+        self.cells.get(column).map(|cell| cell.trim()).unwrap_or("")
+    }
+}
+
+/// Read the rows of `bytes`, keyed by the lowercased column names and
+/// leaving out the blank ones, with the cells an export escaped as they were.
+pub fn read(format: Format, bytes: &[u8]) -> Result<(Vec<String>, Vec<Row>), String> {
+    let mut lines = match format {
+        Format::Csv => csv(bytes)?,
+        Format::Xlsx => xlsx(bytes)?,
+    }
+    .into_iter();
+
+    let columns = lines
+        .next()
+        .unwrap_or_default()
+        .iter()
+        .map(|column| column.trim().to_lowercase())
+        .collect::<Vec<_>>();
+
+    let rows = lines
+        .enumerate()
+        .filter(|(_, cells)| cells.iter().any(|cell| !cell.trim().is_empty()))
+        .map(|(i, cells)| Row {
+            line: i + 2,
+            cells: columns
+                .iter()
+                .cloned()
+                .zip(cells.iter().map(|cell| unescape(cell).to_string()))
+                .collect(),
+        })
+        .collect();
+
+    Ok((columns, rows))
+}
+
+fn csv(bytes: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(bytes)
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(ToString::to_string).collect())
+                .map_err(|e| e.to_string())
+        })
+        .collect()
+}
+
+fn xlsx(bytes: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    let mut workbook: Xlsx<_> =
+        open_workbook_from_rs(Cursor::new(bytes)).map_err(|e: XlsxError| e.to_string())?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| "workbook has no worksheet".to_string())?
+        .map_err(|e| e.to_string())?;
+
+    Ok(range
+        .rows()
+        .map(|row| row.iter().map(ToString::to_string).collect())
+        .collect())
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::{read, Format};
+
+    #[test]
+    pub async fn detect_must_go_by_extension() {
+        assert_eq!(Format::detect("users.CSV"), Some(Format::Csv));
+        assert_eq!(Format::detect("term 1.xlsx"), Some(Format::Xlsx));
+        assert_eq!(Format::detect("users.xls"), None);
+        assert_eq!(Format::detect("users"), None);
+    }
+
+    #[test]
+    pub async fn read_must_key_rows_by_column() {
+        let csv =
+            b" Name ,Email,Roles\njohn,john@local.id,\"admin, teacher\"\n,,\njane,jane@local.id\n";
+        let (columns, rows) = read(Format::Csv, csv).unwrap();
+
+        assert_eq!(columns, vec!["name", "email", "roles"]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get("roles"), "admin, teacher");
+        assert_eq!(rows[1].line, 4);
+        assert_eq!(rows[1].get("email"), "jane@local.id");
+        assert_eq!(rows[1].get("roles"), "");
+    }
+}
@@ -0,0 +1,90 @@
+use std::io::Cursor;
+
+use image::io::{Limits, Reader};
+use image::{ImageFormat, ImageOutputFormat};
+
+/// Longest side of a generated thumbnail.
+pub const THUMBNAIL_SIZE: u32 = 256;
+/// Longest side of an accepted image, guards against decompression bombs.
+pub const MAX_DIMENSION: u32 = 8192;
+
+pub struct Image {
+    pub mime: &'static str,
+    pub extension: &'static str,
+    pub width: u32,
+    pub height: u32,
+    /// Png encoded thumbnail fitting in [`THUMBNAIL_SIZE`].
+    pub thumbnail: Vec<u8>,
+}
+
+/// Decode `bytes`, identified by their content rather than any name or
+/// header sent by the client, and render their thumbnail.
+pub fn process(bytes: &[u8]) -> Result<Image, String> {
+    let mut reader = Reader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?;
+    let (mime, extension) = match reader.format() {
+        Some(ImageFormat::Png) => ("image/png", "png"),
+        Some(ImageFormat::Jpeg) => ("image/jpeg", "jpg"),
+        Some(ImageFormat::Gif) => ("image/gif", "gif"),
+        Some(ImageFormat::WebP) => ("image/webp", "webp"),
+        _ => return Err("unsupported image format".to_string()),
+    };
+    let mut limits = Limits::default();
+
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+
+    reader.limits(limits);
+
+    let image = reader.decode().map_err(|e| e.to_string())?;
+    let mut thumbnail = Cursor::new(vec![]);
+
+    image
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .write_to(&mut thumbnail, ImageOutputFormat::Png)
+        .map_err(|e| e.to_string())?;
+
+    Ok(Image {
+        mime,
+        extension,
+        width: image.width(),
+        height: image.height(),
+        thumbnail: thumbnail.into_inner(),
+    })
+}
+
+#[cfg(test)]
+pub mod test {
+    use std::io::Cursor;
+
+    use image::{DynamicImage, ImageOutputFormat};
+
+    use super::process;
+
+    fn encode(width: u32, height: u32, format: ImageOutputFormat) -> Vec<u8> {
+        let mut bytes = Cursor::new(vec![]);
+
+        DynamicImage::new_rgb8(width, height)
+            .write_to(&mut bytes, format)
+            .unwrap();
+
+        bytes.into_inner()
+    }
+
+    #[test]
+    pub async fn process_must_detect_format_and_shrink_thumbnail() {
+        let image = process(&encode(1024, 512, ImageOutputFormat::Jpeg(80))).unwrap();
+        let thumbnail = image::load_from_memory(&image.thumbnail).unwrap();
+
+        assert_eq!(image.mime, "image/jpeg");
+        assert_eq!((image.width, image.height), (1024, 512));
+        assert_eq!((thumbnail.width(), thumbnail.height()), (256, 128));
+    }
+
+    #[test]
+    pub async fn process_must_reject_non_images() {
+        assert!(process(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>").is_err());
+        assert!(process(&encode(1, 1, ImageOutputFormat::Png)[..16]).is_err());
+    }
+}
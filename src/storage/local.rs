@@ -0,0 +1,77 @@
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+use super::{Error, Storage};
+
+/// Files kept under a directory of the local filesystem.
+pub struct Local {
+    root: PathBuf,
+}
+
+impl Local {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn resolve(&self, path: &str) -> Result<PathBuf, Error> {
+        let relative = Path::new(path);
+        let safe = relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+
+        match safe {
+            true => Ok(self.root.join(relative)),
+            false => Err(format!("invalid storage path {}", path).into()),
+        }
+    }
+}
+
+impl Storage for Local {
+    async fn put(&self, path: &str, bytes: Vec<u8>, _: &str) -> Result<(), Error> {
+        let path = self.resolve(path)?;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        Ok(tokio::fs::write(path, bytes).await?)
+    }
+
+    async fn get(&self, path: &str) -> Result<Vec<u8>, Error> {
+        Ok(tokio::fs::read(self.resolve(path)?).await?)
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), Error> {
+        match tokio::fs::remove_file(self.resolve(path)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::super::Storage;
+    use super::Local;
+
+    #[test]
+    pub async fn local_must_roundtrip_and_reject_traversal() {
+        let root = std::env::temp_dir().join(format!("storage-{}", uuid::Uuid::new_v4()));
+        let disk = Local::new(&root);
+
+        disk.put("a/b.txt", b"hello".to_vec(), "text/plain")
+            .await
+            .unwrap();
+
+        assert_eq!(disk.get("a/b.txt").await.unwrap(), b"hello");
+        assert!(disk.get("../b.txt").await.is_err());
+        assert!(disk.put("/etc/b.txt", vec![], "text/plain").await.is_err());
+
+        disk.delete("a/b.txt").await.unwrap();
+        disk.delete("a/b.txt").await.unwrap();
+
+        assert!(disk.get("a/b.txt").await.is_err());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
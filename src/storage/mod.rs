@@ -0,0 +1,127 @@
+pub mod image;
+mod local;
+mod s3;
+
+use std::fmt::Display;
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+pub use local::Local;
+pub use s3::S3;
+
+use crate::common::{env, sign, time};
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Where uploaded files live. Paths are relative, `/` separated keys
+/// chosen by the service, never by the client.
+pub trait Storage: Send + Sync {
+    fn put(
+        &self,
+        path: &str,
+        bytes: Vec<u8>,
+        mime: &str,
+    ) -> impl Future<Output = Result<(), Error>> + Send;
+
+    fn get(&self, path: &str) -> impl Future<Output = Result<Vec<u8>, Error>> + Send;
+
+    fn delete(&self, path: &str) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+/// A configured [`Storage`], recorded by name on every file so it can
+/// still be read after `STORAGE_DISK` changes.
+pub enum Disk {
+    Local(Local),
+    S3(S3),
+}
+
+impl Disk {
+    /// The disk selected by `STORAGE_DISK`, `local` by default.
+    pub fn configured() -> Result<Self, Error> {
+        Self::named(&env::var("STORAGE_DISK", "local".to_string()))
+    }
+
+    pub fn named(name: &str) -> Result<Self, Error> {
+        match name {
+            "local" => Ok(Self::Local(Local::new(env::var(
+                "STORAGE_ROOT",
+                "storage".to_string(),
+            )))),
+            "s3" => Ok(Self::S3(S3::from_env()?)),
+            name => Err(format!("unknown storage disk {}", name).into()),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Local(_) => "local",
+            Self::S3(_) => "s3",
+        }
+    }
+}
+
+impl Storage for Disk {
+    async fn put(&self, path: &str, bytes: Vec<u8>, mime: &str) -> Result<(), Error> {
+        match self {
+            Self::Local(disk) => disk.put(path, bytes, mime).await,
+            Self::S3(disk) => disk.put(path, bytes, mime).await,
+        }
+    }
+
+    async fn get(&self, path: &str) -> Result<Vec<u8>, Error> {
+        match self {
+            Self::Local(disk) => disk.get(path).await,
+            Self::S3(disk) => disk.get(path).await,
+        }
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), Error> {
+        match self {
+            Self::Local(disk) => disk.delete(path).await,
+            Self::S3(disk) => disk.delete(path).await,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Variant {
+    #[default]
+    Original,
+    Thumbnail,
+}
+
+impl Variant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Original => "original",
+            Self::Thumbnail => "thumbnail",
+        }
+    }
+}
+
+fn signature<I: Display>(id: I, variant: Variant, expires: u64) -> String {
+    format!("file:{}:{}:{}", id, variant.as_str(), expires)
+}
+
+/// A download url of `id` that needs no token but expires after one to
+/// two `FILE_URL_TTL` seconds. The expiry is rounded so the url stays the
+/// same, and cacheable, for a while.
+pub fn url<I: Display>(id: I, variant: Variant) -> String {
+    let ttl = env::var("FILE_URL_TTL", 3600u64).max(1);
+    let expires = (time::unix() / 1000 / ttl + 2) * ttl;
+
+    format!(
+        "/api/v1/file/{}/download?variant={}&expires={}&signature={}",
+        id,
+        variant.as_str(),
+        expires,
+        sign::sign(signature(&id, variant, expires)),
+    )
+}
+
+pub fn verify<I: Display>(id: I, variant: Variant, expires: u64, signature: &str) -> bool {
+    expires >= time::unix() / 1000 && sign::verify(self::signature(id, variant, expires), signature)
+}
@@ -0,0 +1,263 @@
+use std::future::Future;
+
+use sea_orm::prelude::*;
+use sea_orm::{QueryFilter, Select};
+
+use crate::common::env;
+use crate::models::{audit_logs, files, invitations, role_scopes, roles, users, Id};
+
+/// Header naming the tenant a request is for, ahead of its subdomain.
+pub const HEADER: &str = "X-Tenant";
+
+tokio::task_local! {
+    static TENANT: Option<Id>;
+    static EVERY: ();
+}
+
+/// Id of the tenant of the request being handled, `None` outside of a
+/// request, such as in jobs and scheduled tasks.
+pub fn current() -> Option<Id> {
+    TENANT.try_with(Clone::clone).ok().flatten()
+}
+
+/// Whether the rows of every tenant are seen, as within [`every`].
+pub fn sees_every() -> bool {
+    EVERY.try_with(|_| ()).is_ok()
+}
+
+/// The current tenant, for rows that cannot exist outside of one.
+pub fn required() -> Result<Id, DbErr> {
+    current().ok_or_else(|| DbErr::Custom("no tenant to own the row".to_string()))
+}
+
+pub async fn scope<F: Future>(tenant: Id, f: F) -> F::Output {
+    TENANT.scope(Some(tenant), f).await
+}
+
+pub fn sync_scope<F: FnOnce() -> R, R>(tenant: Id, f: F) -> R {
+    TENANT.sync_scope(Some(tenant), f)
+}
+
+/// Run `f` over the rows of every tenant, as jobs and scheduled tasks do.
+pub async fn every<F: Future>(f: F) -> F::Output {
+    EVERY.scope((), f).await
+}
+
+/// Code of the tenant requests fall back to when they name none,
+/// `TENANT_DEFAULT`.
+pub fn fallback() -> String {
+    env::var("TENANT_DEFAULT", "default".to_string())
+}
+
+/// Code of the tenant whose superusers manage every tenant,
+/// `PLATFORM_TENANT`.
+pub fn platform() -> String {
+    env::var("PLATFORM_TENANT", "default".to_string())
+}
+
+/// Code of the tenant a request names, through its [`HEADER`] or else as
+/// the subdomain of `domain` its host is under.
+pub fn code(header: Option<&str>, host: &str, domain: &str) -> Option<String> {
+    if let Some(code) = header.map(str::trim).filter(|code| !code.is_empty()) {
+        return Some(code.to_lowercase());
+    }
+
+    if domain.is_empty() {
+        return None;
+    }
+
+    let host = host.split(':').next().unwrap_or_default().to_lowercase();
+    let domain = domain.trim_start_matches('.').to_lowercase();
+
+    host.strip_suffix(&domain)
+        .and_then(|host| host.strip_suffix('.'))
+        .filter(|subdomain| !subdomain.is_empty() && !subdomain.contains('.'))
+        .map(ToString::to_string)
+}
+
+/// Entities whose rows belong to a single tenant.
+pub trait Owned: EntityTrait {
+    fn tenant() -> Self::Column;
+}
+
+impl Owned for users::Entity {
+    fn tenant() -> Self::Column {
+        users::Column::TenantId
+    }
+}
+
+impl Owned for roles::Entity {
+    fn tenant() -> Self::Column {
+        roles::Column::TenantId
+    }
+}
+
+impl Owned for invitations::Entity {
+    fn tenant() -> Self::Column {
+        invitations::Column::TenantId
+    }
+}
+
+impl Owned for audit_logs::Entity {
+    fn tenant() -> Self::Column {
+        audit_logs::Column::TenantId
+    }
+}
+
+impl Owned for files::Entity {
+    fn tenant() -> Self::Column {
+        files::Column::TenantId
+    }
+}
+
+impl Owned for role_scopes::Entity {
+    fn tenant() -> Self::Column {
+        role_scopes::Column::TenantId
+    }
+}
+
+/// Whether the rows of `table` belong to a single tenant.
+pub fn owns(table: &str) -> bool {
+    [
+        users::Entity.table_name(),
+        roles::Entity.table_name(),
+        invitations::Entity.table_name(),
+        audit_logs::Entity.table_name(),
+        files::Entity.table_name(),
+        role_scopes::Entity.table_name(),
+    ]
+    .contains(&table)
+}
+
+pub trait Tenanted {
+    /// Narrow the query down to the rows of the current tenant, leaving it
+    /// as is within [`every`] and finding nothing without either, as does a
+    /// request that lost its tenant.
+    fn tenanted(self) -> Self;
+}
+
+impl<E: Owned> Tenanted for Select<E> {
+    fn tenanted(self) -> Self {
+        match current() {
+            Some(tenant) => self.filter(E::tenant().eq(tenant)),
+            None if sees_every() => self,
+            // no row is without a tenant
+            None => self.filter(E::tenant().is_null()),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use sea_orm::{DbBackend, EntityTrait, QueryTrait};
+    use uuid::Uuid;
+
+    use crate::common::time;
+    use crate::dao::{self, grant::test::role};
+    use crate::middlewares::audit::Actor;
+    use crate::models::{files, users};
+    use crate::requests::role_scope::RoleScopeStoreRequest;
+
+    use super::{code, current, every, scope, Tenanted};
+
+    #[test]
+    pub async fn code_must_prefer_header_over_subdomain() {
+        assert_eq!(
+            code(Some(" North "), "south.lms.id", "lms.id"),
+            Some("north".to_string())
+        );
+        assert_eq!(
+            code(None, "South.lms.id:8000", "lms.id"),
+            Some("south".to_string())
+        );
+        assert_eq!(code(Some(""), "lms.id", "lms.id"), None);
+        assert_eq!(code(None, "a.b.lms.id", "lms.id"), None);
+        assert_eq!(code(None, "south.lms.id", ""), None);
+        assert_eq!(code(None, "southlms.id", "lms.id"), None);
+    }
+
+    #[test]
+    pub async fn tenanted_must_filter_within_a_tenant_only() {
+        let tenant = Uuid::new_v4();
+        let query = || users::Entity::find().tenanted().build(DbBackend::Postgres);
+
+        assert!(!every(async { query() }).await.to_string().contains("WHERE"));
+        assert_eq!(current(), None);
+        assert!(scope(tenant, async { query() })
+            .await
+            .to_string()
+            .contains(&format!(r#""users"."tenant_id" = '{}'"#, tenant)));
+    }
+
+    #[test]
+    pub async fn tenanted_must_find_nothing_without_a_tenant() {
+        let query = users::Entity::find().tenanted().build(DbBackend::Postgres);
+
+        assert!(query
+            .to_string()
+            .ends_with(r#"WHERE "users"."tenant_id" IS NULL"#));
+    }
+
+    #[test]
+    pub async fn files_and_role_scopes_must_not_be_found_from_another_tenant() {
+        let db = dao::test::database().await;
+        let user = dao::user::test::user(&db).await;
+        let teacher = role(&db, &user, "TEACHER").await;
+        let (owner, other) = (user.tenant_id, Uuid::new_v4());
+        let file = scope(
+            owner,
+            dao::file::store(
+                &db,
+                files::Model {
+                    id: Uuid::new_v4(),
+                    tenant_id: owner,
+                    disk: "local".to_string(),
+                    path: "files/photo.png".to_string(),
+                    thumbnail_path: None,
+                    name: "photo.png".to_string(),
+                    mime: "image/png".to_string(),
+                    size: 0,
+                    checksum: String::new(),
+                    width: None,
+                    height: None,
+                    uploaded_by: Some(user.id),
+                    created_at: time::now(),
+                    updated_at: time::now(),
+                },
+            ),
+        )
+        .await
+        .unwrap();
+        let assigned = scope(
+            owner,
+            dao::role_scope::store(
+                &db,
+                &Actor::system(),
+                user.clone(),
+                teacher.clone(),
+                RoleScopeStoreRequest {
+                    role_id: teacher.id,
+                    scope_type: "course".to_string(),
+                    scope_id: Uuid::new_v4(),
+                },
+            ),
+        )
+        .await
+        .unwrap();
+
+        assert!(scope(other, dao::file::find(&db, file.id)).await.is_none());
+        assert!(
+            scope(other, dao::role_scope::find(&db, user.id, assigned.id))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(scope(owner, dao::file::find(&db, file.id)).await.is_some());
+        assert!(
+            scope(owner, dao::role_scope::find(&db, user.id, assigned.id))
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+}
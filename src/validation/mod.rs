@@ -0,0 +1,380 @@
+pub mod rules;
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset};
+use sea_orm::sea_query::{Expr, Func, Query, SelectStatement, SimpleExpr};
+use sea_orm::{ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, Iden, Value};
+use validator::Validate;
+
+use crate::common::time;
+use crate::dao::grant::Period;
+use crate::i18n;
+use crate::responses::AppError;
+use crate::tenant;
+
+pub use rules::*;
+
+/// Rules that need the database, e.g. uniqueness or foreign keys.
+///
+/// `C` is whatever the request needs beyond itself, such as the id of
+/// the record being updated.
+pub trait Rules<C = ()> {
+    fn rules(&self, _context: &C) -> Vec<Rule> {
+        vec![]
+    }
+}
+
+enum Kind {
+    Unique,
+    Exists,
+}
+
+pub struct Rule {
+    field: &'static str,
+    kind: Kind,
+    table: String,
+    column: String,
+    values: Vec<Value>,
+    except: Option<(String, Value)>,
+    within: Option<(String, Value)>,
+    message: Option<&'static str>,
+}
+
+impl Rule {
+    /// No row of the column's table holds `value`.
+    pub fn unique<C: ColumnTrait, V: Into<Value>>(
+        field: &'static str,
+        column: C,
+        value: V,
+    ) -> Self {
+        Self {
+            field,
+            kind: Kind::Unique,
+            table: column.entity_name().to_string(),
+            column: column.as_str().to_string(),
+            values: vec![value.into()],
+            except: None,
+            within: None,
+            message: None,
+        }
+    }
+
+    /// Every one of `values` is held by a row of the column's table.
+    pub fn exists<C: ColumnTrait, V: Into<Value>>(
+        field: &'static str,
+        column: C,
+        values: Vec<V>,
+    ) -> Self {
+        let mut unique: Vec<Value> = vec![];
+
+        for value in values.into_iter().map(Into::into) {
+            if !unique.contains(&value) {
+                unique.push(value);
+            }
+        }
+
+        Self {
+            field,
+            kind: Kind::Exists,
+            table: column.entity_name().to_string(),
+            column: column.as_str().to_string(),
+            values: unique,
+            except: None,
+            within: None,
+            message: None,
+        }
+    }
+
+    /// Ignore the row whose `column` is `value`, usually the one being updated.
+    pub fn except<C: ColumnTrait, V: Into<Value>>(mut self, column: C, value: V) -> Self {
+        self.except = Some((column.as_str().to_string(), value.into()));
+        self
+    }
+
+    /// Only count the rows whose `column` is `value`, such as the files
+    /// uploaded by the user making the request.
+    pub fn within<C: ColumnTrait, V: Into<Value>>(mut self, column: C, value: V) -> Self {
+        self.within = Some((column.as_str().to_string(), value.into()));
+        self
+    }
+
+    /// Report a failure with the message of `key` instead of the default one.
+    pub fn message(mut self, key: &'static str) -> Self {
+        self.message = Some(key);
+        self
+    }
+
+    fn key(&self) -> &'static str {
+        if let Some(key) = self.message {
+            return key;
+        }
+
+        match self.kind {
+            Kind::Unique => "validation.unique",
+            Kind::Exists => "validation.exists",
+        }
+    }
+
+    fn query(&self) -> SelectStatement {
+        let column = Expr::col(Alias(self.column.clone()));
+        let mut query = Query::select();
+
+        query
+            .expr_as(
+                Func::count(SimpleExpr::from(column.clone())),
+                Alias("count".to_string()),
+            )
+            .from(Alias(self.table.clone()))
+            .and_where(column.is_in(self.values.clone()));
+
+        if let Some((except, value)) = &self.except {
+            query.and_where(Expr::col(Alias(except.clone())).ne(value.clone()));
+        }
+
+        if let Some((within, value)) = &self.within {
+            query.and_where(Expr::col(Alias(within.clone())).eq(value.clone()));
+        }
+
+        // rows of other tenants neither clash with nor satisfy the rule
+        if let Some(tenant) = tenant::current().filter(|_| tenant::owns(&self.table)) {
+            query.and_where(Expr::col(Alias("tenant_id".to_string())).eq(tenant));
+        }
+
+        query
+    }
+
+    async fn passes(&self, db: &DatabaseConnection) -> Result<bool, DbErr> {
+        if self.values.is_empty() {
+            return Ok(true);
+        }
+
+        let builder = db.get_database_backend();
+        let count = db
+            .query_one(builder.build(&self.query()))
+            .await?
+            .map(|row| row.try_get::<i64>("", "count"))
+            .transpose()?
+            .unwrap_or(0);
+
+        Ok(match self.kind {
+            Kind::Unique => count == 0,
+            Kind::Exists => count as usize == self.values.len(),
+        })
+    }
+}
+
+struct Alias(String);
+
+impl Iden for Alias {
+    fn unquoted(&self, s: &mut dyn std::fmt::Write) {
+        write!(s, "{}", self.0).unwrap();
+    }
+}
+
+pub async fn validate<T: Validate + Rules>(
+    db: &DatabaseConnection,
+    request: &T,
+) -> Result<(), AppError> {
+    validate_with(db, request, &()).await
+}
+
+/// Run the derived rules of `request`, then its database rules for the
+/// fields that are still valid, failing with every message at once.
+///
+/// Messages are looked up in the catalogue of the current locale, under
+/// the rule's `message` if it has one and `validation.{code}` otherwise.
+pub async fn validate_with<T: Validate + Rules<C>, C>(
+    db: &DatabaseConnection,
+    request: &T,
+    context: &C,
+) -> Result<(), AppError> {
+    let mut errors: HashMap<String, Vec<String>> = HashMap::new();
+
+    if let Err(e) = request.validate() {
+        for (field, failures) in e.field_errors() {
+            let messages = failures
+                .iter()
+                .map(|error| {
+                    let key = match &error.message {
+                        Some(message) => message.to_string(),
+                        None => format!("validation.{}", error.code),
+                    };
+                    let params = error
+                        .params
+                        .iter()
+                        .filter(|(name, _)| *name != "value" && *name != "other")
+                        .map(|(name, value)| match value {
+                            serde_json::Value::String(value) => (name.to_string(), value.clone()),
+                            value => (name.to_string(), value.to_string()),
+                        })
+                        .collect();
+
+                    message(field, &key, params)
+                })
+                .collect();
+
+            errors.insert(field.to_string(), messages);
+        }
+    }
+
+    for rule in request.rules(context) {
+        if errors.contains_key(rule.field) {
+            continue;
+        }
+
+        if !rule.passes(db).await? {
+            errors
+                .entry(rule.field.to_string())
+                .or_default()
+                .push(message(rule.field, rule.key(), vec![]));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(AppError::Validation(errors));
+    }
+
+    Ok(())
+}
+
+/// A failure of `field` alone, for checks made outside of the request rules.
+pub fn invalid(field: &str, key: &str, params: Vec<(String, String)>) -> AppError {
+    AppError::validation(HashMap::from([(field, vec![message(field, key, params)])]))
+}
+
+/// When a grant is in effect, which must end in the future and after it
+/// starts.
+pub fn period(
+    starts_at: Option<DateTime<FixedOffset>>,
+    expires_at: Option<DateTime<FixedOffset>>,
+) -> Result<Period, AppError> {
+    let period = Period {
+        starts_at: starts_at.map(|at| at.naive_utc()),
+        expires_at: expires_at.map(|at| at.naive_utc()),
+    };
+
+    if let Some(expires_at) = period.expires_at {
+        if expires_at <= time::now() {
+            return Err(invalid("expires_at", "validation.future", vec![]));
+        }
+
+        if period
+            .starts_at
+            .is_some_and(|starts_at| starts_at >= expires_at)
+        {
+            return Err(invalid("expires_at", "validation.after_start", vec![]));
+        }
+    }
+
+    Ok(period)
+}
+
+/// Translate `key` for `field`, which is available to the message as
+/// `{attribute}` and capitalized as `{Attribute}`.
+pub fn message(field: &str, key: &str, mut params: Vec<(String, String)>) -> String {
+    let locale = i18n::current();
+    let attribute = i18n::lookup(locale, &format!("attributes.{}", field))
+        .map(ToString::to_string)
+        .unwrap_or_else(|| field.replace('_', " "));
+    let mut chars = attribute.chars();
+    let capitalized = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    };
+
+    params.push(("attribute".to_string(), attribute));
+    params.push(("Attribute".to_string(), capitalized));
+
+    i18n::translate(locale, key, &params)
+}
+
+#[cfg(test)]
+pub mod test {
+    use sea_orm::sea_query::PostgresQueryBuilder;
+    use uuid::Uuid;
+
+    use crate::models::{roles, users};
+
+    use super::Rule;
+
+    #[test]
+    pub async fn unique_must_count_other_rows() {
+        let id = Uuid::nil();
+        let rule = Rule::unique("email", users::Column::Email, "john@local.id")
+            .except(users::Column::Id, id);
+
+        assert_eq!(
+            rule.query().to_string(PostgresQueryBuilder),
+            format!(
+                r#"SELECT COUNT("email") AS "count" FROM "users" WHERE "email" IN ('john@local.id') AND "id" <> '{}'"#,
+                id
+            )
+        );
+    }
+
+    #[test]
+    pub async fn exists_must_only_count_rows_within() {
+        use crate::models::files;
+
+        let (id, uploader) = (Uuid::nil(), Uuid::max());
+        let rule = Rule::exists("profile_photo_id", files::Column::Id, vec![id])
+            .within(files::Column::UploadedBy, uploader);
+
+        assert_eq!(
+            rule.query().to_string(PostgresQueryBuilder),
+            format!(
+                r#"SELECT COUNT("id") AS "count" FROM "files" WHERE "id" IN ('{}') AND "uploaded_by" = '{}'"#,
+                id, uploader
+            )
+        );
+    }
+
+    #[test]
+    pub async fn rules_must_stay_within_the_current_tenant() {
+        use crate::models::permissions;
+        use crate::tenant;
+
+        let id = Uuid::new_v4();
+        let rule = Rule::unique("username", users::Column::Username, "john");
+        let query = tenant::scope(id, async { rule.query().to_string(PostgresQueryBuilder) }).await;
+
+        assert!(query.ends_with(&format!(r#"AND "tenant_id" = '{}'"#, id)));
+
+        let rule = Rule::unique("code", permissions::Column::Code, "READ_USER");
+        let query = tenant::scope(id, async { rule.query().to_string(PostgresQueryBuilder) }).await;
+
+        assert!(!query.contains("tenant_id"));
+    }
+
+    #[test]
+    pub async fn message_must_use_translated_attribute() {
+        use crate::i18n::{self, Locale};
+
+        use super::message;
+
+        assert_eq!(
+            message("email_or_username", "validation.required", vec![]),
+            "Email or username field is required"
+        );
+        assert_eq!(
+            i18n::scope(Locale::Id, async {
+                message(
+                    "new_password",
+                    "validation.password_length",
+                    vec![("min".to_string(), "6".to_string())],
+                )
+            })
+            .await,
+            "Kata sandi baru minimal 6 karakter"
+        );
+    }
+
+    #[test]
+    pub async fn exists_must_ignore_duplicate_values() {
+        let id = Uuid::nil();
+        let rule = Rule::exists("roles", roles::Column::Id, vec![id, Uuid::max(), id]);
+
+        assert_eq!(rule.values.len(), 2);
+    }
+}
@@ -0,0 +1,224 @@
+use std::borrow::Cow;
+
+use chrono_tz::Tz;
+use validator::ValidationError;
+
+use crate::i18n::Locale;
+
+/// Minimum length of a password.
+pub const PASSWORD_LENGTH: usize = 6;
+
+/// Maximum length of the type of a scope, such as `course`.
+pub const SCOPE_LENGTH: usize = 32;
+
+/// Maximum length of the code of a tenant, a single DNS label.
+pub const TENANT_LENGTH: usize = 63;
+
+// Every rule but `required` lets an empty value through, so a missing
+// field is reported once instead of by every rule on it.
+
+fn fail(code: &'static str) -> Result<(), ValidationError> {
+    Err(ValidationError::new(code))
+}
+
+pub fn required(value: &str) -> Result<(), ValidationError> {
+    match value.trim().is_empty() {
+        true => fail("required"),
+        false => Ok(()),
+    }
+}
+
+/// `required`, for lists.
+pub fn filled<T>(value: &[T]) -> Result<(), ValidationError> {
+    match value.is_empty() {
+        true => fail("required"),
+        false => Ok(()),
+    }
+}
+
+pub fn email(value: &str) -> Result<(), ValidationError> {
+    let value = value.trim();
+
+    match value.is_empty() || validator::validate_email(value) {
+        true => Ok(()),
+        false => fail("email"),
+    }
+}
+
+pub fn locale(value: &str) -> Result<(), ValidationError> {
+    let locales = Locale::ALL.map(|locale| locale.as_str()).join(", ");
+
+    match value.is_empty() || value.parse::<Locale>().is_ok() {
+        true => Ok(()),
+        false => {
+            let mut error = ValidationError::new("locale");
+
+            error.add_param(Cow::from("locales"), &locales);
+
+            Err(error)
+        }
+    }
+}
+
+pub fn timezone(value: &str) -> Result<(), ValidationError> {
+    match value.is_empty() || value.parse::<Tz>().is_ok() {
+        true => Ok(()),
+        false => fail("timezone"),
+    }
+}
+
+pub fn scope(value: &str) -> Result<(), ValidationError> {
+    let snake_case = value.starts_with(|c: char| c.is_ascii_lowercase())
+        && value.chars().all(|c| c.is_ascii_lowercase() || c == '_');
+
+    match value.is_empty() || (snake_case && value.len() <= SCOPE_LENGTH) {
+        true => Ok(()),
+        false => {
+            let mut error = ValidationError::new("scope");
+
+            error.add_param(Cow::from("max"), &SCOPE_LENGTH);
+
+            Err(error)
+        }
+    }
+}
+
+/// Usable as a subdomain: lowercase letters, digits and inner hyphens.
+pub fn subdomain(value: &str) -> Result<(), ValidationError> {
+    let label = value
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !value.starts_with('-')
+        && !value.ends_with('-');
+
+    match value.is_empty() || (label && value.len() <= TENANT_LENGTH) {
+        true => Ok(()),
+        false => {
+            let mut error = ValidationError::new("subdomain");
+
+            error.add_param(Cow::from("max"), &TENANT_LENGTH);
+
+            Err(error)
+        }
+    }
+}
+
+pub fn password_length(value: &str) -> Result<(), ValidationError> {
+    match value.is_empty() || value.chars().count() >= PASSWORD_LENGTH {
+        true => Ok(()),
+        false => {
+            let mut error = ValidationError::new("password_length");
+
+            error.add_param(Cow::from("min"), &PASSWORD_LENGTH);
+
+            Err(error)
+        }
+    }
+}
+
+pub fn password_uppercase(value: &str) -> Result<(), ValidationError> {
+    match value.is_empty() || value.chars().any(char::is_uppercase) {
+        true => Ok(()),
+        false => fail("password_uppercase"),
+    }
+}
+
+pub fn password_digit(value: &str) -> Result<(), ValidationError> {
+    match value.is_empty() || value.chars().any(|c| c.is_ascii_digit()) {
+        true => Ok(()),
+        false => fail("password_digit"),
+    }
+}
+
+pub fn password_special(value: &str) -> Result<(), ValidationError> {
+    match value.is_empty() || value.chars().any(|c| !c.is_alphanumeric()) {
+        true => Ok(()),
+        false => fail("password_special"),
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    #[test]
+    pub async fn required_must_ignore_whitespace() {
+        use super::required;
+
+        assert!(required("john").is_ok());
+        assert!(required("   ").is_err());
+    }
+
+    #[test]
+    pub async fn filled_must_reject_empty_lists() {
+        use super::filled;
+
+        assert!(filled(&[1]).is_ok());
+        assert!(filled::<i32>(&[]).is_err());
+    }
+
+    #[test]
+    pub async fn email_must_have_local_part_and_domain() {
+        use super::email;
+
+        assert!(email(" john@local.id ").is_ok());
+        assert!(email("john@").is_err());
+        assert!(email("@local.id").is_err());
+        assert!(email("john").is_err());
+    }
+
+    #[test]
+    pub async fn timezone_must_be_an_iana_name() {
+        use super::timezone;
+
+        assert!(timezone("Asia/Jakarta").is_ok());
+        assert!(timezone("UTC").is_ok());
+        assert!(timezone("GMT+7").is_err());
+    }
+
+    #[test]
+    pub async fn scope_must_be_a_short_snake_case_name() {
+        use super::scope;
+
+        assert!(scope("course").is_ok());
+        assert!(scope("course_section").is_ok());
+        assert!(scope("Course").is_err());
+        assert!(scope("_course").is_err());
+        assert!(scope("course-1").is_err());
+        assert!(scope(&"a".repeat(33)).is_err());
+    }
+
+    #[test]
+    pub async fn subdomain_must_be_a_single_dns_label() {
+        use super::subdomain;
+
+        assert!(subdomain("north-high").is_ok());
+        assert!(subdomain("school42").is_ok());
+        assert!(subdomain("North").is_err());
+        assert!(subdomain("-north").is_err());
+        assert!(subdomain("north.high").is_err());
+        assert!(subdomain(&"a".repeat(64)).is_err());
+    }
+
+    #[test]
+    pub async fn password_rules_must_leave_empty_to_required() {
+        use super::*;
+
+        assert!(password_length("").is_ok());
+        assert!(password_uppercase("").is_ok());
+        assert!(password_digit("").is_ok());
+        assert!(password_special("").is_ok());
+    }
+
+    #[test]
+    pub async fn password_rules_must_check_each_constraint() {
+        use super::*;
+
+        assert!(password_length("Pa1!").is_err());
+        assert!(password_uppercase("password1!").is_err());
+        assert!(password_digit("Password!").is_err());
+        assert!(password_special("Password1").is_err());
+        assert!(password_length("Password1!").is_ok());
+        assert!(password_uppercase("Password1!").is_ok());
+        assert!(password_digit("Password1!").is_ok());
+        assert!(password_special("Password1!").is_ok());
+    }
+}